@@ -18,6 +18,7 @@ use endpoint::{Endpoints, Endpoint};
 use page::PageEndpoint;
 use proxypac::ProxyPac;
 use parity_dapps::WebApp;
+use ethcore_rpc::{Dapp, DappSource};
 
 mod fs;
 
@@ -40,6 +41,30 @@ pub fn utils() -> Box<Endpoint> {
 pub fn all_endpoints(dapps_path: String) -> Endpoints {
 	// fetch fs dapps at first to avoid overwriting builtins
 	let mut pages = fs::local_endpoints(dapps_path);
+	pages.extend(builtin_endpoints());
+	pages
+}
+
+/// Dapps served by `all_endpoints`, sorted by id.
+pub fn all_dapps(dapps_path: String) -> Vec<Dapp> {
+	let builtin = builtin_endpoints();
+	let local = fs::local_endpoints(dapps_path);
+	let mut dapps: Vec<Dapp> = builtin.iter().map(|(id, endpoint)| (id, endpoint, DappSource::Builtin))
+		.chain(local.iter().filter(|&(id, _)| !builtin.contains_key(id)).map(|(id, endpoint)| (id, endpoint, DappSource::Local)))
+		.filter_map(|(id, endpoint, source)| endpoint.info().map(|info| Dapp {
+			id: id.clone(),
+			name: info.name.clone(),
+			version: info.version.clone(),
+			source: source,
+			content_hash: None,
+		}))
+		.collect();
+	dapps.sort_by(|a, b| a.id.cmp(&b.id));
+	dapps
+}
+
+fn builtin_endpoints() -> Endpoints {
+	let mut pages = Endpoints::new();
 	// Home page needs to be safe embed
 	// because we use Cross-Origin LocalStorage.
 	// TODO [ToDr] Account naming should be moved to parity.
@@ -68,7 +68,7 @@ use std::net::SocketAddr;
 use std::collections::HashMap;
 use jsonrpc_core::{IoHandler, IoDelegate};
 use router::auth::{Authorization, NoAuth, HttpBasicAuth};
-use ethcore_rpc::Extendable;
+use ethcore_rpc::{Extendable, DappsProvider, Dapp};
 
 static DAPPS_DOMAIN : &'static str = ".parity";
 
@@ -106,6 +106,27 @@ impl ServerBuilder {
 	}
 }
 
+/// Dapps served from given directory, listed by `parity_dappsList`.
+/// The directory is read once, like when the server is started.
+pub struct DappsList {
+	dapps: Vec<Dapp>,
+}
+
+impl DappsList {
+	/// Read dapps served for given local dapps directory.
+	pub fn new(dapps_path: String) -> Self {
+		DappsList {
+			dapps: apps::all_dapps(dapps_path),
+		}
+	}
+}
+
+impl DappsProvider for DappsList {
+	fn dapps(&self) -> Vec<Dapp> {
+		self.dapps.clone()
+	}
+}
+
 /// Webapps HTTP server.
 pub struct Server {
 	server: Option<hyper::server::Listening>,
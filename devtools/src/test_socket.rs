@@ -22,6 +22,7 @@ pub struct TestSocket {
 	pub write_buffer: Vec<u8>,
 	pub cursor: usize,
 	pub buf_size: usize,
+	pub write_calls: usize,
 }
 
 impl Default for TestSocket {
@@ -37,6 +38,7 @@ impl TestSocket {
 			write_buffer: vec![],
 			cursor: 0,
 			buf_size: 0,
+			write_calls: 0,
 		}
 	}
 
@@ -46,6 +48,7 @@ impl TestSocket {
 			write_buffer: vec![],
 			cursor: 0,
 			buf_size: buf_size,
+			write_calls: 0,
 		}
 	}
 
@@ -55,6 +58,7 @@ impl TestSocket {
 			write_buffer: vec![],
 			cursor: 0,
 			buf_size: 0,
+			write_calls: 0,
 		}
 	}
 }
@@ -78,6 +82,7 @@ impl Read for TestSocket {
 
 impl Write for TestSocket {
 	fn write(&mut self, buf: &[u8]) -> Result<usize> {
+		self.write_calls += 1;
 		if self.buf_size == 0 || buf.len() < self.buf_size {
 			self.write_buffer.extend(buf.iter().cloned());
 			Ok(buf.len())
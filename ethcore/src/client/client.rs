@@ -42,7 +42,7 @@ use client::{BlockID, TransactionID, UncleID, TraceId, ClientConfig, BlockChainC
 use client::Error as ClientError;
 use env_info::EnvInfo;
 use executive::{Executive, Executed, TransactOptions, contract_address};
-use receipt::LocalizedReceipt;
+use receipt::{Receipt, LocalizedReceipt};
 pub use blockchain::CacheSize as BlockChainCacheSize;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace;
@@ -87,6 +87,7 @@ pub struct Client<V = CanonVerifier> where V: Verifier {
 	chain: Arc<BlockChain>,
 	tracedb: Arc<TraceDB<BlockChain>>,
 	engine: Arc<Box<Engine>>,
+	spec_name: String,
 	state_db: Mutex<Box<JournalDB>>,
 	block_queue: BlockQueue,
 	report: RwLock<ClientReport>,
@@ -155,6 +156,7 @@ impl<V> Client<V> where V: Verifier {
 			state_db.commit(0, &spec.genesis_header().hash(), None).expect("Error commiting genesis state to state DB");
 		}
 
+		let spec_name = spec.name.clone();
 		let engine = Arc::new(spec.engine);
 
 		let block_queue = BlockQueue::new(config.queue, engine.clone(), message_channel.clone());
@@ -165,6 +167,7 @@ impl<V> Client<V> where V: Verifier {
 			chain: chain,
 			tracedb: tracedb,
 			engine: engine,
+			spec_name: spec_name,
 			state_db: Mutex::new(state_db),
 			block_queue: block_queue,
 			report: RwLock::new(Default::default()),
@@ -473,6 +476,36 @@ impl<V> Client<V> where V: Verifier {
 	}
 }
 
+/// Attach block and transaction information to a receipt. `prior_gas_used` is the cumulative
+/// gas used by transactions preceding `tx` in its block.
+fn localize_receipt(tx: LocalizedTransaction, receipt: Receipt, prior_gas_used: U256) -> LocalizedReceipt {
+	let block_hash = tx.block_hash.clone();
+	let block_number = tx.block_number;
+	let transaction_hash = tx.hash();
+	let transaction_index = tx.transaction_index;
+	LocalizedReceipt {
+		transaction_hash: transaction_hash.clone(),
+		transaction_index: transaction_index,
+		block_hash: block_hash.clone(),
+		block_number: block_number,
+		cumulative_gas_used: receipt.gas_used,
+		gas_used: receipt.gas_used - prior_gas_used,
+		contract_address: match tx.action {
+			Action::Call(_) => None,
+			Action::Create => Some(contract_address(&tx.sender().unwrap(), &tx.nonce))
+		},
+		logs: receipt.logs.into_iter().enumerate().map(|(i, log)| LocalizedLogEntry {
+			entry: log,
+			block_hash: block_hash.clone(),
+			block_number: block_number,
+			transaction_hash: transaction_hash.clone(),
+			transaction_index: transaction_index,
+			log_index: i
+		}).collect(),
+		log_bloom: receipt.log_bloom,
+	}
+}
+
 impl<V> BlockChainClient for Client<V> where V: Verifier {
 	fn call(&self, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, ExecutionError> {
 		let header = self.block_header(BlockID::Latest).unwrap();
@@ -516,6 +549,14 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 		&self.vm_factory
 	}
 
+	fn registrar_address(&self) -> Option<Address> {
+		self.engine.registrar()
+	}
+
+	fn spec_name(&self) -> String {
+		self.spec_name.clone()
+	}
+
 	fn block_header(&self, id: BlockID) -> Option<Bytes> {
 		Self::block_hash(&self.chain, id).and_then(|hash| self.chain.block(&hash).map(|bytes| BlockView::new(&bytes).rlp().at(0).as_raw().to_vec()))
 	}
@@ -586,10 +627,6 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 
 			match (t, self.chain.transaction_receipt(&address)) {
 				(Some(tx), Some(receipt)) => {
-					let block_hash = tx.block_hash.clone();
-					let block_number = tx.block_number.clone();
-					let transaction_hash = tx.hash();
-					let transaction_index = tx.transaction_index;
 					let prior_gas_used = match tx.transaction_index {
 						0 => U256::zero(),
 						i => {
@@ -598,32 +635,34 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 							prior_receipt.gas_used
 						}
 					};
-					Some(LocalizedReceipt {
-						transaction_hash: tx.hash(),
-						transaction_index: tx.transaction_index,
-						block_hash: tx.block_hash,
-						block_number: tx.block_number,
-						cumulative_gas_used: receipt.gas_used,
-						gas_used: receipt.gas_used - prior_gas_used,
-						contract_address: match tx.action {
-							Action::Call(_) => None,
-							Action::Create => Some(contract_address(&tx.sender().unwrap(), &tx.nonce))
-						},
-						logs: receipt.logs.into_iter().enumerate().map(|(i, log)| LocalizedLogEntry {
-							entry: log,
-							block_hash: block_hash.clone(),
-							block_number: block_number,
-							transaction_hash: transaction_hash.clone(),
-							transaction_index: transaction_index,
-							log_index: i
-						}).collect()
-					})
+					Some(localize_receipt(tx, receipt, prior_gas_used))
 				},
 				_ => None
 			}
 		})
 	}
 
+	fn localized_block_receipts(&self, id: BlockID) -> Option<Vec<LocalizedReceipt>> {
+		// the block is resolved once and everything else is read by its hash,
+		// so receipts can't mix blocks if the chain advances in the meantime.
+		let hash = match Self::block_hash(&self.chain, id) {
+			Some(hash) => hash,
+			None => return None,
+		};
+		let (block, receipts) = match (self.chain.block(&hash), self.chain.block_receipts(&hash)) {
+			(Some(block), Some(receipts)) => (block, receipts.receipts),
+			_ => return None,
+		};
+
+		let mut prior_gas_used = U256::zero();
+		Some(BlockView::new(&block).localized_transactions().into_iter().zip(receipts.into_iter()).map(|(tx, receipt)| {
+			let cumulative_gas_used = receipt.gas_used;
+			let receipt = localize_receipt(tx, receipt, prior_gas_used);
+			prior_gas_used = cumulative_gas_used;
+			receipt
+		}).collect())
+	}
+
 	fn tree_route(&self, from: &H256, to: &H256) -> Option<TreeRoute> {
 		match self.chain.is_known(from) && self.chain.is_known(to) {
 			true => Some(self.chain.tree_route(from.clone(), to.clone())),
@@ -720,6 +759,7 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 					.collect::<Vec<LocalizedLogEntry>>()
 
 			})
+			.take(filter.limit.unwrap_or(usize::max_value()))
 			.collect()
 	}
 
@@ -797,7 +837,7 @@ impl<V> BlockChainClient for Client<V> where V: Verifier {
 }
 
 impl<V> MiningBlockChainClient for Client<V> where V: Verifier {
-	fn prepare_open_block(&self, author: Address, gas_floor_target: U256, extra_data: Bytes) -> OpenBlock {
+	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = self.engine.deref().deref();
 		let h = self.chain.best_block_hash();
 
@@ -810,7 +850,7 @@ impl<V> MiningBlockChainClient for Client<V> where V: Verifier {
 			self.build_last_hashes(h.clone()),
 			self.dao_rescue_block_gas_limit(),
 			author,
-			gas_floor_target,
+			gas_range_target,
 			extra_data,
 		).expect("OpenBlock::new only fails if parent state root invalid; state root of best block's header is never invalid; qed");
 
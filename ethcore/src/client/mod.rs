@@ -131,6 +131,10 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get transaction receipt with given hash.
 	fn transaction_receipt(&self, id: TransactionID) -> Option<LocalizedReceipt>;
 
+	/// Get receipts of all transactions in given block, ordered by transaction index.
+	/// Returns `None` if the block is unknown.
+	fn localized_block_receipts(&self, id: BlockID) -> Option<Vec<LocalizedReceipt>>;
+
 	/// Get a tree route between `from` and `to`.
 	/// See `BlockChain::tree_route`.
 	fn tree_route(&self, from: &H256, to: &H256) -> Option<TreeRoute>;
@@ -175,6 +179,12 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns EvmFactory.
 	fn vm_factory(&self) -> &EvmFactory;
 
+	/// Returns address of the name registry contract, if the chain has one.
+	fn registrar_address(&self) -> Option<Address>;
+
+	/// Returns name of the chain specification the client runs.
+	fn spec_name(&self) -> String;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -225,6 +235,27 @@ pub trait BlockChainClient : Sync + Send {
 		}
 	}
 
+	/// Get the sorted priority fees paid in the last `sample_size` blocks.
+	/// The priority fee of a transaction is its gas price above the cheapest transaction of the same block.
+	fn priority_fee_statistics(&self, sample_size: usize) -> Vec<U256> {
+		let mut h = self.chain_info().best_block_hash;
+		let mut corpus = Vec::new();
+		for _ in 0..sample_size {
+			let block_bytes = self.block(BlockID::Hash(h)).expect("h is either the best_block_hash or an ancestor; qed");
+			let block = BlockView::new(&block_bytes);
+			let header = block.header_view();
+			if header.number() == 0 {
+				break;
+			}
+			let prices = block.transaction_views().iter().map(|t| t.gas_price()).collect::<Vec<_>>();
+			if let Some(floor) = prices.iter().min().cloned() {
+				corpus.extend(prices.into_iter().map(|price| price - floor));
+			}
+			h = header.parent_hash().clone();
+		}
+		corpus.sort();
+		corpus
+	}
 
 	/// Get `Some` gas limit of block 1_760_000, or `None` if chain is not yet that long.
 	fn dao_rescue_block_gas_limit(&self) -> Option<U256> {
@@ -236,6 +267,6 @@ pub trait BlockChainClient : Sync + Send {
 /// Extended client interface used for mining
 pub trait MiningBlockChainClient : BlockChainClient {
 	/// Returns OpenBlock prepared for closing.
-	fn prepare_open_block(&self, author: Address, gas_floor_target: U256, extra_data: Bytes)
+	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes)
 		-> OpenBlock;
 }
@@ -32,6 +32,7 @@ use miner::{Miner, MinerService};
 
 use block_queue::BlockQueueInfo;
 use block::OpenBlock;
+use views::BlockView;
 use executive::Executed;
 use error::{ExecutionError};
 use trace::LocalizedTrace;
@@ -63,10 +64,16 @@ pub struct TestBlockChainClient {
 	pub execution_result: RwLock<Option<Executed>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionID, LocalizedReceipt>>,
+	/// Logs returned by `logs`, in chain order.
+	pub logs: RwLock<Vec<LocalizedLogEntry>>,
 	/// Block queue size.
 	pub queue_size: AtomicUsize,
 	/// Miner
 	pub miner: Arc<Miner>,
+	/// Name registry address.
+	pub registrar: RwLock<Option<Address>>,
+	/// Chain specification name.
+	pub spec_name: RwLock<String>,
 }
 
 #[derive(Clone)]
@@ -104,8 +111,11 @@ impl TestBlockChainClient {
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
 			receipts: RwLock::new(HashMap::new()),
+			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
 			miner: Arc::new(Miner::default()),
+			registrar: RwLock::new(None),
+			spec_name: RwLock::new("Test".to_owned()),
 		};
 		client.add_blocks(1, EachBlockWith::Nothing); // add genesis block
 		client.genesis_hash = client.last_hash.read().unwrap().clone();
@@ -117,11 +127,26 @@ impl TestBlockChainClient {
 		self.receipts.write().unwrap().insert(id, receipt);
 	}
 
+	/// Set logs returned by `logs`. Filters only select them by block range and limit.
+	pub fn set_logs(&self, logs: Vec<LocalizedLogEntry>) {
+		*self.logs.write().unwrap() = logs;
+	}
+
 	/// Set the execution result.
 	pub fn set_execution_result(&self, result: Executed) {
 		*self.execution_result.write().unwrap() = Some(result);
 	}
 
+	/// Set the name registry address.
+	pub fn set_registrar(&self, registrar: Option<Address>) {
+		*self.registrar.write().unwrap() = registrar;
+	}
+
+	/// Set the chain specification name.
+	pub fn set_spec_name(&self, name: &str) {
+		*self.spec_name.write().unwrap() = name.to_owned();
+	}
+
 	/// Set the balance of account `address` to `balance`.
 	pub fn set_balance(&self, address: Address, balance: U256) {
 		self.balances.write().unwrap().insert(address, balance);
@@ -240,7 +265,7 @@ impl TestBlockChainClient {
 }
 
 impl MiningBlockChainClient for TestBlockChainClient {
-	fn prepare_open_block(&self, _author: Address, _gas_floor_target: U256, _extra_data: Bytes) -> OpenBlock {
+	fn prepare_open_block(&self, _author: Address, _gas_range_target: (U256, U256), _extra_data: Bytes) -> OpenBlock {
 		unimplemented!();
 	}
 }
@@ -285,24 +310,47 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
-	fn transaction(&self, _id: TransactionID) -> Option<LocalizedTransaction> {
-		unimplemented!();
+	fn transaction(&self, id: TransactionID) -> Option<LocalizedTransaction> {
+		match id {
+			TransactionID::Location(block, index) => self.block(block).and_then(|block| BlockView::new(&block).localized_transaction_at(index)),
+			TransactionID::Hash(_) => unimplemented!(),
+		}
 	}
 
-	fn uncle(&self, _id: UncleID) -> Option<BlockHeader> {
-		unimplemented!();
+	fn uncle(&self, id: UncleID) -> Option<BlockHeader> {
+		let index = id.1;
+		self.block(id.0).and_then(|block| BlockView::new(&block).uncle_at(index))
 	}
 
 	fn transaction_receipt(&self, id: TransactionID) -> Option<LocalizedReceipt> {
 		self.receipts.read().unwrap().get(&id).cloned()
 	}
 
+	fn localized_block_receipts(&self, id: BlockID) -> Option<Vec<LocalizedReceipt>> {
+		self.block_hash(id).and_then(|hash| self.blocks.read().unwrap().get(&hash).map(|_| hash)).map(|hash| {
+			let mut receipts: Vec<_> = self.receipts.read().unwrap().values()
+				.filter(|r| r.block_hash == hash)
+				.cloned()
+				.collect();
+			receipts.sort_by(|a, b| a.transaction_index.cmp(&b.transaction_index));
+			receipts
+		})
+	}
+
 	fn blocks_with_bloom(&self, _bloom: &H2048, _from_block: BlockID, _to_block: BlockID) -> Option<Vec<BlockNumber>> {
 		unimplemented!();
 	}
 
-	fn logs(&self, _filter: Filter) -> Vec<LocalizedLogEntry> {
-		unimplemented!();
+	fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry> {
+		let from = match filter.from_block {
+			BlockID::Number(number) => number,
+			_ => 0,
+		};
+		self.logs.read().unwrap().iter()
+			.filter(|log| log.block_number >= from)
+			.take(filter.limit.unwrap_or(usize::max_value()))
+			.cloned()
+			.collect()
 	}
 
 	fn last_hashes(&self) -> LastHashes {
@@ -466,6 +514,14 @@ impl BlockChainClient for TestBlockChainClient {
 		unimplemented!();
 	}
 
+	fn registrar_address(&self) -> Option<Address> {
+		self.registrar.read().unwrap().clone()
+	}
+
+	fn spec_name(&self) -> String {
+		self.spec_name.read().unwrap().clone()
+	}
+
 	fn filter_traces(&self, _filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		unimplemented!();
 	}
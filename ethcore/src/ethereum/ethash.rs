@@ -83,6 +83,13 @@ impl Ethash {
 
 impl Engine for Ethash {
 	fn name(&self) -> &str { "Ethash" }
+
+	fn registrar(&self) -> Option<Address> {
+		match self.ethash_params.registrar.is_zero() {
+			true => None,
+			false => Some(self.ethash_params.registrar.clone()),
+		}
+	}
 	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
 	// Two fields - mix
 	fn seal_fields(&self) -> usize { 2 }
@@ -112,15 +119,18 @@ impl Engine for Ethash {
 		}
 	}
 
-	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256) {
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_range_target: (U256, U256)) {
+		let (gas_floor_target, gas_ceil_target) = gas_range_target;
 		header.difficulty = self.calculate_difficuty(header, parent);
 		header.gas_limit = {
 			let gas_limit = parent.gas_limit;
 			let bound_divisor = self.ethash_params.gas_limit_bound_divisor;
 			if gas_limit < gas_floor_target {
 				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else if gas_limit > gas_ceil_target {
+				max(gas_ceil_target, gas_limit - gas_limit / bound_divisor + 1.into())
 			} else {
-				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into() + (header.gas_used * 6.into() / 5.into()) / bound_divisor)
+				min(gas_ceil_target, max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into() + (header.gas_used * 6.into() / 5.into()) / bound_divisor))
 			}
 		};
 		header.note_dirty();
@@ -323,7 +333,7 @@ mod tests {
 		spec.ensure_db_good(db.as_hashdb_mut());
 		let last_hashes = vec![genesis_header.hash()];
 		let vm_factory = Default::default();
-		let b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), 3141562.into(), vec![]).unwrap();
+		let b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
 		let b = b.close();
 		assert_eq!(b.state().balance(&Address::zero()), U256::from_str("4563918244f40000").unwrap());
 	}
@@ -338,7 +348,7 @@ mod tests {
 		spec.ensure_db_good(db.as_hashdb_mut());
 		let last_hashes = vec![genesis_header.hash()];
 		let vm_factory = Default::default();
-		let mut b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), 3141562.into(), vec![]).unwrap();
+		let mut b = OpenBlock::new(engine.deref(), &vm_factory, false, db, &genesis_header, last_hashes, None, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
 		let mut uncle = Header::new();
 		let uncle_author = address_from_hex("ef2d6d194084c2de36e0dabfce45d046b37d1106");
 		uncle.author = uncle_author.clone();
@@ -536,5 +546,24 @@ mod tests {
 		assert_eq!(Ethash::difficulty_to_boundary(&U256::from(32)), H256::from_str("0800000000000000000000000000000000000000000000000000000000000000").unwrap());
 	}
 
+	#[test]
+	fn gas_limit_follows_range_target() {
+		let engine = new_morden().engine;
+		let mut parent = Header::default();
+		parent.set_gas_limit(3141592.into());
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_timestamp(10);
+
+		engine.populate_from_parent(&mut header, &parent, (4700000.into(), 6000000.into()));
+		assert_eq!(header.gas_limit, 3144658.into());
+
+		engine.populate_from_parent(&mut header, &parent, (5000.into(), 2000000.into()));
+		assert_eq!(header.gas_limit, 3138526.into());
+
+		engine.populate_from_parent(&mut header, &parent, (3141592.into(), 3141592.into()));
+		assert_eq!(header.gas_limit, 3141592.into());
+	}
+
 	// TODO: difficulty test
 }
@@ -40,6 +40,12 @@ pub struct Filter {
 	/// If None, match all.
 	/// If specified, log must contain one of these topics.
 	pub topics: [Option<Vec<H256>>; 4],
+
+	/// Logs limit.
+	///
+	/// If None, return all logs.
+	/// If specified, only the first `limit` matching logs, in chain order, are returned.
+	pub limit: Option<usize>,
 }
 
 impl Clone for Filter {
@@ -53,7 +59,8 @@ impl Clone for Filter {
 			from_block: self.from_block.clone(),
 			to_block: self.to_block.clone(),
 			address: self.address.clone(),
-			topics: topics
+			topics: topics,
+			limit: self.limit,
 		}
 	}
 }
@@ -111,7 +118,8 @@ mod tests {
 			from_block: BlockID::Earliest,
 			to_block: BlockID::Latest,
 			address: None,
-			topics: [None, None, None, None]
+			topics: [None, None, None, None],
+			limit: None,
 		};
 
 		let possibilities = none_filter.bloom_possibilities();
@@ -129,7 +137,8 @@ mod tests {
 			topics: [
 				Some(vec![H256::from_str("ff74e91598aed6ae5d2fdcf8b24cd2c7be49a0808112a305069355b7160f23f9").unwrap()]),
 				None, None, None
-			]
+			],
+			limit: None,
 		};
 
 		let possibilities = filter.bloom_possibilities();
@@ -146,7 +155,8 @@ mod tests {
 				Some(vec![H256::from_str("ff74e91598aed6ae5d2fdcf8b24cd2c7be49a0808112a305069355b7160f23f9").unwrap()]),
 				Some(vec![H256::from_str("ff74e91598aed6ae5d2fdcf8b24cd2c7be49a0808112a305069355b7160f23f9").unwrap()]),
 				None, None
-			]
+			],
+			limit: None,
 		};
 
 		let possibilities = filter.bloom_possibilities();
@@ -173,7 +183,8 @@ mod tests {
 				]),
 				Some(vec![H256::from_str("ff74e91598aed6ae5d2fdcf8b24cd2c7be49a0808112a305069355b7160f23f9").unwrap()]),
 				None
-			]
+			],
+			limit: None,
 		};
 
 		// number of possibilites should be equal 2 * 2 * 2 * 1 = 8
@@ -192,7 +203,8 @@ mod tests {
 				Some(vec![H256::from_str("ff74e91598aed6ae5d2fdcf8b24cd2c7be49a0808112a305069355b7160f23f9").unwrap()]),
 				Some(vec![H256::from_str("ff74e91598aed6ae5d2fdcf8b24cd2c7be49a0808112a305069355b7160f23fa").unwrap()]),
 				None, None
-			]
+			],
+			limit: None,
 		};
 
 		let entry0 = LogEntry {
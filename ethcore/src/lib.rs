@@ -143,3 +143,4 @@ mod json_tests;
 pub use types::*;
 pub use evm::get_info;
 pub use executive::contract_address;
+pub use verification::{verify_extra_data, verify_gas_range_target, MAX_GAS_LIMIT};
@@ -16,15 +16,22 @@
 
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use util::numbers::U256;
 use util::hash::H256;
 
+/// Time in seconds after which hashrate of a miner that stopped reporting is no longer counted.
+pub const HASHRATE_EXPIRY_SEC: u64 = 10;
+
+/// Latest hashrate reported by each miner, with the time it was reported.
+pub type Hashrates = HashMap<H256, (U256, Instant)>;
+
 /// External miner interface.
 pub trait ExternalMinerService: Send + Sync {
 	/// Submit hashrate for given miner.
 	fn submit_hashrate(&self, hashrate: U256, id: H256);
 
-	/// Total hashrate.
+	/// Total hashrate of miners which reported recently.
 	fn hashrate(&self) -> U256;
 
 	/// Returns true if external miner is mining.
@@ -33,42 +40,67 @@ pub trait ExternalMinerService: Send + Sync {
 
 /// External Miner.
 pub struct ExternalMiner {
-	hashrates: Arc<RwLock<HashMap<H256, U256>>>,
+	hashrates: Arc<RwLock<Hashrates>>,
+	expiry: Duration,
 }
 
 impl Default for ExternalMiner {
 	fn default() -> Self {
-		ExternalMiner {
-			hashrates: Arc::new(RwLock::new(HashMap::new())),
-		}
+		ExternalMiner::new(Arc::new(RwLock::new(HashMap::new())))
 	}
 }
 
 impl ExternalMiner {
 	/// Creates new external miner with prefilled hashrates.
-	pub fn new(hashrates: Arc<RwLock<HashMap<H256, U256>>>) -> Self {
+	pub fn new(hashrates: Arc<RwLock<Hashrates>>) -> Self {
+		ExternalMiner::with_expiry(hashrates, Duration::from_secs(HASHRATE_EXPIRY_SEC))
+	}
+
+	/// Creates new external miner with prefilled hashrates, counting each miner's hashrate
+	/// for `expiry` after its last report.
+	pub fn with_expiry(hashrates: Arc<RwLock<Hashrates>>, expiry: Duration) -> Self {
 		ExternalMiner {
-			hashrates: hashrates
+			hashrates: hashrates,
+			expiry: expiry,
+		}
+	}
+
+	/// Removes hashrates of miners which didn't report within the expiry window.
+	fn prune(&self, hashrates: &mut Hashrates) {
+		let expired: Vec<H256> = hashrates.iter()
+			.filter(|&(_, &(_, reported))| reported.elapsed() > self.expiry)
+			.map(|(id, _)| id.clone())
+			.collect();
+		for id in expired {
+			hashrates.remove(&id);
 		}
 	}
 }
 
 impl ExternalMinerService for ExternalMiner {
 	fn submit_hashrate(&self, hashrate: U256, id: H256) {
-		self.hashrates.write().unwrap().insert(id, hashrate);
+		self.hashrates.write().unwrap().insert(id, (hashrate, Instant::now()));
 	}
 
 	fn hashrate(&self) -> U256 {
-		self.hashrates.read().unwrap().iter().fold(U256::from(0), |sum, (_, v)| sum + *v)
+		let mut hashrates = self.hashrates.write().unwrap();
+		self.prune(&mut hashrates);
+		hashrates.values().fold(U256::from(0), |sum, &(v, _)| sum + v)
 	}
 
 	fn is_mining(&self) -> bool {
-		!self.hashrates.read().unwrap().is_empty()
+		let mut hashrates = self.hashrates.write().unwrap();
+		self.prune(&mut hashrates);
+		!hashrates.is_empty()
 	}
 }
 
 #[cfg(test)]
 mod tests {
+	use std::thread;
+	use std::time::Duration;
+	use std::collections::HashMap;
+	use std::sync::{Arc, RwLock};
 	use super::*;
 	use util::{H256, U256};
 
@@ -105,4 +137,29 @@ mod tests {
 		// then
 		assert_eq!(m.hashrate(), U256::from(35));
 	}
+
+	#[test]
+	fn should_expire_hashrate_of_miner_that_stopped_reporting() {
+		// given
+		let m = ExternalMiner::with_expiry(Arc::new(RwLock::new(HashMap::new())), Duration::from_millis(200));
+		m.submit_hashrate(U256::from(10), H256::from(1));
+		m.submit_hashrate(U256::from(20), H256::from(2));
+		assert_eq!(m.hashrate(), U256::from(30));
+
+		// when
+		thread::sleep(Duration::from_millis(120));
+		m.submit_hashrate(U256::from(25), H256::from(2));
+		thread::sleep(Duration::from_millis(120));
+
+		// then
+		assert_eq!(m.hashrate(), U256::from(25));
+		assert_eq!(m.is_mining(), true);
+
+		// when
+		thread::sleep(Duration::from_millis(250));
+
+		// then
+		assert_eq!(m.hashrate(), U256::from(0));
+		assert_eq!(m.is_mining(), false);
+	}
 }
@@ -27,6 +27,7 @@ use transaction::SignedTransaction;
 use receipt::{Receipt};
 use spec::Spec;
 use engine::Engine;
+use verification::{verify_extra_data, verify_gas_range_target, MAX_GAS_LIMIT};
 use miner::{MinerService, MinerStatus, TransactionQueue, AccountDetails, TransactionImportResult, TransactionOrigin};
 
 /// Keeps track of transactions using priority queue and holds currently mined block.
@@ -40,6 +41,7 @@ pub struct Miner {
 	sealing_enabled: AtomicBool,
 	sealing_block_last_request: Mutex<u64>,
 	gas_floor_target: RwLock<U256>,
+	gas_ceil_target: RwLock<U256>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
 	spec: Spec,
@@ -49,17 +51,19 @@ pub struct Miner {
 
 impl Default for Miner {
 	fn default() -> Miner {
+		let spec = Spec::new_test();
 		Miner {
 			transaction_queue: Mutex::new(TransactionQueue::new()),
 			force_sealing: false,
 			sealing_enabled: AtomicBool::new(false),
 			sealing_block_last_request: Mutex::new(0),
 			sealing_work: Mutex::new(UsingQueue::new(5)),
-			gas_floor_target: RwLock::new(U256::zero()),
+			gas_floor_target: RwLock::new(spec.engine.params().min_gas_limit),
+			gas_ceil_target: RwLock::new(MAX_GAS_LIMIT.into()),
 			author: RwLock::new(Address::default()),
 			extra_data: RwLock::new(Vec::new()),
 			accounts: None,
-			spec: Spec::new_test(),
+			spec: spec,
 		}
 	}
 }
@@ -73,7 +77,8 @@ impl Miner {
 			sealing_enabled: AtomicBool::new(force_sealing),
 			sealing_block_last_request: Mutex::new(0),
 			sealing_work: Mutex::new(UsingQueue::new(5)),
-			gas_floor_target: RwLock::new(U256::zero()),
+			gas_floor_target: RwLock::new(spec.engine.params().min_gas_limit),
+			gas_ceil_target: RwLock::new(MAX_GAS_LIMIT.into()),
 			author: RwLock::new(Address::default()),
 			extra_data: RwLock::new(Vec::new()),
 			accounts: None,
@@ -89,7 +94,8 @@ impl Miner {
 			sealing_enabled: AtomicBool::new(force_sealing),
 			sealing_block_last_request: Mutex::new(0),
 			sealing_work: Mutex::new(UsingQueue::new(5)),
-			gas_floor_target: RwLock::new(U256::zero()),
+			gas_floor_target: RwLock::new(spec.engine.params().min_gas_limit),
+			gas_ceil_target: RwLock::new(MAX_GAS_LIMIT.into()),
 			author: RwLock::new(Address::default()),
 			extra_data: RwLock::new(Vec::new()),
 			accounts: Some(accounts),
@@ -131,7 +137,7 @@ impl Miner {
 					trace!(target: "miner", "No existing work - making new block");
 					chain.prepare_open_block(
 						self.author(),
-						self.gas_floor_target(),
+						(self.gas_floor_target(), self.gas_ceil_target()),
 						self.extra_data()
 					)
 				}
@@ -217,6 +223,65 @@ impl Miner {
 		queue.set_gas_limit(gas_limit);
 	}
 
+	/// Block with top transactions from the queue applied on top of the best block.
+	/// Used to query the pending state when there is no sealing work. It's neither sealed nor kept.
+	fn simulated_pending_block(&self, chain: &MiningBlockChainClient) -> ClosedBlock {
+		let transactions = {self.transaction_queue.lock().unwrap().top_transactions()};
+		let mut open_block = chain.prepare_open_block(
+			self.author(),
+			(self.gas_floor_target(), self.gas_ceil_target()),
+			self.extra_data()
+		);
+		for tx in transactions {
+			let hash = tx.hash();
+			if let Err(e) = open_block.push_transaction(tx, None) {
+				trace!(target: "miner", "Skipping transaction in simulated pending block: {:?}, Error: {:?}", hash, e);
+			}
+		}
+		open_block.close()
+	}
+
+	/// Executes `t` on top of the state of `work` without altering it.
+	fn call_on_block(&self, chain: &MiningBlockChainClient, work: &ClosedBlock, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, ExecutionError> {
+		let block = work.block();
+
+		// TODO: merge this code with client.rs's fn call somwhow.
+		let header = block.header();
+		let last_hashes = chain.last_hashes();
+		let env_info = EnvInfo {
+			number: header.number(),
+			author: *header.author(),
+			timestamp: header.timestamp(),
+			difficulty: *header.difficulty(),
+			last_hashes: last_hashes,
+			gas_used: U256::zero(),
+			gas_limit: U256::max_value(),
+			dao_rescue_block_gas_limit: chain.dao_rescue_block_gas_limit(),
+		};
+		// that's just a copy of the state.
+		let mut state = block.state().clone();
+		let sender = try!(t.sender().map_err(|e| {
+			let message = format!("Transaction malformed: {:?}", e);
+			ExecutionError::TransactionMalformed(message)
+		}));
+		let balance = state.balance(&sender);
+		let needed_balance = t.value + t.gas * t.gas_price;
+		if balance < needed_balance {
+			// give the sender a sufficient balance
+			state.add_balance(&sender, &(needed_balance - balance));
+		}
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let mut ret = Executive::new(&mut state, &env_info, self.engine(), chain.vm_factory()).transact(t, options);
+
+		// TODO gav move this into Executive.
+		if analytics.state_diffing {
+			if let Ok(ref mut x) = ret {
+				x.state_diff = Some(state.diff_from(block.state().clone()));
+			}
+		}
+		ret
+	}
+
 	/// Returns true if we had to prepare new pending block
 	fn enable_and_prepare_sealing(&self, chain: &MiningBlockChainClient) -> bool {
 		trace!(target: "miner", "enable_and_prepare_sealing: entering");
@@ -258,51 +323,18 @@ impl MinerService for Miner {
 	}
 
 	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, ExecutionError> {
-		let sealing_work = self.sealing_work.lock().unwrap();
-		match sealing_work.peek_last_ref() {
-			Some(work) => {
-				let block = work.block();
-
-				// TODO: merge this code with client.rs's fn call somwhow.
-				let header = block.header();
-				let last_hashes = chain.last_hashes();
-				let env_info = EnvInfo {
-					number: header.number(),
-					author: *header.author(),
-					timestamp: header.timestamp(),
-					difficulty: *header.difficulty(),
-					last_hashes: last_hashes,
-					gas_used: U256::zero(),
-					gas_limit: U256::max_value(),
-					dao_rescue_block_gas_limit: chain.dao_rescue_block_gas_limit(),
-				};
-				// that's just a copy of the state.
-				let mut state = block.state().clone();
-				let sender = try!(t.sender().map_err(|e| {
-					let message = format!("Transaction malformed: {:?}", e);
-					ExecutionError::TransactionMalformed(message)
-				}));
-				let balance = state.balance(&sender);
-				let needed_balance = t.value + t.gas * t.gas_price;
-				if balance < needed_balance {
-					// give the sender a sufficient balance
-					state.add_balance(&sender, &(needed_balance - balance));
-				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-				let mut ret = Executive::new(&mut state, &env_info, self.engine(), chain.vm_factory()).transact(t, options);
-
-				// TODO gav move this into Executive.
-				if analytics.state_diffing {
-					if let Ok(ref mut x) = ret {
-						x.state_diff = Some(state.diff_from(block.state().clone()));
-					}
-				}
-				ret
-			},
-			None => {
-				chain.call(t, analytics)
+		{
+			// the lock is held for the whole call, so the pending state can't change under it.
+			let sealing_work = self.sealing_work.lock().unwrap();
+			if let Some(work) = sealing_work.peek_last_ref() {
+				return self.call_on_block(chain, work, t, analytics);
 			}
 		}
+		if self.transaction_queue.lock().unwrap().status().pending == 0 {
+			return chain.call(t, analytics);
+		}
+		let block = self.simulated_pending_block(chain);
+		self.call_on_block(chain, &block, t, analytics)
 	}
 
 	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> U256 {
@@ -335,13 +367,27 @@ impl MinerService for Miner {
 		*self.author.write().unwrap() = author;
 	}
 
-	fn set_extra_data(&self, extra_data: Bytes) {
+	fn set_extra_data(&self, extra_data: Bytes) -> Result<(), BlockError> {
+		try!(verify_extra_data(&extra_data, self.engine().maximum_extra_data_size()));
 		*self.extra_data.write().unwrap() = extra_data;
+		Ok(())
 	}
 
-	/// Set the gas limit we wish to target when sealing a new block.
-	fn set_gas_floor_target(&self, target: U256) {
+	/// Set the lowest gas limit we wish to target when sealing a new block.
+	fn set_gas_floor_target(&self, target: U256) -> Result<(), BlockError> {
+		try!(verify_gas_range_target((target, self.gas_ceil_target()), self.engine().params().min_gas_limit));
 		*self.gas_floor_target.write().unwrap() = target;
+		// drop pending work so that the next block is opened against the new target.
+		self.sealing_work.lock().unwrap().reset();
+		Ok(())
+	}
+
+	/// Set the highest gas limit we wish to target when sealing a new block.
+	fn set_gas_ceil_target(&self, target: U256) -> Result<(), BlockError> {
+		try!(verify_gas_range_target((self.gas_floor_target(), target), self.engine().params().min_gas_limit));
+		*self.gas_ceil_target.write().unwrap() = target;
+		self.sealing_work.lock().unwrap().reset();
+		Ok(())
 	}
 
 	fn set_minimal_gas_price(&self, min_gas_price: U256) {
@@ -379,11 +425,16 @@ impl MinerService for Miner {
 		self.extra_data.read().unwrap().clone()
 	}
 
-	/// Get the gas limit we wish to target when sealing a new block.
+	/// Get the lowest gas limit we wish to target when sealing a new block.
 	fn gas_floor_target(&self) -> U256 {
 		*self.gas_floor_target.read().unwrap()
 	}
 
+	/// Get the highest gas limit we wish to target when sealing a new block.
+	fn gas_ceil_target(&self) -> U256 {
+		*self.gas_ceil_target.read().unwrap()
+	}
+
 	fn import_transactions<T>(&self, chain: &MiningBlockChainClient, transactions: Vec<SignedTransaction>, fetch_account: T) ->
 		Vec<Result<TransactionImportResult, Error>>
 		where T: Fn(&Address) -> AccountDetails {
@@ -467,6 +518,11 @@ impl MinerService for Miner {
 		queue.top_transactions()
 	}
 
+	fn future_transactions(&self) -> Vec<SignedTransaction> {
+		let queue = self.transaction_queue.lock().unwrap();
+		queue.future_transactions()
+	}
+
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		let queue = self.transaction_queue.lock().unwrap();
 		// TODO: should only use the sealing_work when it's current (it could be an old block)
@@ -617,6 +673,7 @@ mod tests {
 	use util::*;
 	use client::{TestBlockChainClient, EachBlockWith};
 	use block::*;
+	use verification::MAX_GAS_LIMIT;
 
 	// TODO [ToDr] To uncomment` when TestBlockChainClient can actually return a ClosedBlock.
 	#[ignore]
@@ -652,4 +709,25 @@ mod tests {
 		// solution to original work submitted.
 		assert!(miner.submit_seal(&client, res.unwrap(), vec![]).is_ok());
 	}
+	#[test]
+	fn should_reject_invalid_gas_range_target() {
+		// given
+		let miner = Miner::default();
+		assert!(miner.set_gas_floor_target(3141592.into()).is_ok());
+		assert!(miner.set_gas_ceil_target(4700000.into()).is_ok());
+
+		// when
+		let floor_above_ceil = miner.set_gas_floor_target(4700001.into());
+		let ceil_below_floor = miner.set_gas_ceil_target(3141591.into());
+		let floor_below_minimum = miner.set_gas_floor_target(0.into());
+		let ceil_above_maximum = miner.set_gas_ceil_target(U256::from(MAX_GAS_LIMIT) + 1.into());
+
+		// then
+		assert!(floor_above_ceil.is_err());
+		assert!(ceil_below_floor.is_err());
+		assert!(floor_below_minimum.is_err());
+		assert!(ceil_above_maximum.is_err());
+		assert_eq!(miner.gas_floor_target(), 3141592.into());
+		assert_eq!(miner.gas_ceil_target(), 4700000.into());
+	}
 }
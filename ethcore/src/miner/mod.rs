@@ -47,14 +47,14 @@ mod transaction_queue;
 
 pub use self::transaction_queue::{TransactionQueue, AccountDetails, TransactionImportResult, TransactionOrigin};
 pub use self::miner::{Miner};
-pub use self::external::{ExternalMiner, ExternalMinerService};
+pub use self::external::{ExternalMiner, ExternalMinerService, Hashrates, HASHRATE_EXPIRY_SEC};
 
 use std::collections::BTreeMap;
 use util::{H256, U256, Address, Bytes};
 use client::{MiningBlockChainClient, Executed, CallAnalytics};
 use block::ClosedBlock;
 use receipt::Receipt;
-use error::{Error, ExecutionError};
+use error::{Error, ExecutionError, BlockError};
 use transaction::SignedTransaction;
 
 /// Miner client API
@@ -73,7 +73,8 @@ pub trait MinerService : Send + Sync {
 	fn extra_data(&self) -> Bytes;
 
 	/// Set the extra_data that we will seal blocks with.
-	fn set_extra_data(&self, extra_data: Bytes);
+	/// Fails if the extra data doesn't fit in a block header.
+	fn set_extra_data(&self, extra_data: Bytes) -> Result<(), BlockError>;
 
 	/// Get current minimal gas price for transactions accepted to queue.
 	fn minimal_gas_price(&self) -> U256;
@@ -81,11 +82,19 @@ pub trait MinerService : Send + Sync {
 	/// Set minimal gas price of transaction to be accepted for mining.
 	fn set_minimal_gas_price(&self, min_gas_price: U256);
 
-	/// Get the gas limit we wish to target when sealing a new block.
+	/// Get the lowest gas limit we wish to target when sealing a new block.
 	fn gas_floor_target(&self) -> U256;
 
-	/// Set the gas limit we wish to target when sealing a new block.
-	fn set_gas_floor_target(&self, target: U256);
+	/// Get the highest gas limit we wish to target when sealing a new block.
+	fn gas_ceil_target(&self) -> U256;
+
+	/// Set the lowest gas limit we wish to target when sealing a new block.
+	/// Fails if the target is below the protocol minimum or above the ceiling target.
+	fn set_gas_floor_target(&self, target: U256) -> Result<(), BlockError>;
+
+	/// Set the highest gas limit we wish to target when sealing a new block.
+	/// Fails if the target is above the protocol maximum or below the floor target.
+	fn set_gas_ceil_target(&self, target: U256) -> Result<(), BlockError>;
 
 	/// Get current transactions limit in queue.
 	fn transactions_limit(&self) -> usize;
@@ -132,6 +141,9 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of all pending transactions.
 	fn pending_transactions(&self) -> Vec<SignedTransaction>;
 
+	/// Get a list of transactions waiting for transactions with lower nonces.
+	fn future_transactions(&self) -> Vec<SignedTransaction>;
+
 	/// Get a list of all pending receipts.
 	fn pending_receipts(&self) -> BTreeMap<H256, Receipt>;
 
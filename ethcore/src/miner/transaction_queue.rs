@@ -384,7 +384,8 @@ impl TransactionQueue {
 		self.current.set_limit(limit);
 		self.future.set_limit(limit);
 		// And ensure the limits
-		self.current.enforce_limit(&mut self.by_hash);
+		let removed = self.current.enforce_limit(&mut self.by_hash);
+		self.update_last_nonces(&removed);
 		self.future.enforce_limit(&mut self.by_hash);
 	}
 
@@ -573,6 +574,15 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Returns all transactions from future (waiting for transactions with lower nonces), ordered by priority.
+	pub fn future_transactions(&self) -> Vec<SignedTransaction> {
+		self.future.by_priority
+			.iter()
+			.map(|t| self.by_hash.get(&t.hash).expect("All transactions in `current` and `future` are always included in `by_hash`"))
+			.map(|t| t.transaction.clone())
+			.collect()
+	}
+
 	/// Returns hashes of all transactions from current, ordered by priority.
 	pub fn pending_hashes(&self) -> Vec<H256> {
 		self.current.by_priority
@@ -1142,6 +1152,22 @@ mod test {
 		assert_eq!(top.len(), 2);
 	}
 
+	#[test]
+	fn should_return_future_transactions() {
+		// given
+		let mut txq = TransactionQueue::new();
+
+		let (tx, tx2) = new_txs(U256::from(2));
+
+		// when
+		txq.add(tx.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+		txq.add(tx2.clone(), &default_nonce, TransactionOrigin::External).unwrap();
+
+		// then
+		assert_eq!(txq.top_transactions(), vec![tx]);
+		assert_eq!(txq.future_transactions(), vec![tx2]);
+	}
+
 	#[test]
 	fn should_put_transaction_to_futures_if_gap_detected() {
 		// given
@@ -1317,6 +1343,38 @@ mod test {
 		assert_eq!(txq.last_nonce(&sender), Some(nonce));
 	}
 
+	#[test]
+	fn should_evict_lowest_priority_transactions_when_lowering_the_limit() {
+		// given
+		let mut txq = TransactionQueue::new();
+		let signed = |secret: &Secret, nonce: U256, gas_price: u64| {
+			let mut tx = new_unsigned_tx(nonce);
+			tx.gas_price = U256::from(gas_price);
+			tx.sign(secret)
+		};
+		let keypair = KeyPair::create().unwrap();
+		let nonce = default_nonce_val();
+		let tx1 = signed(keypair.secret(), nonce, 3);
+		let tx2 = signed(keypair.secret(), nonce + U256::one(), 3);
+		let tx3 = signed(KeyPair::create().unwrap().secret(), nonce, 2);
+		let tx4 = signed(KeyPair::create().unwrap().secret(), nonce, 1);
+		let sender = tx1.sender().unwrap();
+		for tx in vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4] {
+			txq.add(tx, &default_nonce, TransactionOrigin::External).unwrap();
+		}
+		assert_eq!(txq.status().pending, 4);
+
+		// when
+		txq.set_limit(2);
+
+		// then
+		// the higher nonce of the same sender goes last despite its gas price, then the cheapest transaction
+		assert_eq!(txq.limit(), 2);
+		assert_eq!(txq.status().pending, 2);
+		assert_eq!(txq.top_transactions(), vec![tx1.clone(), tx3]);
+		assert_eq!(txq.last_nonce(&sender), Some(tx1.nonce));
+	}
+
 	#[test]
 	fn should_limit_future_transactions() {
 		let mut txq = TransactionQueue::with_limit(1);
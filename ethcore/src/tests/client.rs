@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockID};
+use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockID, TransactionID};
 use block::IsBlock;
 use tests::helpers::*;
 use common::*;
@@ -39,6 +39,14 @@ fn returns_state_root_basic() {
 	assert!(client.state_data(&state_root).is_some());
 }
 
+#[test]
+fn returns_spec_name() {
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+
+	assert_eq!(client.spec_name(), get_test_spec().name);
+}
+
 #[test]
 fn imports_good_block() {
 	let dir = RandomTempPath::new();
@@ -125,6 +133,31 @@ fn can_generate_gas_price_statistics() {
 	assert_eq!(s, vec_into![0, 1, 3, 5, 7, 9, 11, 13, 15]);
 }
 
+#[test]
+#[cfg_attr(feature="dev", allow(useless_vec))]
+fn can_collect_block_receipts() {
+	let client_result = generate_dummy_client_with_data(2, 3, &vec_into![0]);
+	let client = client_result.reference();
+
+	let receipts = client.localized_block_receipts(BlockID::Number(2)).unwrap();
+	assert_eq!(receipts.len(), 3);
+	for (i, receipt) in receipts.iter().enumerate() {
+		assert_eq!(Some(receipt.clone()), client.transaction_receipt(TransactionID::Location(BlockID::Number(2), i)));
+	}
+	let total_gas_used = receipts.iter().fold(U256::zero(), |total, r| total + r.gas_used);
+	assert_eq!(total_gas_used, receipts[2].cumulative_gas_used);
+
+	assert_eq!(client.localized_block_receipts(BlockID::Number(3)), None);
+}
+
+#[test]
+fn block_receipts_of_empty_block_are_empty() {
+	let client_result = generate_dummy_client(2);
+	let client = client_result.reference();
+
+	assert_eq!(client.localized_block_receipts(BlockID::Latest), Some(vec![]));
+}
+
 #[test]
 fn can_handle_long_fork() {
 	let client_result = generate_dummy_client(1200);
@@ -150,7 +183,7 @@ fn can_mine() {
 	let client_result = get_test_client_with_blocks(vec![dummy_blocks[0].clone()]);
 	let client = client_result.reference();
 
-	let b = client.prepare_open_block(Address::default(), 31415926.into(), vec![]).close();
+	let b = client.prepare_open_block(Address::default(), (31415926.into(), 314159260.into()), vec![]).close();
 
 	assert_eq!(*b.block().header().parent_hash(), BlockView::new(&dummy_blocks[0]).header_view().sha3());
 }
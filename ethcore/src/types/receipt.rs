@@ -99,6 +99,8 @@ pub struct LocalizedReceipt {
 	pub contract_address: Option<Address>,
 	/// Logs
 	pub logs: Vec<LocalizedLogEntry>,
+	/// The OR-wide combination of all logs' blooms for this transaction.
+	pub log_bloom: LogBloom,
 }
 
 #[test]
@@ -187,9 +187,33 @@ fn verify_header(header: &Header, engine: &Engine) -> Result<(), Error> {
 	if header.gas_limit < min_gas_limit {
 		return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas_limit), max: None, found: header.gas_limit })));
 	}
-	let maximum_extra_data_size = engine.maximum_extra_data_size();
-	if header.number != 0 && header.extra_data.len() > maximum_extra_data_size {
-		return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(maximum_extra_data_size), found: header.extra_data.len() })));
+	if header.number != 0 {
+		try!(verify_extra_data(&header.extra_data, engine.maximum_extra_data_size()));
+	}
+	Ok(())
+}
+
+/// Check that block extra data is at most `maximum_extra_data_size` bytes long.
+pub fn verify_extra_data(extra_data: &[u8], maximum_extra_data_size: usize) -> Result<(), BlockError> {
+	if extra_data.len() > maximum_extra_data_size {
+		return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(maximum_extra_data_size), found: extra_data.len() }));
+	}
+	Ok(())
+}
+
+/// Largest gas limit a block header may carry (2^63 - 1).
+pub const MAX_GAS_LIMIT: u64 = 0x7fff_ffff_ffff_ffff;
+
+/// Check that a gas floor and ceiling target for sealed blocks are within protocol bounds
+/// and that the floor does not lie above the ceiling.
+pub fn verify_gas_range_target(gas_range_target: (U256, U256), min_gas_limit: U256) -> Result<(), BlockError> {
+	let (floor, ceil) = gas_range_target;
+	let max_gas_limit = U256::from(MAX_GAS_LIMIT);
+	if floor < min_gas_limit || floor > ceil {
+		return Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas_limit), max: Some(min(ceil, max_gas_limit)), found: floor }));
+	}
+	if ceil > max_gas_limit {
+		return Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(floor), max: Some(max_gas_limit), found: ceil }));
 	}
 	Ok(())
 }
@@ -482,4 +506,20 @@ mod tests {
 
 		// TODO: some additional uncle checks
 	}
+
+	#[test]
+	fn test_verify_gas_range_target() {
+		let min_gas_limit = U256::from(5000);
+		let max_gas_limit = U256::from(MAX_GAS_LIMIT);
+
+		assert!(verify_gas_range_target((5000.into(), 5000.into()), min_gas_limit).is_ok());
+		assert!(verify_gas_range_target((3141562.into(), max_gas_limit), min_gas_limit).is_ok());
+
+		assert_eq!(verify_gas_range_target((4999.into(), 3141562.into()), min_gas_limit),
+			Err(InvalidGasLimit(OutOfBounds { min: Some(min_gas_limit), max: Some(3141562.into()), found: 4999.into() })));
+		assert_eq!(verify_gas_range_target((3141563.into(), 3141562.into()), min_gas_limit),
+			Err(InvalidGasLimit(OutOfBounds { min: Some(min_gas_limit), max: Some(3141562.into()), found: 3141563.into() })));
+		assert_eq!(verify_gas_range_target((3141562.into(), max_gas_limit + 1.into()), min_gas_limit),
+			Err(InvalidGasLimit(OutOfBounds { min: Some(3141562.into()), max: Some(max_gas_limit), found: max_gas_limit + 1.into() })));
+	}
 }
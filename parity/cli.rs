@@ -79,6 +79,14 @@ Networking Options:
                            These nodes will always have a reserved slot on top
                            of the normal maximum peers.
   --reserved-only          Connect only to reserved nodes.
+  --handshake-timeout MS   Drop peers which do not complete the RLPx handshake
+                           within MS milliseconds [default: 5000].
+  --write-timeout MS       Drop peers which do not read data sent to them
+                           within MS milliseconds [default: 60000].
+  --tcp-keepalive SPEC     Enable TCP keepalive on peer connections. SPEC is
+                           IDLE,INTERVAL,PROBES: seconds before the first
+                           probe, seconds between probes and number of
+                           unanswered probes, e.g. 60,10,5.
 
 API and Console Options:
   --jsonrpc-off            Disable the JSON-RPC API server.
@@ -86,13 +94,49 @@ API and Console Options:
                            [default: 8545].
   --jsonrpc-interface IP   Specify the hostname portion of the JSONRPC API
                            server, IP should be an interface's IP address, or
-                           all (all interfaces) or local [default: local].
+                           all (all interfaces) or local. A comma-delimited
+                           list binds to each of them [default: local].
   --jsonrpc-cors URL       Specify CORS header for JSON-RPC API responses.
   --jsonrpc-apis APIS      Specify the APIs available through the JSONRPC
                            interface. APIS is a comma-delimited list of API
                            name. Possible name are web3, eth, net, personal,
-                           ethcore, ethcore_set, traces.
+                           ethcore, ethcore_set, traces, txpool, shh.
                            [default: web3,eth,net,ethcore,personal,traces].
+  --jsonrpc-cache-size ITEMS  Specify the number of immutable JSON-RPC results
+                           (e.g. old blocks and receipts) kept in memory.
+                           0 disables the cache [default: 1024].
+  --jsonrpc-max-executions NUM  Specify the number of EVM executions (eth_call,
+                           eth_estimateGas, trace_call) JSON-RPC requests
+                           may run at the same time. Requests over the limit
+                           are rejected if no execution finishes within a
+                           second. 0 means no limit [default: 8].
+  --jsonrpc-tip-percentile PCT  Percentile of priority fees paid in recent
+                           blocks suggested by eth_maxPriorityFeePerGas. The
+                           priority fee of a transaction is its gas price
+                           above the cheapest transaction in the same block
+                           [default: 60].
+  --jsonrpc-timeouts TIMEOUTS  Specify request timeouts per API namespace for
+                           JSON-RPC over HTTP and IPC. TIMEOUTS is a
+                           comma-delimited list of NAMESPACE=MS entries, e.g.
+                           eth=5000,trace=60000. 0 means no timeout.
+  --jsonrpc-slow-thresholds THRESHOLDS  Log a warning for JSON-RPC requests
+                           taking longer than given time, per API namespace.
+                           THRESHOLDS is a comma-delimited list of
+                           NAMESPACE=MS entries, e.g. eth=1000. 0 means no
+                           warning.
+  --jsonrpc-remote-accounts ACCOUNTS  Specify accounts listed by eth_accounts
+                           when JSON-RPC over HTTP listens on an interface
+                           other than localhost. The filter applies to all
+                           HTTP clients of that interface, local ones
+                           included. ACCOUNTS is all, none or a
+                           comma-delimited list of addresses. IPC always
+                           lists all accounts [default: none].
+  --jsonrpc-fetch-timeout MS  Specify the time allowed for fetching remote
+                           content in parity_hashContent [default: 10000].
+  --jsonrpc-fetch-max-size BYTES  Specify the maximal size of remote content
+                           fetched by parity_hashContent [default: 5242880].
+  --solc PATH              Specify the solc binary used by eth_compileSolidity.
+                           Compilation is not available unless set.
 
   --ipc-off                Disable JSON-RPC over IPC service.
   --ipc-path PATH          Specify custom path for JSON-RPC over IPC service
@@ -121,6 +165,9 @@ API and Console Options:
                            [default: 8180].
   --signer-path PATH       Specify directory where Signer UIs tokens should
                            be stored. [default: $HOME/.parity/signer]
+  --signer-cors URL        Specify origins (comma-separated) other than the
+                           Signer itself allowed to connect to Trusted Signer.
+                           Use * to allow any origin and null for local files.
   --no-token               By default a new system UI security token will be
                            output on start up. This will prevent it.
 
@@ -255,6 +302,9 @@ pub struct Args {
 	pub flag_node_key: Option<String>,
 	pub flag_reserved_peers: Option<String>,
 	pub flag_reserved_only: bool,
+	pub flag_handshake_timeout: u64,
+	pub flag_write_timeout: u64,
+	pub flag_tcp_keepalive: Option<String>,
 	pub flag_cache_pref_size: usize,
 	pub flag_cache_max_size: usize,
 	pub flag_queue_max_size: usize,
@@ -263,6 +313,15 @@ pub struct Args {
 	pub flag_jsonrpc_port: u16,
 	pub flag_jsonrpc_cors: Option<String>,
 	pub flag_jsonrpc_apis: String,
+	pub flag_jsonrpc_cache_size: usize,
+	pub flag_jsonrpc_max_executions: usize,
+	pub flag_jsonrpc_tip_percentile: usize,
+	pub flag_jsonrpc_timeouts: Option<String>,
+	pub flag_jsonrpc_slow_thresholds: Option<String>,
+	pub flag_jsonrpc_remote_accounts: String,
+	pub flag_jsonrpc_fetch_timeout: u64,
+	pub flag_jsonrpc_fetch_max_size: u64,
+	pub flag_solc: Option<String>,
 	pub flag_ipc_off: bool,
 	pub flag_ipc_path: String,
 	pub flag_ipc_apis: String,
@@ -275,6 +334,7 @@ pub struct Args {
 	pub flag_signer: bool,
 	pub flag_signer_port: u16,
 	pub flag_signer_path: String,
+	pub flag_signer_cors: Option<String>,
 	pub flag_no_token: bool,
 	pub flag_force_sealing: bool,
 	pub flag_author: String,
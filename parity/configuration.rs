@@ -122,9 +122,8 @@ impl Configuration {
 
 	pub fn extra_data(&self) -> Bytes {
 		match self.args.flag_extradata.as_ref().or(self.args.flag_extra_data.as_ref()) {
-			Some(ref x) if x.len() <= 32 => x.as_bytes().to_owned(),
+			Some(ref x) => x.as_bytes().to_owned(),
 			None => version_data(),
-			Some(ref x) => { die!("{}: Extra data must be at most 32 characters.", x); }
 		}
 	}
 
@@ -205,6 +204,9 @@ impl Configuration {
 		net_path.push("network");
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = self.init_reserved_nodes();
+		ret.handshake_timeout = self.args.flag_handshake_timeout;
+		ret.write_timeout = self.args.flag_write_timeout;
+		ret.tcp_keepalive = self.tcp_keepalive();
 
 		if self.args.flag_reserved_only {
 			ret.non_reserved_mode = ::util::network::NonReservedPeerMode::Deny;
@@ -212,6 +214,20 @@ impl Configuration {
 		ret
 	}
 
+	pub fn tcp_keepalive(&self) -> Option<KeepaliveConfig> {
+		self.args.flag_tcp_keepalive.as_ref().map(|spec| {
+			let mut values = spec.split(',').map(|v| v.parse::<u32>().ok());
+			match (values.next(), values.next(), values.next(), values.next()) {
+				(Some(Some(idle)), Some(Some(interval)), Some(Some(probes)), None) => KeepaliveConfig {
+					idle: idle,
+					interval: interval,
+					probes: probes,
+				},
+				_ => die!("{}: Invalid TCP keepalive given. Expected IDLE,INTERVAL,PROBES.", spec),
+			}
+		})
+	}
+
 	pub fn find_best_db(&self, spec: &Spec) -> Option<journaldb::Algorithm> {
 		let mut ret = None;
 		let mut latest_era = None;
@@ -338,6 +354,38 @@ impl Configuration {
 		cors.map_or_else(Vec::new, |c| c.split(',').map(|s| s.to_owned()).collect())
 	}
 
+	pub fn signer_cors(&self) -> Option<Vec<String>> {
+		self.args.flag_signer_cors.as_ref().map(|c| c.split(',').map(|s| s.to_owned()).collect())
+	}
+
+	pub fn rpc_timeouts(&self) -> Vec<(String, u64)> {
+		self.args.flag_jsonrpc_timeouts.as_ref().map_or_else(Vec::new, |timeouts| Self::namespace_durations(timeouts, "timeout"))
+	}
+
+	pub fn rpc_slow_thresholds(&self) -> Vec<(String, u64)> {
+		self.args.flag_jsonrpc_slow_thresholds.as_ref().map_or_else(Vec::new, |thresholds| Self::namespace_durations(thresholds, "slow request threshold"))
+	}
+
+	fn namespace_durations(entries: &str, setting: &str) -> Vec<(String, u64)> {
+		entries.split(',').map(|entry| {
+			let mut parts = entry.splitn(2, '=');
+			match (parts.next(), parts.next().and_then(|ms| ms.parse().ok())) {
+				(Some(namespace), Some(ms)) if !namespace.is_empty() => (namespace.to_owned(), ms),
+				_ => die!("{}: Invalid JSON-RPC {}. Expected NAMESPACE=MS.", entry, setting),
+			}
+		}).collect()
+	}
+
+	pub fn rpc_remote_accounts(&self) -> Option<Vec<Address>> {
+		match self.args.flag_jsonrpc_remote_accounts.as_str() {
+			"all" => None,
+			"none" => Some(Vec::new()),
+			accounts => Some(accounts.split(',').map(|a| Address::from_str(clean_0x(a)).unwrap_or_else(|_| {
+				die!("{}: Invalid address for --jsonrpc-remote-accounts. Must be 40 hex characters.", a)
+			})).collect()),
+		}
+	}
+
 	fn geth_ipc_path(&self) -> String {
 		if cfg!(windows) {
 			r"\\.\pipe\geth.ipc".to_owned()
@@ -358,9 +406,18 @@ impl Configuration {
 			enabled: !(self.args.flag_ipcdisable || self.args.flag_ipc_off),
 			socket_addr: self.ipc_path(),
 			apis: self.args.flag_ipcapi.clone().unwrap_or(self.args.flag_ipc_apis.clone()),
+			timeouts: self.rpc_timeouts(),
+			slow_thresholds: self.rpc_slow_thresholds(),
 		}
 	}
 
+	/// Whether `shh` is among the APIs of an enabled JSON-RPC or IPC endpoint.
+	pub fn shh_enabled(&self) -> bool {
+		let has_shh = |apis: &str| apis.split(',').any(|api| api == "shh");
+		let ipc = self.ipc_settings();
+		(!self.args.flag_jsonrpc_off && has_shh(&self.rpc_apis())) || (ipc.enabled && has_shh(&ipc.apis))
+	}
+
 	pub fn network_settings(&self) -> NetworkSettings {
 		if self.args.flag_jsonrpc { println!("WARNING: Flag -j/--json-rpc is deprecated. JSON-RPC is now on by default. Ignoring."); }
 		NetworkSettings {
@@ -468,6 +525,116 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_parse_handshake_timeout() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let custom = parse(&["parity", "--handshake-timeout", "2000"]);
+
+		// then
+		assert_eq!(default.args.flag_handshake_timeout, 5000);
+		assert_eq!(custom.args.flag_handshake_timeout, 2000);
+	}
+
+	#[test]
+	fn should_parse_write_timeout() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let custom = parse(&["parity", "--write-timeout", "10000"]);
+
+		// then
+		assert_eq!(default.args.flag_write_timeout, 60000);
+		assert_eq!(custom.args.flag_write_timeout, 10000);
+	}
+
+	#[test]
+	fn should_parse_rpc_timeouts() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let custom = parse(&["parity", "--jsonrpc-timeouts", "eth=5000,trace=0"]);
+
+		// then
+		assert!(default.rpc_timeouts().is_empty());
+		assert_eq!(custom.rpc_timeouts(), vec![("eth".to_owned(), 5000), ("trace".to_owned(), 0)]);
+	}
+
+	#[test]
+	fn should_parse_rpc_slow_thresholds() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let custom = parse(&["parity", "--jsonrpc-slow-thresholds", "eth=1000,net=0"]);
+
+		// then
+		assert!(default.rpc_slow_thresholds().is_empty());
+		assert_eq!(custom.rpc_slow_thresholds(), vec![("eth".to_owned(), 1000), ("net".to_owned(), 0)]);
+	}
+
+	#[test]
+	fn should_parse_tcp_keepalive() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let custom = parse(&["parity", "--tcp-keepalive", "30,5,3"]);
+
+		// then
+		assert_eq!(default.tcp_keepalive(), None);
+		assert_eq!(custom.tcp_keepalive(), Some(KeepaliveConfig { idle: 30, interval: 5, probes: 3 }));
+	}
+
+	#[test]
+	fn should_enable_shh_only_when_requested() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let rpc = parse(&["parity", "--jsonrpc-apis", "web3,shh"]);
+		let ipc = parse(&["parity", "--ipc-apis", "shh"]);
+		let rpc_off = parse(&["parity", "--jsonrpc-apis", "shh", "--jsonrpc-off"]);
+
+		// then
+		assert!(!default.shh_enabled());
+		assert!(rpc.shh_enabled());
+		assert!(ipc.shh_enabled());
+		assert!(!rpc_off.shh_enabled());
+	}
+
+	#[test]
+	fn should_parse_signer_cors() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let custom = parse(&["parity", "--signer-cors", "http://parity.io,null"]);
+
+		// then
+		assert_eq!(default.signer_cors(), None);
+		assert_eq!(custom.signer_cors(), Some(vec!["http://parity.io".to_owned(), "null".to_owned()]));
+	}
+
+	#[test]
+	fn should_parse_rpc_remote_accounts() {
+		// given
+
+		// when
+		let default = parse(&["parity"]);
+		let all = parse(&["parity", "--jsonrpc-remote-accounts", "all"]);
+		let custom = parse(&["parity", "--jsonrpc-remote-accounts", "0x0000000000000000000000000000000000000001,0000000000000000000000000000000000000002"]);
+
+		// then
+		assert_eq!(default.rpc_remote_accounts(), Some(vec![]));
+		assert_eq!(all.rpc_remote_accounts(), None);
+		assert_eq!(custom.rpc_remote_accounts(), Some(vec![Address::from(1), Address::from(2)]));
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
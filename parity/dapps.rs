@@ -67,6 +67,20 @@ pub fn new(configuration: Configuration, deps: Dependencies) -> Option<WebappSer
 	Some(setup_dapps_server(deps, configuration.dapps_path, &addr, auth))
 }
 
+/// Dapps listed by `parity_dappsList`, `None` if the dapps server is disabled.
+#[cfg(feature = "dapps")]
+pub fn list(configuration: &Configuration) -> Option<Arc<rpc_apis::DappsProvider>> {
+	if !configuration.enabled {
+		return None;
+	}
+	Some(Arc::new(::ethcore_dapps::DappsList::new(configuration.dapps_path.clone())))
+}
+
+#[cfg(not(feature = "dapps"))]
+pub fn list(_configuration: &Configuration) -> Option<Arc<rpc_apis::DappsProvider>> {
+	None
+}
+
 #[cfg(not(feature = "dapps"))]
 pub fn setup_dapps_server(
 	_deps: Dependencies,
@@ -87,7 +101,7 @@ pub fn setup_dapps_server(
 	use ethcore_dapps as dapps;
 
 	let server = dapps::ServerBuilder::new(dapps_path);
-	let server = rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::UnsafeContext);
+	let server = rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::UnsafeContext, rpc_apis::AccountsFilter::All);
 	let start_result = match auth {
 		None => {
 			server.start_unsecure_http(url)
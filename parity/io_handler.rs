@@ -50,7 +50,7 @@ impl IoHandler<NetSyncMessage> for ClientIoHandler {
 				info!("Starting network");
 				if let Some(network) = self.network.upgrade() {
 					network.start().unwrap_or_else(|e| warn!("Error starting network: {:?}", e));
-					EthSync::register(&*network, self.sync.clone()).unwrap_or_else(|e| warn!("Error registering eth protocol handler: {}", e));
+					EthSync::register(&network, self.sync.clone()).unwrap_or_else(|e| warn!("Error registering eth protocol handler: {}", e));
 				}
 			},
 			NetworkIoMessage::User(SyncMessage::StopNetwork) => {
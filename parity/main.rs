@@ -27,6 +27,7 @@ extern crate rustc_serialize;
 extern crate ethcore_util as util;
 extern crate ethcore;
 extern crate ethsync;
+extern crate ethcore_whisper as whisper;
 #[macro_use]
 extern crate log as rlog;
 extern crate env_logger;
@@ -87,6 +88,7 @@ use ethcore::error::{Error, ImportError};
 use ethcore::service::ClientService;
 use ethcore::spec::Spec;
 use ethsync::EthSync;
+use whisper::{WhisperHost, DEFAULT_WORK};
 use ethcore::miner::{Miner, MinerService, ExternalMiner};
 use migration::migrate;
 use informant::Informant;
@@ -199,8 +201,8 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 	// Miner
 	let miner = Miner::with_accounts(conf.args.flag_force_sealing, conf.spec(), account_service.clone());
 	miner.set_author(conf.author());
-	miner.set_gas_floor_target(conf.gas_floor_target());
-	miner.set_extra_data(conf.extra_data());
+	miner.set_gas_floor_target(conf.gas_floor_target()).unwrap_or_else(|e| die!("Invalid gas floor target: {}", e));
+	miner.set_extra_data(conf.extra_data()).unwrap_or_else(|e| die!("Invalid extra data: {}", e));
 	miner.set_minimal_gas_price(conf.gas_price());
 	miner.set_transactions_limit(conf.args.flag_tx_limit);
 
@@ -217,19 +219,47 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 
 	// Sync
 	let sync = EthSync::new(sync_config, client.clone());
-	EthSync::register(&*service.network(), sync.clone()).unwrap_or_else(|e| die_with_error("Error registering eth protocol handler", UtilError::from(e).into()));
+	EthSync::register(&service.network(), sync.clone()).unwrap_or_else(|e| die_with_error("Error registering eth protocol handler", UtilError::from(e).into()));
+
+	// Whisper, only when some endpoint exposes the shh API
+	let whisper = if conf.shh_enabled() {
+		let whisper = WhisperHost::new(DEFAULT_WORK);
+		WhisperHost::register(&service.network(), whisper.clone()).unwrap_or_else(|e| die_with_error("Error registering shh protocol handler", UtilError::from(e).into()));
+		Some(whisper)
+	} else {
+		None
+	};
+
+	let dapps_conf = dapps::Configuration {
+		enabled: !conf.args.flag_dapps_off,
+		interface: conf.args.flag_dapps_interface.clone(),
+		port: conf.args.flag_dapps_port,
+		user: conf.args.flag_dapps_user.clone(),
+		pass: conf.args.flag_dapps_pass.clone(),
+		dapps_path: conf.directories().dapps,
+	};
 
 	let deps_for_rpc_apis = Arc::new(rpc_apis::Dependencies {
 		signer_port: conf.signer_port(),
 		signer_queue: Arc::new(rpc_apis::ConfirmationsQueue::default()),
 		client: client.clone(),
 		sync: sync.clone(),
+		whisper: whisper.clone(),
 		secret_store: account_service.clone(),
 		miner: miner.clone(),
 		external_miner: external_miner.clone(),
 		logger: logger.clone(),
 		settings: network_settings.clone(),
 		allow_pending_receipt_query: !conf.args.flag_geth,
+		result_cache: Arc::new(rpc_apis::ResultCache::new(conf.args.flag_jsonrpc_cache_size)),
+		execution_limit: Arc::new(rpc_apis::ExecutionLimit::new(conf.args.flag_jsonrpc_max_executions)),
+		tip_percentile: conf.args.flag_jsonrpc_tip_percentile,
+		dapps: dapps::list(&dapps_conf),
+		fetch_limits: rpc_apis::FetchLimits {
+			timeout_ms: conf.args.flag_jsonrpc_fetch_timeout,
+			max_size: conf.args.flag_jsonrpc_fetch_max_size,
+		},
+		solc_path: conf.args.flag_solc.clone(),
 		net_service: service.network(),
 	});
 
@@ -245,6 +275,9 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 		port: network_settings.rpc_port,
 		apis: conf.rpc_apis(),
 		cors: conf.rpc_cors(),
+		timeouts: conf.rpc_timeouts(),
+		slow_thresholds: conf.rpc_slow_thresholds(),
+		remote_accounts: conf.rpc_remote_accounts(),
 	}, &dependencies);
 
 	// setup ipc rpc
@@ -252,14 +285,7 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 	debug!("IPC: {}", conf.ipc_settings());
 
 	if conf.args.flag_webapp { println!("WARNING: Flag -w/--webapp is deprecated. Dapps server is now on by default. Ignoring."); }
-	let dapps_server = dapps::new(dapps::Configuration {
-		enabled: !conf.args.flag_dapps_off,
-		interface: conf.args.flag_dapps_interface.clone(),
-		port: conf.args.flag_dapps_port,
-		user: conf.args.flag_dapps_user.clone(),
-		pass: conf.args.flag_dapps_pass.clone(),
-		dapps_path: conf.directories().dapps,
-	}, dapps::Dependencies {
+	let dapps_server = dapps::new(dapps_conf, dapps::Dependencies {
 		panic_handler: panic_handler.clone(),
 		apis: deps_for_rpc_apis.clone(),
 	});
@@ -269,6 +295,7 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 		enabled: deps_for_rpc_apis.signer_port.is_some(),
 		port: conf.args.flag_signer_port,
 		signer_path: conf.directories().signer,
+		cors: conf.signer_cors(),
 	}, signer::Dependencies {
 		panic_handler: panic_handler.clone(),
 		apis: deps_for_rpc_apis.clone(),
@@ -290,6 +317,9 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig)
 
 	// Handle exit
 	wait_for_exit(panic_handler, rpc_server, dapps_server, signer_server);
+
+	// Tell peers we are quitting and give queued packets a chance to be sent
+	service.network().stop().unwrap_or_else(|e| warn!("Error stopping network: {:?}", e));
 }
 
 fn flush_stdout() {
@@ -310,17 +340,10 @@ fn execute_export(conf: Configuration) {
 
 	let spec = conf.spec();
 	let net_settings = NetworkConfiguration {
-		config_path: None,
-		listen_address: None,
-		public_address: None,
-		udp_port: None,
 		nat_enabled: false,
 		discovery_enabled: false,
-		boot_nodes: Vec::new(),
-		use_secret: None,
 		ideal_peers: 0,
-		reserved_nodes: Vec::new(),
-		non_reserved_mode: ::util::network::NonReservedPeerMode::Accept,
+		..NetworkConfiguration::new()
 	};
 	let client_config = conf.client_config(&spec);
 
@@ -382,17 +405,10 @@ fn execute_import(conf: Configuration) {
 
 	let spec = conf.spec();
 	let net_settings = NetworkConfiguration {
-		config_path: None,
-		listen_address: None,
-		public_address: None,
-		udp_port: None,
 		nat_enabled: false,
 		discovery_enabled: false,
-		boot_nodes: Vec::new(),
-		use_secret: None,
 		ideal_peers: 0,
-		reserved_nodes: Vec::new(),
-		non_reserved_mode: ::util::network::NonReservedPeerMode::Accept,
+		..NetworkConfiguration::new()
 	};
 	let client_config = conf.client_config(&spec);
 
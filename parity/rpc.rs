@@ -19,17 +19,19 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::net::SocketAddr;
 use util::panics::PanicHandler;
+use util::Address;
 use die::*;
-use jsonipc;
 use rpc_apis;
 use std::fmt;
 
 #[cfg(feature = "rpc")]
-pub use ethcore_rpc::Server as RpcServer;
+pub use ethcore_rpc::{MultiHttpServer as RpcServer, IpcServer};
 #[cfg(feature = "rpc")]
-use ethcore_rpc::{RpcServerError, RpcServer as Server};
+use ethcore_rpc::{StartError, StartErrorKind, RpcServer as Server, AccountsFilter};
 #[cfg(not(feature = "rpc"))]
 pub struct RpcServer;
+#[cfg(not(feature = "rpc"))]
+pub struct IpcServer;
 
 pub struct HttpConfiguration {
 	pub enabled: bool,
@@ -37,12 +39,18 @@ pub struct HttpConfiguration {
 	pub port: u16,
 	pub apis: String,
 	pub cors: Vec<String>,
+	pub timeouts: Vec<(String, u64)>,
+	pub slow_thresholds: Vec<(String, u64)>,
+	/// Accounts visible to clients when not listening on a loopback interface. `None` shows all accounts.
+	pub remote_accounts: Option<Vec<Address>>,
 }
 
 pub struct IpcConfiguration {
 	pub enabled: bool,
 	pub socket_addr: String,
 	pub apis: String,
+	pub timeouts: Vec<(String, u64)>,
+	pub slow_thresholds: Vec<(String, u64)>,
 }
 
 impl fmt::Display for IpcConfiguration {
@@ -66,30 +74,54 @@ pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Option<RpcServe
 		return None;
 	}
 
-	let interface = match conf.interface.as_str() {
-		"all" => "0.0.0.0",
-		"local" => "127.0.0.1",
-		x => x,
-	};
 	let apis = conf.apis.split(',').collect();
-	let url = format!("{}:{}", interface, conf.port);
-	let addr = SocketAddr::from_str(&url).unwrap_or_else(|_| die!("{}: Invalid JSONRPC listen host/port given.", url));
+	let addrs: Vec<SocketAddr> = conf.interface.split(',').map(|interface| {
+		let interface = match interface {
+			"all" => "0.0.0.0",
+			"local" => "127.0.0.1",
+			x => x,
+		};
+		let url = format!("{}:{}", interface, conf.port);
+		SocketAddr::from_str(&url).unwrap_or_else(|_| die!("{}: Invalid JSONRPC listen host/port given.", url))
+	}).collect();
+
+	// all interfaces share the handler, so the filter applies if any of them is not a loopback one
+	let is_loopback = |addr: &SocketAddr| match *addr {
+		SocketAddr::V4(ref a) => a.ip().is_loopback(),
+		SocketAddr::V6(ref a) => a.ip().is_loopback(),
+	};
+	let remote_accounts = match addrs.iter().all(is_loopback) {
+		true => None,
+		false => conf.remote_accounts,
+	};
+	if let Some(ref accounts) = remote_accounts {
+		warn!("JSON-RPC over HTTP is not bound to localhost, eth_accounts lists {} account(s) to all its clients. See --jsonrpc-remote-accounts.", accounts.len());
+	}
 
-	Some(setup_http_rpc_server(deps, &addr, conf.cors, apis))
+	Some(setup_http_rpc_server(deps, &addrs, conf.cors, apis, &conf.timeouts, &conf.slow_thresholds, remote_accounts))
 }
 
-fn setup_rpc_server(apis: Vec<&str>, deps: &Dependencies) -> Server {
+fn setup_rpc_server(apis: Vec<&str>, timeouts: &[(String, u64)], slow_thresholds: &[(String, u64)], accounts: AccountsFilter, deps: &Dependencies) -> Server {
 	let apis = rpc_apis::from_str(apis);
 	let server = Server::new();
-	rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::List(apis))
+	for &(ref namespace, timeout) in timeouts {
+		server.set_request_timeout(namespace, timeout);
+	}
+	for &(ref namespace, threshold) in slow_thresholds {
+		server.set_slow_request_threshold(namespace, threshold);
+	}
+	rpc_apis::setup_rpc(server, deps.apis.clone(), rpc_apis::ApiSet::List(apis), accounts)
 }
 
 #[cfg(not(feature = "rpc"))]
 pub fn setup_http_rpc_server(
 	_deps: &Dependencies,
-	_url: &SocketAddr,
+	_addrs: &[SocketAddr],
 	_cors_domain: Vec<String>,
 	_apis: Vec<&str>,
+	_timeouts: &[(String, u64)],
+	_slow_thresholds: &[(String, u64)],
+	_remote_accounts: Option<Vec<Address>>,
 ) -> ! {
 	die!("Your Parity version has been compiled without JSON-RPC support.")
 }
@@ -97,42 +129,64 @@ pub fn setup_http_rpc_server(
 #[cfg(feature = "rpc")]
 pub fn setup_http_rpc_server(
 	dependencies: &Dependencies,
-	url: &SocketAddr,
+	addrs: &[SocketAddr],
 	cors_domains: Vec<String>,
 	apis: Vec<&str>,
+	timeouts: &[(String, u64)],
+	slow_thresholds: &[(String, u64)],
+	remote_accounts: Option<Vec<Address>>,
 ) -> RpcServer {
-	let server = setup_rpc_server(apis, dependencies);
-	let start_result = server.start_http(url, cors_domains);
-	let ph = dependencies.panic_handler.clone();
+	let accounts = remote_accounts.map_or(AccountsFilter::All, AccountsFilter::Only);
+	let server = setup_rpc_server(apis, timeouts, slow_thresholds, accounts, dependencies);
+	let start_result = server.start_http_multi(addrs, cors_domains);
 	match start_result {
-		Err(RpcServerError::IoError(err)) => die_with_io_error("RPC", err),
-		Err(e) => die!("RPC: {:?}", e),
-		Ok(server) => {
-			server.set_panic_handler(move || {
-				ph.notify_all("Panic in RPC thread.".to_owned());
-			});
-			server
+		Err(e) => die_with_start_error("RPC", e),
+		Ok(servers) => {
+			for server in servers.server().into_iter().flat_map(|s| s.iter()) {
+				let ph = dependencies.panic_handler.clone();
+				server.set_panic_handler(move || {
+					ph.notify_all("Panic in RPC thread.".to_owned());
+				});
+			}
+			servers
 		},
 	}
 }
 
 #[cfg(not(feature = "rpc"))]
-pub fn setup_ipc_rpc_server(_dependencies: &Dependencies, _addr: &str, _apis: Vec<&str>) -> ! {
+pub fn setup_ipc_rpc_server(_dependencies: &Dependencies, _addr: &str, _apis: Vec<&str>, _timeouts: &[(String, u64)], _slow_thresholds: &[(String, u64)]) -> ! {
 	die!("Your Parity version has been compiled without JSON-RPC support.")
 }
 
-pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Option<jsonipc::Server> {
+pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Option<IpcServer> {
 	if !conf.enabled { return None; }
 	let apis = conf.apis.split(',').collect();
-	Some(setup_ipc_rpc_server(deps, &conf.socket_addr, apis))
+	Some(setup_ipc_rpc_server(deps, &conf.socket_addr, apis, &conf.timeouts, &conf.slow_thresholds))
 }
 
 #[cfg(feature = "rpc")]
-pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: Vec<&str>) -> jsonipc::Server {
-	let server = setup_rpc_server(apis, dependencies);
+pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: Vec<&str>, timeouts: &[(String, u64)], slow_thresholds: &[(String, u64)]) -> IpcServer {
+	let server = setup_rpc_server(apis, timeouts, slow_thresholds, AccountsFilter::All, dependencies);
 	match server.start_ipc(addr) {
-		Err(jsonipc::Error::Io(io_error)) => die_with_io_error("RPC", io_error),
-		Err(any_error) => die!("RPC: {:?}", any_error),
+		Err(e) => die_with_start_error("RPC", e),
 		Ok(server) => server
 	}
 }
+
+#[cfg(feature = "rpc")]
+fn die_with_start_error(module: &'static str, e: StartError) -> ! {
+	trace!(target: module, "{:?}", e);
+
+	match e.kind {
+		StartErrorKind::PermissionDenied => {
+			die!("{}: No permissions to bind to {}.", module, e.address)
+		},
+		StartErrorKind::AddressInUse => {
+			die!("{}: Address {} is already in use. Please make sure that nothing is listening on the same port or try using a different one.", module, e.address)
+		},
+		StartErrorKind::InvalidAddress => {
+			die!("{}: Could not use {}: the interface is not available or the address is invalid.", module, e.address)
+		},
+		StartErrorKind::Other => die!("{}: {}", module, e),
+	}
+}
@@ -20,6 +20,7 @@ use std::sync::Arc;
 
 use die::*;
 use ethsync::EthSync;
+use whisper::WhisperHost;
 use ethcore::miner::{Miner, ExternalMiner};
 use ethcore::client::Client;
 use util::RotatingLogger;
@@ -28,10 +29,30 @@ use util::network_settings::NetworkSettings;
 use util::network::NetworkService;
 
 #[cfg(feature="rpc")]
-pub use ethcore_rpc::ConfirmationsQueue;
+pub use ethcore_rpc::{ConfirmationsQueue, ResultCache, AccountsFilter, DappsProvider, FetchLimits, ExecutionLimit};
 #[cfg(not(feature="rpc"))]
 #[derive(Default)]
 pub struct ConfirmationsQueue;
+#[cfg(not(feature="rpc"))]
+pub struct ResultCache;
+#[cfg(not(feature="rpc"))]
+#[derive(Clone)]
+pub struct FetchLimits {
+	pub timeout_ms: u64,
+	pub max_size: u64,
+}
+#[cfg(not(feature="rpc"))]
+pub trait DappsProvider: Send + Sync {}
+#[cfg(not(feature="rpc"))]
+impl ResultCache {
+	pub fn new(_capacity: usize) -> Self { ResultCache }
+}
+#[cfg(not(feature="rpc"))]
+pub struct ExecutionLimit;
+#[cfg(not(feature="rpc"))]
+impl ExecutionLimit {
+	pub fn new(_max: usize) -> Self { ExecutionLimit }
+}
 
 #[cfg(feature="rpc")]
 use ethcore_rpc::Extendable;
@@ -46,6 +67,8 @@ pub enum Api {
 	EthcoreSet,
 	Traces,
 	Rpc,
+	TxPool,
+	Shh,
 }
 
 pub enum ApiError {
@@ -74,6 +97,8 @@ impl FromStr for Api {
 			"ethcore_set" => Ok(EthcoreSet),
 			"traces" => Ok(Traces),
 			"rpc" => Ok(Rpc),
+			"txpool" => Ok(TxPool),
+			"shh" => Ok(Shh),
 			e => Err(ApiError::UnknownApi(e.into())),
 		}
 	}
@@ -84,12 +109,19 @@ pub struct Dependencies {
 	pub signer_queue: Arc<ConfirmationsQueue>,
 	pub client: Arc<Client>,
 	pub sync: Arc<EthSync>,
+	pub whisper: Option<Arc<WhisperHost>>,
 	pub secret_store: Arc<AccountProvider>,
 	pub miner: Arc<Miner>,
 	pub external_miner: Arc<ExternalMiner>,
 	pub logger: Arc<RotatingLogger>,
 	pub settings: Arc<NetworkSettings>,
 	pub allow_pending_receipt_query: bool,
+	pub result_cache: Arc<ResultCache>,
+	pub execution_limit: Arc<ExecutionLimit>,
+	pub tip_percentile: usize,
+	pub dapps: Option<Arc<DappsProvider>>,
+	pub fetch_limits: FetchLimits,
+	pub solc_path: Option<String>,
 	pub net_service: Arc<NetworkService<::ethcore::service::SyncMessage>>,
 }
 
@@ -106,6 +138,8 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 			Api::EthcoreSet => ("ethcore_set", "1.0"),
 			Api::Traces => ("traces", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
+			Api::TxPool => ("txpool", "1.0"),
+			Api::Shh => ("shh", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
 	}
@@ -123,7 +157,13 @@ pub fn from_str(apis: Vec<&str>) -> Vec<Api> {
 
 fn list_apis(apis: ApiSet) -> Vec<Api> {
 	match apis {
-		ApiSet::List(apis) => apis,
+		ApiSet::List(apis) => apis.into_iter().filter(|api| match *api {
+			Api::Signer => {
+				warn!("Signer API is only available through the Trusted Signer endpoint.");
+				false
+			},
+			_ => true,
+		}).collect(),
 		ApiSet::UnsafeContext => {
 			vec![Api::Web3, Api::Net, Api::Eth, Api::Personal, Api::Ethcore, Api::Traces, Api::Rpc]
 		},
@@ -133,7 +173,7 @@ fn list_apis(apis: ApiSet) -> Vec<Api> {
 	}
 }
 
-pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet) -> T {
+pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet, accounts: AccountsFilter) -> T {
 	use ethcore_rpc::v1::*;
 
 	let apis = list_apis(apis);
@@ -146,7 +186,7 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 				server.add_delegate(NetClient::new(&deps.sync).to_delegate());
 			},
 			Api::Eth => {
-				server.add_delegate(EthClient::new(&deps.client, &deps.sync, &deps.secret_store, &deps.miner, &deps.external_miner, deps.allow_pending_receipt_query).to_delegate());
+				server.add_delegate(EthClient::new(&deps.client, &deps.sync, &deps.secret_store, &deps.miner, &deps.external_miner, deps.allow_pending_receipt_query, &deps.result_cache, deps.tip_percentile, accounts.clone(), deps.solc_path.as_ref().map(|path| SolcConfig::new(path)), &deps.execution_limit).to_delegate());
 				server.add_delegate(EthFilterClient::new(&deps.client, &deps.miner).to_delegate());
 
 				if deps.signer_port.is_some() {
@@ -163,17 +203,25 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 			},
 			Api::Ethcore => {
 				let queue = deps.signer_port.map(|_| deps.signer_queue.clone());
-				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, deps.logger.clone(), deps.settings.clone(), queue).to_delegate())
+				server.add_delegate(EthcoreClient::new(&deps.client, &deps.miner, &deps.net_service, deps.logger.clone(), deps.settings.clone(), queue, deps.dapps.clone(), deps.fetch_limits.clone(), &deps.result_cache).to_delegate())
 			},
 			Api::EthcoreSet => {
 				server.add_delegate(EthcoreSetClient::new(&deps.miner, &deps.net_service).to_delegate())
 			},
 			Api::Traces => {
-				server.add_delegate(TracesClient::new(&deps.client, &deps.miner).to_delegate())
+				server.add_delegate(TracesClient::new(&deps.client, &deps.miner, &deps.execution_limit).to_delegate())
 			},
 			Api::Rpc => {
 				let modules = to_modules(&apis);
-				server.add_delegate(RpcClient::new(modules).to_delegate());
+				server.add_delegate(RpcClient::new(modules, server.method_names()).to_delegate());
+			},
+			Api::TxPool => {
+				server.add_delegate(TxPoolClient::new(&deps.miner).to_delegate());
+			},
+			Api::Shh => {
+				if let Some(ref whisper) = deps.whisper {
+					server.add_delegate(ShhClient::new(whisper).to_delegate());
+				}
 			}
 		}
 	}
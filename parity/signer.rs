@@ -38,6 +38,7 @@ pub struct Configuration {
 	pub enabled: bool,
 	pub port: u16,
 	pub signer_path: String,
+	pub cors: Option<Vec<String>>,
 }
 
 pub struct Dependencies {
@@ -82,7 +83,11 @@ fn do_start(conf: Configuration, deps: Dependencies) -> SignerServer {
 			deps.apis.signer_queue.clone(),
 			codes_path(conf.signer_path),
 		);
-		let server = rpc_apis::setup_rpc(server, deps.apis, rpc_apis::ApiSet::SafeContext);
+		let server = match conf.cors {
+			Some(ref cors) => server.with_cors(cors),
+			None => server,
+		};
+		let server = rpc_apis::setup_rpc(server, deps.apis, rpc_apis::ApiSet::SafeContext, rpc_apis::AccountsFilter::All);
 		server.start(addr)
 	};
 
@@ -0,0 +1,79 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Origin checks shared by all transports.
+
+use jsonrpc_http_server::AccessControlAllowOrigin;
+
+/// Parses allowed origins as given on the command line: `*` allows any origin,
+/// `null` the `null` origin sent by sandboxed pages and local files, anything else is an exact origin.
+pub fn cors_domains(domains: &[String]) -> Vec<AccessControlAllowOrigin> {
+	domains.iter()
+		.map(|v| match v.as_str() {
+			"*" => AccessControlAllowOrigin::Any,
+			"null" => AccessControlAllowOrigin::Null,
+			v => AccessControlAllowOrigin::Value(v.into()),
+		})
+		.collect()
+}
+
+/// Checks the value of the `Origin` header of a request against allowed origins.
+/// Requests without the header don't come from a browser and are not subject to the check.
+pub fn origin_is_allowed(domains: &[AccessControlAllowOrigin], origin: Option<&str>) -> bool {
+	let origin = match origin {
+		Some(origin) => origin,
+		None => return true,
+	};
+	domains.iter().any(|domain| match *domain {
+		AccessControlAllowOrigin::Any => true,
+		AccessControlAllowOrigin::Null => origin == "null",
+		AccessControlAllowOrigin::Value(ref value) => origin == value,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{cors_domains, origin_is_allowed};
+
+	fn domains(domains: &[&str]) -> Vec<String> {
+		domains.iter().map(|d| d.to_string()).collect()
+	}
+
+	#[test]
+	fn should_allow_listed_origins_only() {
+		let allowed = cors_domains(&domains(&["http://parity.io", "https://ethcore.io"]));
+		assert!(origin_is_allowed(&allowed, Some("http://parity.io")));
+		assert!(origin_is_allowed(&allowed, Some("https://ethcore.io")));
+		assert!(!origin_is_allowed(&allowed, Some("http://parity.io.evil.com")));
+		assert!(!origin_is_allowed(&allowed, Some("null")));
+		assert!(origin_is_allowed(&allowed, None));
+	}
+
+	#[test]
+	fn should_allow_any_origin_with_wildcard() {
+		let allowed = cors_domains(&domains(&["*"]));
+		assert!(origin_is_allowed(&allowed, Some("http://parity.io")));
+		assert!(origin_is_allowed(&allowed, Some("null")));
+	}
+
+	#[test]
+	fn should_allow_null_origin_only_when_listed() {
+		let allowed = cors_domains(&domains(&["null"]));
+		assert!(origin_is_allowed(&allowed, Some("null")));
+		assert!(!origin_is_allowed(&allowed, Some("http://parity.io")));
+		assert!(!origin_is_allowed(&cors_domains(&[]), Some("null")));
+	}
+}
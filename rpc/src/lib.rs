@@ -30,7 +30,10 @@ extern crate jsonrpc_http_server;
 extern crate ethcore_util as util;
 extern crate ethcore;
 extern crate ethsync;
+extern crate ethcore_whisper as whisper;
 extern crate transient_hashmap;
+extern crate tiny_keccak;
+extern crate hyper;
 extern crate json_ipc_server as ipc;
 
 #[cfg(test)]
@@ -38,29 +41,152 @@ extern crate ethjson;
 #[cfg(test)]
 extern crate ethcore_devtools as devtools;
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, Mutex};
 use std::net::SocketAddr;
-use self::jsonrpc_core::{IoHandler, IoDelegate};
+use self::jsonrpc_core::{IoHandler, IoDelegate, Params, Value, Error};
 
-pub use jsonrpc_http_server::{Server, RpcServerError};
+pub use jsonrpc_http_server::{Server, RpcServerError, AccessControlAllowOrigin};
 pub mod v1;
-pub use v1::{SigningQueue, ConfirmationsQueue};
+mod cors;
+mod methods;
+mod span;
+mod start_error;
+pub use v1::{SigningQueue, ConfirmationsQueue, ResultCache, ResultCacheStats, AccountsFilter, DappsProvider, Dapp, DappSource, FetchLimits, SolcConfig, ExecutionLimit};
+pub use methods::{DelegateError, MethodNames, closure_delegate};
+pub use span::{Span, SPAN_TARGET};
+pub use cors::{cors_domains, origin_is_allowed};
+pub use start_error::{StartError, StartErrorKind};
+use methods::MethodRegistry;
 
-/// An object that can be extended with `IoDelegates`
+/// An object that can be extended with `IoDelegates`.
+///
+/// Downstream crates can expose their own methods by building an `IoDelegate`
+/// over their handler struct, or with `add_method` for one-off methods:
+///
+/// ```
+/// extern crate jsonrpc_core;
+/// extern crate ethcore_rpc;
+///
+/// use jsonrpc_core::Value;
+/// use ethcore_rpc::{Extendable, RpcServer};
+///
+/// fn main() {
+/// 	let server = RpcServer::new();
+/// 	server.add_method("myco_internalStats", |_params| Ok(Value::U64(42)));
+/// }
+/// ```
 pub trait Extendable {
 	/// Add `Delegate` to this object.
 	fn add_delegate<D: Send + Sync + 'static>(&self, delegate: IoDelegate<D>);
+
+	/// Add a single method handled by given closure.
+	fn add_method<F>(&self, name: &str, method: F) where F: Fn(Params) -> Result<Value, Error> + Send + Sync + 'static {
+		self.add_delegate(closure_delegate(name, method));
+	}
+
+	/// Names of methods added so far and later on, if this object keeps track of them.
+	fn method_names(&self) -> Option<MethodNames> {
+		None
+	}
+}
+
+/// State of a server started by `RpcServer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerStatus {
+	/// Server was not started yet or is binding its listener.
+	Starting,
+	/// Server is accepting connections.
+	Listening,
+	/// Server was closed.
+	Stopped,
+	/// Server failed to start.
+	Errored,
+}
+
+/// Handle of a running server. Each handle tracks the status of its own server.
+/// The server is closed when the handle is dropped.
+pub struct ServerHandle<S> {
+	server: Option<S>,
+	status: Arc<RwLock<ServerStatus>>,
+}
+
+impl<S> ServerHandle<S> {
+	/// Creates handle from the result of starting the server. `status` becomes `Listening`
+	/// if the server was started and `Errored` otherwise.
+	fn start<E>(result: Result<S, E>, status: Arc<RwLock<ServerStatus>>) -> Result<Self, E> {
+		match result {
+			Ok(server) => {
+				*status.write().unwrap() = ServerStatus::Listening;
+				Ok(ServerHandle {
+					server: Some(server),
+					status: status,
+				})
+			},
+			Err(e) => {
+				*status.write().unwrap() = ServerStatus::Errored;
+				Err(e)
+			},
+		}
+	}
+
+	/// Returns the underlying server, or `None` once the handle is closed.
+	pub fn server(&self) -> Option<&S> {
+		self.server.as_ref()
+	}
+
+	/// Returns current server status.
+	pub fn status(&self) -> ServerStatus {
+		*self.status.read().unwrap()
+	}
+
+	/// Returns true if the server is accepting connections.
+	pub fn is_listening(&self) -> bool {
+		self.status() == ServerStatus::Listening
+	}
+
+	/// Close the server.
+	pub fn close(&mut self) {
+		self.server = None;
+		*self.status.write().unwrap() = ServerStatus::Stopped;
+	}
+}
+
+impl<S> Drop for ServerHandle<S> {
+	fn drop(&mut self) {
+		self.close();
+	}
+}
+
+/// Http server handle.
+pub type HttpServer = ServerHandle<Server>;
+/// Handle of http servers listening on multiple addresses.
+pub type MultiHttpServer = ServerHandle<Vec<Server>>;
+/// Ipc server handle.
+pub type IpcServer = ServerHandle<ipc::Server>;
+
+/// Status cell of a server that is about to be started.
+fn new_status() -> Arc<RwLock<ServerStatus>> {
+	Arc::new(RwLock::new(ServerStatus::Starting))
 }
 
 /// Http server.
 pub struct RpcServer {
 	handler: Arc<jsonrpc_core::io::IoHandler>,
+	registry: Mutex<MethodRegistry>,
+	http_status: Mutex<Arc<RwLock<ServerStatus>>>,
+	ipc_status: Mutex<Arc<RwLock<ServerStatus>>>,
 }
 
 impl Extendable for RpcServer {
 	/// Add io delegate.
 	fn add_delegate<D: Send + Sync + 'static>(&self, delegate: IoDelegate<D>) {
-		self.handler.add_delegate(delegate);
+		if let Err(e) = self.registry.lock().unwrap().register(&self.handler, None, delegate) {
+			warn!(target: "rpc", "Delegate was not registered: {}", e);
+		}
+	}
+
+	fn method_names(&self) -> Option<MethodNames> {
+		Some(self.registry.lock().unwrap().names())
 	}
 }
 
@@ -69,25 +195,215 @@ impl RpcServer {
 	pub fn new() -> RpcServer {
 		RpcServer {
 			handler: Arc::new(IoHandler::new()),
+			registry: Mutex::new(MethodRegistry::new()),
+			http_status: Mutex::new(new_status()),
+			ipc_status: Mutex::new(new_status()),
 		}
 	}
 
+	/// Creates new http server object which refuses delegates with methods
+	/// colliding with already registered ones.
+	pub fn new_strict() -> RpcServer {
+		let server = RpcServer::new();
+		server.registry.lock().unwrap().set_strict(true);
+		server
+	}
+
+	/// Sets request timeout in milliseconds for methods in given namespace (e.g. `eth`).
+	/// Zero disables the timeout. Only delegates added afterwards are affected.
+	pub fn set_request_timeout(&self, namespace: &str, timeout_ms: u64) {
+		self.registry.lock().unwrap().set_timeout(namespace, timeout_ms);
+	}
+
+	/// Sets duration in milliseconds above which requests to methods in given namespace are logged
+	/// with a warning, even if they succeed. Zero disables the warning. Only delegates added afterwards are affected.
+	pub fn set_slow_request_threshold(&self, namespace: &str, threshold_ms: u64) {
+		self.registry.lock().unwrap().set_slow_threshold(namespace, threshold_ms);
+	}
+
+	/// Add io delegate, returning an error if it can't be registered.
+	pub fn try_add_delegate<D: Send + Sync + 'static>(&self, delegate: IoDelegate<D>) -> Result<(), DelegateError> {
+		self.registry.lock().unwrap().register(&self.handler, None, delegate)
+	}
+
+	/// Add io delegate with all its methods moved to given namespace prefix
+	/// (i.e. `eth_getBalance` is registered as `prefix_getBalance`).
+	/// Fails if the prefix is already used by other registered methods.
+	pub fn add_delegate_with_prefix<D: Send + Sync + 'static>(&self, prefix: &str, delegate: IoDelegate<D>) -> Result<(), DelegateError> {
+		self.registry.lock().unwrap().register(&self.handler, Some(prefix), delegate)
+	}
+
+	/// Returns status of the last started http server.
+	pub fn http_status(&self) -> ServerStatus {
+		let status = self.http_status.lock().unwrap();
+		let status = status.read().unwrap();
+		*status
+	}
+
+	/// Returns status of the last started ipc server.
+	pub fn ipc_status(&self) -> ServerStatus {
+		let status = self.ipc_status.lock().unwrap();
+		let status = status.read().unwrap();
+		*status
+	}
+
 	/// Start http server asynchronously and returns result with `Server` handle on success or an error.
-	pub fn start_http(&self, addr: &SocketAddr, cors_domains: Vec<String>) -> Result<Server, RpcServerError> {
-		let cors_domains = cors_domains.into_iter()
-			.map(|v| match v.as_str() {
-				"*" => jsonrpc_http_server::AccessControlAllowOrigin::Any,
-				"null" => jsonrpc_http_server::AccessControlAllowOrigin::Null,
-				v => jsonrpc_http_server::AccessControlAllowOrigin::Value(v.into()),
-			})
-			.collect();
-		Server::start(addr, self.handler.clone(), cors_domains)
+	/// The error tells why `addr` could not be bound.
+	pub fn start_http(&self, addr: &SocketAddr, cors_domains: Vec<String>) -> Result<HttpServer, StartError> {
+		let status = new_status();
+		*self.http_status.lock().unwrap() = status.clone();
+		let result = Server::start(addr, self.handler.clone(), cors::cors_domains(&cors_domains));
+		ServerHandle::start(result, status).map_err(|e| StartError::from_http(addr, e))
+	}
+
+	/// Start http servers listening on all given addresses with the same configuration.
+	/// If any of the addresses can't be bound servers already started are closed and an error is returned.
+	pub fn start_http_multi(&self, addrs: &[SocketAddr], cors_domains: Vec<String>) -> Result<MultiHttpServer, StartError> {
+		let status = new_status();
+		*self.http_status.lock().unwrap() = status.clone();
+		// dropping servers already started on error closes them
+		let result = addrs.iter()
+			.map(|addr| Server::start(addr, self.handler.clone(), cors::cors_domains(&cors_domains)).map_err(|e| StartError::from_http(addr, e)))
+			.collect::<Result<Vec<_>, _>>();
+		ServerHandle::start(result, status)
 	}
 
 	/// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
-	pub fn start_ipc(&self, addr: &str) -> Result<ipc::Server, ipc::Error> {
-		let server = try!(ipc::Server::new(addr, &self.handler));
-		try!(server.run_async());
-		Ok(server)
+	pub fn start_ipc(&self, addr: &str) -> Result<IpcServer, StartError> {
+		let status = new_status();
+		*self.ipc_status.lock().unwrap() = status.clone();
+		let result = ipc::Server::new(addr, &self.handler).and_then(|server| {
+			try!(server.run_async());
+			Ok(server)
+		});
+		ServerHandle::start(result, status).map_err(|e| StartError::from_ipc(addr, e))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, RwLock};
+	use std::io::{Read, Write};
+	use std::net::{SocketAddr, TcpListener, TcpStream};
+	use std::str::FromStr;
+	use std::time::Duration;
+	use jsonrpc_core::Value;
+	use super::{Extendable, ServerHandle, ServerStatus, RpcServer, StartErrorKind};
+
+	fn free_addr() -> SocketAddr {
+		TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+	}
+
+	fn post(addr: &SocketAddr, body: &str) -> String {
+		let mut stream = TcpStream::connect(addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+		let request = format!("POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", addr, body.len(), body);
+		stream.write_all(request.as_bytes()).unwrap();
+		let mut response = String::new();
+		let _ = stream.read_to_string(&mut response);
+		response
+	}
+
+	#[test]
+	fn handle_should_report_listening_until_closed() {
+		let status = Arc::new(RwLock::new(ServerStatus::Starting));
+		let mut handle = ServerHandle::start(Ok::<_, ()>(()), status.clone()).unwrap();
+		assert!(handle.is_listening());
+
+		handle.close();
+		assert!(!handle.is_listening());
+		assert!(handle.server().is_none());
+		assert_eq!(*status.read().unwrap(), ServerStatus::Stopped);
+	}
+
+	#[test]
+	fn handle_should_mark_stopped_on_drop() {
+		let status = Arc::new(RwLock::new(ServerStatus::Starting));
+		{
+			let _handle = ServerHandle::start(Ok::<_, ()>(()), status.clone()).unwrap();
+			assert_eq!(*status.read().unwrap(), ServerStatus::Listening);
+		}
+		assert_eq!(*status.read().unwrap(), ServerStatus::Stopped);
+	}
+
+	#[test]
+	fn handle_should_not_be_created_for_failed_start() {
+		let status = Arc::new(RwLock::new(ServerStatus::Starting));
+		assert!(ServerHandle::<()>::start(Err(()), status.clone()).is_err());
+		assert_eq!(*status.read().unwrap(), ServerStatus::Errored);
+	}
+
+	#[test]
+	fn start_http_should_report_listening_once_bound() {
+		let server = RpcServer::new();
+		let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+
+		let handle = server.start_http(&addr, vec![]).unwrap();
+
+		assert!(handle.is_listening());
+		assert_eq!(server.http_status(), ServerStatus::Listening);
+	}
+
+	#[test]
+	fn closing_one_handle_should_not_stop_others() {
+		let server = RpcServer::new();
+		let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+		let mut first = server.start_http(&addr, vec![]).unwrap();
+		let second = server.start_http(&addr, vec![]).unwrap();
+
+		first.close();
+
+		assert!(!first.is_listening());
+		assert!(second.is_listening());
+		assert_eq!(server.http_status(), ServerStatus::Listening);
+	}
+
+	#[test]
+	fn start_http_multi_should_serve_all_addresses() {
+		let server = RpcServer::new();
+		server.add_method("test_hello", |_| Ok(Value::String("hello".to_owned())));
+		let addrs = vec![free_addr(), free_addr()];
+
+		let handle = server.start_http_multi(&addrs, vec![]).unwrap();
+
+		assert!(handle.is_listening());
+		assert_eq!(handle.server().unwrap().len(), 2);
+		let request = r#"{"jsonrpc": "2.0", "method": "test_hello", "params": [], "id": 1}"#;
+		for addr in &addrs {
+			assert!(post(addr, request).contains(r#""result":"hello""#), "{} is not served", addr);
+		}
+	}
+
+	#[test]
+	fn server_should_start_in_starting_state() {
+		let server = RpcServer::new();
+		assert_eq!(server.http_status(), ServerStatus::Starting);
+		assert_eq!(server.ipc_status(), ServerStatus::Starting);
+	}
+
+	#[test]
+	fn start_http_should_report_address_in_use() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		let server = RpcServer::new();
+
+		let error = server.start_http(&addr, vec![]).err().expect("Port is taken");
+
+		assert_eq!(error.kind, StartErrorKind::AddressInUse);
+		assert_eq!(error.address, format!("{}", addr));
+		assert_eq!(server.http_status(), ServerStatus::Errored);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn start_http_should_report_privileged_port() {
+		let addr = SocketAddr::from_str("127.0.0.1:1").unwrap();
+		let server = RpcServer::new();
+
+		// Succeeds when the tests run with root privileges.
+		if let Err(error) = server.start_http(&addr, vec![]) {
+			assert_eq!(error.kind, StartErrorKind::PermissionDenied);
+			assert_eq!(error.address, "127.0.0.1:1");
+		}
 	}
 }
@@ -0,0 +1,512 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bookkeeping of methods registered on the `IoHandler`.
+
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex, RwLock, Condvar};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use jsonrpc_core::{IoHandler, IoDelegate, MethodCommand, NotificationCommand, Params, Value, Error, ErrorCode};
+use span::Span;
+
+const REQUEST_TIMEOUT_CODE: i64 = -32003;
+const TOO_MANY_HANDLERS_CODE: i64 = -32004;
+/// Maximal number of handlers of requests with a timeout running at once, including timed out ones.
+const MAX_TIMED_HANDLERS: usize = 64;
+
+/// Error returned when a delegate cannot be registered.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DelegateError {
+	/// Namespace prefix is already used by another delegate.
+	PrefixInUse(String),
+	/// Method with the same name is already registered.
+	MethodCollision(String),
+}
+
+impl fmt::Display for DelegateError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			DelegateError::PrefixInUse(ref prefix) => write!(f, "Namespace prefix `{}` is already registered.", prefix),
+			DelegateError::MethodCollision(ref method) => write!(f, "Method `{}` is already registered.", method),
+		}
+	}
+}
+
+/// Timeout applied to a method: namespace it was configured for and its length in milliseconds.
+type MethodTimeout = (String, u64);
+
+/// Number of running handlers of requests with a timeout.
+/// Handlers of timed out requests keep running, so new requests are refused once there are too many.
+struct TimedHandlers {
+	running: AtomicUsize,
+	limit: usize,
+}
+
+impl TimedHandlers {
+	fn new(limit: usize) -> Self {
+		TimedHandlers {
+			running: AtomicUsize::new(0),
+			limit: limit,
+		}
+	}
+
+	/// Reserve a slot for a new handler. Returns `false` if all slots are taken.
+	fn acquire(&self) -> bool {
+		if self.running.fetch_add(1, Ordering::SeqCst) >= self.limit {
+			self.release();
+			return false;
+		}
+		true
+	}
+
+	fn release(&self) {
+		self.running.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+impl Default for TimedHandlers {
+	fn default() -> Self {
+		TimedHandlers::new(MAX_TIMED_HANDLERS)
+	}
+}
+
+struct DelegateMethod {
+	name: String,
+	command: Arc<Box<MethodCommand>>,
+	timeout: Option<MethodTimeout>,
+	handlers: Arc<TimedHandlers>,
+	/// Duration in milliseconds above which a warning is logged for the request.
+	slow_threshold_ms: Option<u64>,
+}
+
+impl MethodCommand for DelegateMethod {
+	fn execute(&self, params: Params) -> Result<Value, Error> {
+		let _span = Span::enter(&self.name);
+		let started = Instant::now();
+		let result = match self.timeout {
+			Some(_) if !self.handlers.acquire() => Err(too_many_handlers_error()),
+			Some((ref namespace, timeout_ms)) => execute_with_timeout(self.command.clone(), params, timeout_ms, self.handlers.clone())
+				.unwrap_or_else(|| Err(timeout_error(namespace, timeout_ms))),
+			None => self.command.execute(params),
+		};
+		if let Some(threshold_ms) = self.slow_threshold_ms {
+			if let Some(warning) = slow_request_warning(&self.name, elapsed_ms(started), threshold_ms) {
+				warn!(target: "rpc", "{}", warning);
+			}
+		}
+		result
+	}
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+	let elapsed = started.elapsed();
+	elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1000_000
+}
+
+/// Warning about a request which took longer than the threshold, `None` if it didn't.
+fn slow_request_warning(method: &str, elapsed_ms: u64, threshold_ms: u64) -> Option<String> {
+	if elapsed_ms > threshold_ms {
+		Some(format!("Slow request: {} took {} ms (threshold {} ms).", method, elapsed_ms, threshold_ms))
+	} else {
+		None
+	}
+}
+
+/// Executes the command on a separate thread, in a slot already acquired from `handlers`.
+/// Returns `None` if it doesn't finish within `timeout_ms`. The command is not interrupted then;
+/// it keeps running, holding its slot, and its result is discarded.
+fn execute_with_timeout(command: Arc<Box<MethodCommand>>, params: Params, timeout_ms: u64, handlers: Arc<TimedHandlers>) -> Option<Result<Value, Error>> {
+	let result = Arc::new((Mutex::new(None), Condvar::new()));
+	let handler_result = result.clone();
+	let parent_span = Span::current();
+	thread::spawn(move || {
+		let value = Span::with_parent(parent_span, || command.execute(params));
+		handlers.release();
+		let &(ref lock, ref cvar) = &*handler_result;
+		*lock.lock().unwrap() = Some(value);
+		cvar.notify_one();
+	});
+
+	let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+	let &(ref lock, ref cvar) = &*result;
+	let mut value = lock.lock().unwrap();
+	while value.is_none() {
+		let now = Instant::now();
+		if now >= deadline {
+			return None;
+		}
+		value = cvar.wait_timeout(value, deadline - now).unwrap().0;
+	}
+	value.take()
+}
+
+fn timeout_error(namespace: &str, timeout_ms: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(REQUEST_TIMEOUT_CODE),
+		message: format!("Request exceeded the `{}` namespace timeout of {} ms.", namespace, timeout_ms),
+		data: None,
+	}
+}
+
+fn too_many_handlers_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(TOO_MANY_HANDLERS_CODE),
+		message: "Too many requests are still being handled. Try again later.".into(),
+		data: None,
+	}
+}
+
+struct DelegateNotification(String, Box<NotificationCommand>);
+
+impl NotificationCommand for DelegateNotification {
+	fn execute(&self, params: Params) {
+		let _span = Span::enter(&self.0);
+		self.1.execute(params)
+	}
+}
+
+/// Returns namespace prefix of given method name (`eth` for `eth_getBalance`).
+pub fn method_prefix(name: &str) -> Option<&str> {
+	name.find('_').map(|i| &name[..i])
+}
+
+/// Moves method to given namespace (`eth_getBalance` becomes `prefix_getBalance`).
+pub fn with_prefix(prefix: &str, name: &str) -> String {
+	match name.find('_') {
+		Some(i) => format!("{}{}", prefix, &name[i..]),
+		None => format!("{}_{}", prefix, name),
+	}
+}
+
+/// Looks up the setting of the method's namespace. Zero values mean the setting is disabled.
+fn namespace_setting(settings: &HashMap<String, u64>, name: &str) -> Option<(String, u64)> {
+	method_prefix(name)
+		.and_then(|prefix| settings.get(prefix).map(|value| (prefix.to_owned(), *value)))
+		.and_then(|(prefix, value)| if value == 0 { None } else { Some((prefix, value)) })
+}
+
+/// Creates a delegate with a single method backed by given closure.
+pub fn closure_delegate<F>(name: &str, method: F) -> IoDelegate<F>
+	where F: Fn(Params) -> Result<Value, Error> + Send + Sync + 'static {
+	let mut delegate = IoDelegate::new(Arc::new(method));
+	delegate.add_method(name, |method: &F, params| method(params));
+	delegate
+}
+
+/// Names of methods and notifications registered on the `IoHandler`.
+/// Clones share the list, so they see methods registered later on.
+#[derive(Debug, Clone, Default)]
+pub struct MethodNames(Arc<RwLock<BTreeSet<String>>>);
+
+impl MethodNames {
+	/// Returns all registered names in alphabetical order.
+	pub fn list(&self) -> Vec<String> {
+		self.0.read().unwrap().iter().cloned().collect()
+	}
+
+	fn contains(&self, name: &str) -> bool {
+		self.0.read().unwrap().contains(name)
+	}
+
+	fn insert(&self, name: &str) {
+		self.0.write().unwrap().insert(name.to_owned());
+	}
+}
+
+/// Keeps track of namespaces and methods registered on the `IoHandler`.
+#[derive(Default)]
+pub struct MethodRegistry {
+	prefixes: HashSet<String>,
+	methods: MethodNames,
+	timeouts: HashMap<String, u64>,
+	slow_thresholds: HashMap<String, u64>,
+	handlers: Arc<TimedHandlers>,
+	strict: bool,
+}
+
+impl MethodRegistry {
+	/// Creates empty registry.
+	pub fn new() -> Self {
+		MethodRegistry::default()
+	}
+
+	/// In strict mode delegates overriding already registered methods are refused.
+	/// Otherwise the new method shadows the old one and a warning is logged.
+	pub fn set_strict(&mut self, strict: bool) {
+		self.strict = strict;
+	}
+
+	/// Returns a live view of the registered method names.
+	pub fn names(&self) -> MethodNames {
+		self.methods.clone()
+	}
+
+	/// Sets request timeout in milliseconds for methods in given namespace (e.g. `eth`).
+	/// Zero means no timeout. Applies to delegates registered afterwards.
+	pub fn set_timeout(&mut self, namespace: &str, timeout_ms: u64) {
+		self.timeouts.insert(namespace.to_owned(), timeout_ms);
+	}
+
+	/// Sets duration in milliseconds above which requests to methods in given namespace are logged
+	/// with a warning. Zero disables the warning. Applies to delegates registered afterwards.
+	pub fn set_slow_threshold(&mut self, namespace: &str, threshold_ms: u64) {
+		self.slow_thresholds.insert(namespace.to_owned(), threshold_ms);
+	}
+
+	fn timeout(&self, name: &str) -> Option<MethodTimeout> {
+		namespace_setting(&self.timeouts, name)
+	}
+
+	fn slow_threshold(&self, name: &str) -> Option<u64> {
+		namespace_setting(&self.slow_thresholds, name).map(|(_, threshold)| threshold)
+	}
+
+	/// Registers all methods of the delegate on the handler.
+	/// When `prefix` is given all methods are moved to that namespace.
+	pub fn register<D>(&mut self, handler: &IoHandler, prefix: Option<&str>, delegate: IoDelegate<D>) -> Result<(), DelegateError>
+		where D: Send + Sync + 'static {
+		if let Some(prefix) = prefix {
+			if self.prefixes.contains(prefix) {
+				return Err(DelegateError::PrefixInUse(prefix.to_owned()));
+			}
+		}
+
+		let (methods, notifications): (HashMap<String, Box<MethodCommand>>, HashMap<String, Box<NotificationCommand>>) = delegate.into();
+		let rename = |name: String| match prefix {
+			Some(prefix) => with_prefix(prefix, &name),
+			None => name,
+		};
+
+		let methods = methods.into_iter().map(|(name, command)| (rename(name), command)).collect::<Vec<_>>();
+		let notifications = notifications.into_iter().map(|(name, command)| (rename(name), command)).collect::<Vec<_>>();
+
+		let mut collisions = methods.iter().map(|m| &m.0)
+			.chain(notifications.iter().map(|n| &n.0))
+			.filter(|name| self.methods.contains(name))
+			.cloned()
+			.collect::<Vec<_>>();
+		collisions.sort();
+		if let Some(name) = collisions.first() {
+			if self.strict {
+				return Err(DelegateError::MethodCollision(name.clone()));
+			}
+		}
+		for name in &collisions {
+			warn!(target: "rpc", "Method {} is registered twice. Previous handler is shadowed.", name);
+		}
+
+		for (name, command) in methods {
+			self.insert(&name);
+			let timeout = self.timeout(&name);
+			let slow_threshold_ms = self.slow_threshold(&name);
+			handler.add_method(&name, DelegateMethod {
+				name: name.clone(),
+				command: Arc::new(command),
+				timeout: timeout,
+				handlers: self.handlers.clone(),
+				slow_threshold_ms: slow_threshold_ms,
+			});
+		}
+		for (name, command) in notifications {
+			self.insert(&name);
+			handler.add_notification(&name, DelegateNotification(name.clone(), command));
+		}
+		Ok(())
+	}
+
+	fn insert(&mut self, name: &str) {
+		if let Some(p) = method_prefix(name) {
+			self.prefixes.insert(p.to_owned());
+		}
+		self.methods.insert(name);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
+	use jsonrpc_core::{IoHandler, IoDelegate, Params, Value, Error};
+	use super::{MethodRegistry, DelegateError, TimedHandlers, method_prefix, with_prefix, closure_delegate, slow_request_warning};
+
+	struct Echo;
+
+	impl Echo {
+		fn hello(&self, _: Params) -> Result<Value, Error> {
+			Ok(Value::String("hello".to_owned()))
+		}
+
+		fn slow(&self, _: Params) -> Result<Value, Error> {
+			thread::sleep(Duration::from_millis(500));
+			Ok(Value::String("slow".to_owned()))
+		}
+	}
+
+	fn delegate() -> IoDelegate<Echo> {
+		let mut delegate = IoDelegate::new(Arc::new(Echo));
+		delegate.add_method("echo_hello", Echo::hello);
+		delegate
+	}
+
+	fn slow_delegate() -> IoDelegate<Echo> {
+		let mut delegate = IoDelegate::new(Arc::new(Echo));
+		delegate.add_method("echo_hello", Echo::hello);
+		delegate.add_method("echo_slow", Echo::slow);
+		delegate.add_method("heavy_slow", Echo::slow);
+		delegate
+	}
+
+	#[test]
+	fn should_apply_namespace_timeouts() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		registry.set_timeout("echo", 50);
+		registry.set_timeout("heavy", 0);
+		registry.register(&io, None, slow_delegate()).unwrap();
+
+		let request = r#"{"jsonrpc": "2.0", "method": "echo_hello", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "echo_slow", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Request exceeded the `echo` namespace timeout of 50 ms.","data":null},"id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "heavy_slow", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"slow","id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_refuse_requests_while_timed_out_handlers_run() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		registry.handlers = Arc::new(TimedHandlers::new(1));
+		registry.set_timeout("echo", 50);
+		registry.register(&io, None, slow_delegate()).unwrap();
+
+		let request = r#"{"jsonrpc": "2.0", "method": "echo_slow", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Request exceeded the `echo` namespace timeout of 50 ms.","data":null},"id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+		// the timed out handler is still running
+		let request = r#"{"jsonrpc": "2.0", "method": "echo_hello", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32004,"message":"Too many requests are still being handled. Try again later.","data":null},"id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+		thread::sleep(Duration::from_millis(700));
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_apply_namespace_slow_thresholds() {
+		let mut registry = MethodRegistry::new();
+		registry.set_timeout("eth", 5000);
+		registry.set_slow_threshold("eth", 500);
+		registry.set_slow_threshold("trace", 0);
+
+		assert_eq!(registry.slow_threshold("eth_getLogs"), Some(500));
+		assert_eq!(registry.slow_threshold("trace_filter"), None);
+		assert_eq!(registry.slow_threshold("net_version"), None);
+		assert_eq!(registry.timeout("eth_getLogs"), Some(("eth".to_owned(), 5000)));
+	}
+
+	#[test]
+	fn should_warn_only_above_slow_threshold() {
+		assert_eq!(slow_request_warning("eth_getLogs", 500, 500), None);
+		assert_eq!(slow_request_warning("eth_getLogs", 1200, 500), Some("Slow request: eth_getLogs took 1200 ms (threshold 500 ms).".to_owned()));
+	}
+
+	#[test]
+	fn should_register_closure_as_method() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		let greeting = "hi".to_owned();
+		registry.register(&io, None, closure_delegate("myco_greet", move |_| Ok(Value::String(greeting.clone())))).unwrap();
+
+		let request = r#"{"jsonrpc": "2.0", "method": "myco_greet", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hi","id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+		assert_eq!(registry.names().list(), vec!["myco_greet"]);
+	}
+
+	#[test]
+	fn should_split_and_replace_prefix() {
+		assert_eq!(method_prefix("eth_getBalance"), Some("eth"));
+		assert_eq!(method_prefix("modules"), None);
+		assert_eq!(with_prefix("compat", "eth_getBalance"), "compat_getBalance".to_owned());
+		assert_eq!(with_prefix("compat", "modules"), "compat_modules".to_owned());
+	}
+
+	#[test]
+	fn should_register_delegate_under_custom_prefix() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		registry.register(&io, None, delegate()).unwrap();
+		registry.register(&io, Some("compat"), delegate()).unwrap();
+
+		let request = r#"{"jsonrpc": "2.0", "method": "compat_hello", "params": [], "id": 1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":"hello","id":1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+		let request = r#"{"jsonrpc": "2.0", "method": "echo_hello", "params": [], "id": 1}"#;
+		assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_list_registered_names() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		let names = registry.names();
+		registry.register(&io, None, slow_delegate()).unwrap();
+		registry.register(&io, Some("compat"), delegate()).unwrap();
+
+		assert_eq!(names.list(), vec!["compat_hello", "echo_hello", "echo_slow", "heavy_slow"]);
+	}
+
+	#[test]
+	fn should_reject_prefix_already_in_use() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		registry.register(&io, None, delegate()).unwrap();
+
+		assert_eq!(registry.register(&io, Some("echo"), delegate()), Err(DelegateError::PrefixInUse("echo".to_owned())));
+	}
+
+	#[test]
+	fn should_shadow_colliding_methods_by_default() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		registry.register(&io, None, delegate()).unwrap();
+
+		assert_eq!(registry.register(&io, None, delegate()), Ok(()));
+	}
+
+	#[test]
+	fn should_refuse_colliding_methods_in_strict_mode() {
+		let io = IoHandler::new();
+		let mut registry = MethodRegistry::new();
+		registry.set_strict(true);
+		registry.register(&io, None, delegate()).unwrap();
+
+		assert_eq!(registry.register(&io, None, delegate()), Err(DelegateError::MethodCollision("echo_hello".to_owned())));
+	}
+}
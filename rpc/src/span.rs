@@ -0,0 +1,122 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Timing spans for request handling.
+//!
+//! Spans are logged with `trace` level under the `rpc::span` target when they end, e.g.
+//! `span=12 parent=11 name=evm elapsed_us=840`. Spans entered while another span is open
+//! on the same thread are its children. When the target is disabled spans do nothing.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use std::time::Instant;
+use log::LogLevel;
+
+/// Log target spans are written to.
+pub const SPAN_TARGET: &'static str = "rpc::span";
+
+static NEXT_SPAN_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+thread_local!(static CURRENT_SPAN: Cell<usize> = Cell::new(0));
+
+struct ActiveSpan {
+	id: usize,
+	parent: usize,
+	name: String,
+	started: Instant,
+}
+
+/// Timing span. Ends when dropped.
+pub struct Span {
+	active: Option<ActiveSpan>,
+}
+
+impl Span {
+	/// Start a new span. The span is a child of the span currently open on this thread, if any.
+	pub fn enter(name: &str) -> Span {
+		if !log_enabled!(target: SPAN_TARGET, LogLevel::Trace) {
+			return Span { active: None };
+		}
+		let id = NEXT_SPAN_ID.fetch_add(1, Ordering::Relaxed) + 1;
+		let parent = CURRENT_SPAN.with(|current| {
+			let parent = current.get();
+			current.set(id);
+			parent
+		});
+		Span {
+			active: Some(ActiveSpan {
+				id: id,
+				parent: parent,
+				name: name.to_owned(),
+				started: Instant::now(),
+			})
+		}
+	}
+
+	/// Check if the span is being recorded.
+	pub fn is_active(&self) -> bool {
+		self.active.is_some()
+	}
+
+	/// Id of the span currently open on this thread, zero if there is none.
+	pub fn current() -> usize {
+		CURRENT_SPAN.with(|current| current.get())
+	}
+
+	/// Runs `f` with spans it enters on this thread attached to `parent`,
+	/// e.g. a span opened on the thread which handed the work over.
+	pub fn with_parent<F, R>(parent: usize, f: F) -> R where F: FnOnce() -> R {
+		let previous = CURRENT_SPAN.with(|current| {
+			let previous = current.get();
+			current.set(parent);
+			previous
+		});
+		let result = f();
+		CURRENT_SPAN.with(|current| current.set(previous));
+		result
+	}
+}
+
+impl Drop for Span {
+	fn drop(&mut self) {
+		if let Some(ref span) = self.active {
+			CURRENT_SPAN.with(|current| current.set(span.parent));
+			let elapsed = span.started.elapsed();
+			let elapsed_us = elapsed.as_secs() * 1000_000 + elapsed.subsec_nanos() as u64 / 1000;
+			trace!(target: SPAN_TARGET, "span={} parent={} name={} elapsed_us={}", span.id, span.parent, span.name, elapsed_us);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Span;
+
+	#[test]
+	fn should_not_record_spans_without_logger() {
+		let outer = Span::enter("outer");
+		let inner = Span::enter("inner");
+		assert!(!outer.is_active());
+		assert!(!inner.is_active());
+	}
+
+	#[test]
+	fn should_attach_spans_to_given_parent() {
+		assert_eq!(Span::current(), 0);
+		assert_eq!(Span::with_parent(7, Span::current), 7);
+		assert_eq!(Span::current(), 0);
+	}
+}
@@ -0,0 +1,124 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Errors reported when a server can't be started.
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use jsonrpc_http_server::RpcServerError;
+use ipc;
+
+/// Reason a server could not be started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartErrorKind {
+	/// Something else is already listening on the address.
+	AddressInUse,
+	/// Binding to the address requires more privileges.
+	PermissionDenied,
+	/// Address is malformed or does not belong to a local interface.
+	InvalidAddress,
+	/// Any other error.
+	Other,
+}
+
+impl StartErrorKind {
+	/// Maps an IO error onto a reason.
+	pub fn from_io(kind: io::ErrorKind) -> Self {
+		match kind {
+			io::ErrorKind::AddrInUse => StartErrorKind::AddressInUse,
+			io::ErrorKind::PermissionDenied => StartErrorKind::PermissionDenied,
+			io::ErrorKind::AddrNotAvailable | io::ErrorKind::InvalidInput => StartErrorKind::InvalidAddress,
+			_ => StartErrorKind::Other,
+		}
+	}
+}
+
+/// Error returned when an http or ipc server can't be started.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartError {
+	/// Why the server could not be started.
+	pub kind: StartErrorKind,
+	/// Address or ipc path the server was supposed to listen on.
+	pub address: String,
+	/// Description of the underlying error.
+	pub details: String,
+}
+
+impl StartError {
+	/// Creates an error for `address` caused by an IO error.
+	pub fn from_io(address: &str, error: &io::Error) -> Self {
+		StartError {
+			kind: StartErrorKind::from_io(error.kind()),
+			address: address.to_owned(),
+			details: format!("{}", error),
+		}
+	}
+
+	/// Creates an error for an http server which could not listen on `address`.
+	pub fn from_http(address: &SocketAddr, error: RpcServerError) -> Self {
+		match error {
+			RpcServerError::IoError(ref e) => StartError::from_io(&format!("{}", address), e),
+			e => StartError {
+				kind: StartErrorKind::Other,
+				address: format!("{}", address),
+				details: format!("{:?}", e),
+			},
+		}
+	}
+
+	/// Creates an error for an ipc server which could not listen on `path`.
+	pub fn from_ipc(path: &str, error: ipc::Error) -> Self {
+		match error {
+			ipc::Error::Io(ref e) => StartError::from_io(path, e),
+			e => StartError {
+				kind: StartErrorKind::Other,
+				address: path.to_owned(),
+				details: format!("{:?}", e),
+			},
+		}
+	}
+}
+
+impl fmt::Display for StartError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let reason = match self.kind {
+			StartErrorKind::AddressInUse => "address is already in use",
+			StartErrorKind::PermissionDenied => "no permissions to bind to the address",
+			StartErrorKind::InvalidAddress => "address is invalid or not available on this host",
+			StartErrorKind::Other => "unable to listen",
+		};
+		write!(f, "{}: {} ({})", self.address, reason, self.details)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use super::{StartError, StartErrorKind};
+
+	#[test]
+	fn should_map_io_error_kinds() {
+		let error = |kind| StartError::from_io("127.0.0.1:8545", &io::Error::new(kind, "test"));
+
+		assert_eq!(error(io::ErrorKind::AddrInUse).kind, StartErrorKind::AddressInUse);
+		assert_eq!(error(io::ErrorKind::PermissionDenied).kind, StartErrorKind::PermissionDenied);
+		assert_eq!(error(io::ErrorKind::AddrNotAvailable).kind, StartErrorKind::InvalidAddress);
+		assert_eq!(error(io::ErrorKind::InvalidInput).kind, StartErrorKind::InvalidAddress);
+		assert_eq!(error(io::ErrorKind::Other).kind, StartErrorKind::Other);
+		assert_eq!(error(io::ErrorKind::AddrInUse).address, "127.0.0.1:8545");
+	}
+}
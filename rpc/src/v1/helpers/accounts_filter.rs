@@ -0,0 +1,58 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Visibility of managed accounts to RPC callers.
+
+use util::Address;
+
+/// Accounts returned by `eth_accounts`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountsFilter {
+	/// All managed accounts are visible.
+	All,
+	/// Only listed accounts are visible. Empty list hides all accounts.
+	Only(Vec<Address>),
+}
+
+impl Default for AccountsFilter {
+	fn default() -> Self {
+		AccountsFilter::All
+	}
+}
+
+impl AccountsFilter {
+	/// Returns accounts visible to the caller, preserving their order.
+	pub fn apply(&self, accounts: Vec<Address>) -> Vec<Address> {
+		match *self {
+			AccountsFilter::All => accounts,
+			AccountsFilter::Only(ref visible) => accounts.into_iter().filter(|a| visible.contains(a)).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::Address;
+	use super::AccountsFilter;
+
+	#[test]
+	fn should_filter_accounts() {
+		let accounts = vec![Address::from(1), Address::from(2), Address::from(3)];
+		assert_eq!(AccountsFilter::All.apply(accounts.clone()), accounts);
+		assert_eq!(AccountsFilter::Only(vec![]).apply(accounts.clone()), vec![]);
+		assert_eq!(AccountsFilter::Only(vec![Address::from(3), Address::from(4)]).apply(accounts), vec![Address::from(3)]);
+	}
+}
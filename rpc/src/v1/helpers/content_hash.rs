@@ -0,0 +1,300 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fetches remote content and hashes it.
+
+use std::io::{self, Read};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use hyper::{Client, Url};
+use hyper::client::RedirectPolicy;
+use hyper::header::{Connection, ContentLength};
+use tiny_keccak::Keccak;
+use util::hash::{H256, FixedHash};
+
+/// Default maximal size of the fetched content in bytes.
+pub const MAX_CONTENT_SIZE: u64 = 5 * 1024 * 1024;
+/// Default maximal time spent fetching the content in milliseconds.
+pub const FETCH_TIMEOUT_MS: u64 = 10_000;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Limits applied to content fetches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchLimits {
+	/// Total time allowed for the fetch in milliseconds.
+	pub timeout_ms: u64,
+	/// Maximal size of the content in bytes.
+	pub max_size: u64,
+}
+
+impl Default for FetchLimits {
+	fn default() -> Self {
+		FetchLimits {
+			timeout_ms: FETCH_TIMEOUT_MS,
+			max_size: MAX_CONTENT_SIZE,
+		}
+	}
+}
+
+/// Content hashing errors.
+#[derive(Debug)]
+pub enum ContentHashError {
+	/// Only `http` and `https` URLs are allowed.
+	UnsupportedScheme,
+	/// Request could not be sent or the content could not be read.
+	Fetch(String),
+	/// Server responded with a non-success status.
+	Status(u16),
+	/// URL, or a redirect, points to a non-http URL or to a private, loopback or link-local address.
+	Blocked,
+	/// Content is larger than the size limit.
+	TooLarge,
+	/// Fetching took longer than the time limit.
+	Timeout,
+}
+
+fn is_http(url: &str) -> bool {
+	let url = url.to_lowercase();
+	url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+	match *ip {
+		IpAddr::V4(ref ip) => !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast()),
+		IpAddr::V6(ref ip) => {
+			if ip.is_loopback() || ip.is_unspecified() {
+				return false;
+			}
+			if let Some(ip) = ip.to_ipv4() {
+				return is_public_ip(&IpAddr::V4(ip));
+			}
+			let first = ip.segments()[0];
+			// fc00::/7 unique local, fe80::/10 link-local
+			!(first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80)
+		},
+	}
+}
+
+/// Checks that the host of `url` resolves to public addresses only,
+/// so that content can't be fetched from the node itself or its local network.
+fn is_public_host(url: &Url) -> bool {
+	let host = match url.serialize_host() {
+		Some(host) => host.trim_left_matches('[').trim_right_matches(']').to_owned(),
+		None => return false,
+	};
+	let port = url.port_or_default().unwrap_or(80);
+	match (host.as_str(), port).to_socket_addrs() {
+		Ok(addrs) => {
+			let addrs: Vec<_> = addrs.collect();
+			!addrs.is_empty() && addrs.iter().all(|addr| is_public_ip(&addr.ip()))
+		},
+		Err(_) => false,
+	}
+}
+
+fn follow_redirect(url: &Url) -> bool {
+	is_http(&format!("{}", url)) && is_public_host(url)
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+	e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock
+}
+
+/// Fetches content at `url` and returns its Keccak hash.
+/// Download size and total time are bounded by `limits`. Only `http` and `https` schemes are accepted,
+/// and both the URL and redirects must point to hosts with public addresses.
+pub fn hash_content(url: &str, limits: &FetchLimits) -> Result<H256, ContentHashError> {
+	fetch_and_hash(url, limits, is_public_host)
+}
+
+/// Same as `hash_content`, with the check for the host of the initial URL given by `allow_host`.
+fn fetch_and_hash(url: &str, limits: &FetchLimits, allow_host: fn(&Url) -> bool) -> Result<H256, ContentHashError> {
+	if !is_http(url) {
+		return Err(ContentHashError::UnsupportedScheme);
+	}
+	let parsed = try!(Url::parse(url).map_err(|e| ContentHashError::Fetch(format!("{}", e))));
+	if !allow_host(&parsed) {
+		return Err(ContentHashError::Blocked);
+	}
+
+	let timeout = Duration::from_millis(limits.timeout_ms);
+	let mut client = Client::new();
+	client.set_read_timeout(Some(timeout));
+	client.set_write_timeout(Some(timeout));
+	client.set_redirect_policy(RedirectPolicy::FollowIf(follow_redirect));
+
+	let started = Instant::now();
+	let mut response = try!(client.get(url)
+		.header(Connection::close())
+		.send()
+		.map_err(|e| ContentHashError::Fetch(format!("{}", e))));
+
+	if response.status.is_redirection() {
+		return Err(ContentHashError::Blocked);
+	}
+	if !response.status.is_success() {
+		return Err(ContentHashError::Status(response.status.to_u16()));
+	}
+	if let Some(&ContentLength(len)) = response.headers.get::<ContentLength>() {
+		if len > limits.max_size {
+			return Err(ContentHashError::TooLarge);
+		}
+	}
+
+	let mut keccak = Keccak::new_keccak256();
+	let mut buf = vec![0u8; CHUNK_SIZE];
+	let mut total = 0u64;
+	loop {
+		if started.elapsed() > timeout {
+			return Err(ContentHashError::Timeout);
+		}
+		let read = match response.read(&mut buf) {
+			Ok(read) => read,
+			Err(ref e) if is_timeout(e) => return Err(ContentHashError::Timeout),
+			Err(e) => return Err(ContentHashError::Fetch(format!("{}", e))),
+		};
+		if read == 0 {
+			break;
+		}
+		total += read as u64;
+		if total > limits.max_size {
+			return Err(ContentHashError::TooLarge);
+		}
+		keccak.update(&buf[0..read]);
+	}
+
+	let mut hash = H256::new();
+	keccak.finalize(&mut hash);
+	Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Read, Write};
+	use std::net::{IpAddr, TcpListener};
+	use std::str::FromStr;
+	use std::thread;
+	use std::time::Duration;
+	use hyper::Url;
+	use super::{hash_content, fetch_and_hash, is_public_ip, ContentHashError, FetchLimits};
+
+	fn serve_once(response: &'static str, delay_ms: u64) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut buf = [0u8; 1024];
+			let _ = stream.read(&mut buf);
+			let _ = stream.write_all(response.as_bytes());
+			let _ = stream.flush();
+			thread::sleep(Duration::from_millis(delay_ms));
+		});
+		format!("http://{}/", addr)
+	}
+
+	/// Fetches from the local test server, still refusing private redirects.
+	fn fetch_local(url: &str, limits: &FetchLimits) -> Result<::util::hash::H256, ContentHashError> {
+		fn any_host(_: &Url) -> bool { true }
+		fetch_and_hash(url, limits, any_host)
+	}
+
+	#[test]
+	fn rejects_non_http_schemes() {
+		let limits = FetchLimits::default();
+		match hash_content("file:///etc/passwd", &limits) {
+			Err(ContentHashError::UnsupportedScheme) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match hash_content("gopher://localhost/", &limits) {
+			Err(ContentHashError::UnsupportedScheme) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn times_out_on_slow_body() {
+		let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc", 1000);
+		let limits = FetchLimits { timeout_ms: 100, max_size: 1024 };
+		match fetch_local(&url, &limits) {
+			Err(ContentHashError::Timeout) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn rejects_content_length_over_limit() {
+		let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2048\r\n\r\n", 0);
+		let limits = FetchLimits { timeout_ms: 1000, max_size: 1024 };
+		match fetch_local(&url, &limits) {
+			Err(ContentHashError::TooLarge) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn rejects_streamed_body_over_limit() {
+		let body = concat!(
+			"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n",
+			"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+			"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+		);
+		let url = serve_once(body, 0);
+		let limits = FetchLimits { timeout_ms: 1000, max_size: 64 };
+		match fetch_local(&url, &limits) {
+			Err(ContentHashError::TooLarge) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn refuses_redirect_to_local_address() {
+		let url = serve_once("HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:8545/\r\nContent-Length: 0\r\n\r\n", 0);
+		match fetch_local(&url, &FetchLimits::default()) {
+			Err(ContentHashError::Blocked) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn refuses_local_address() {
+		let limits = FetchLimits::default();
+		match hash_content("http://127.0.0.1:8545/", &limits) {
+			Err(ContentHashError::Blocked) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match hash_content("http://[::1]:8545/", &limits) {
+			Err(ContentHashError::Blocked) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn classifies_public_addresses() {
+		let public = |ip: &str| is_public_ip(&IpAddr::from_str(ip).unwrap());
+		assert!(public("8.8.8.8"));
+		assert!(public("2001:4860:4860::8888"));
+		assert!(!public("127.0.0.1"));
+		assert!(!public("10.1.2.3"));
+		assert!(!public("192.168.0.1"));
+		assert!(!public("169.254.169.254"));
+		assert!(!public("0.0.0.0"));
+		assert!(!public("::1"));
+		assert!(!public("fd00::1"));
+		assert!(!public("fe80::1"));
+		assert!(!public("::ffff:127.0.0.1"));
+	}
+}
@@ -0,0 +1,139 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bound on EVM executions run concurrently on behalf of rpc requests.
+
+use std::sync::{Mutex, Condvar};
+use std::time::{Duration, Instant};
+
+/// Default number of EVM executions allowed to run at the same time.
+pub const DEFAULT_MAX_EXECUTIONS: usize = 8;
+
+/// Time in milliseconds a request waits for a free execution slot before it's rejected.
+pub const EXECUTION_QUEUE_TIMEOUT_MS: u64 = 1000;
+
+/// Semaphore limiting the number of simultaneous EVM executions (`eth_call`, `eth_estimateGas`, `trace_call`).
+///
+/// Executions beyond the limit wait for a slot to become free and are rejected
+/// if none does within the queue timeout.
+pub struct ExecutionLimit {
+	max: usize,
+	queue_timeout: Duration,
+	running: Mutex<usize>,
+	released: Condvar,
+}
+
+/// Slot of a running execution. Released when dropped.
+pub struct ExecutionPermit<'a> {
+	limit: &'a ExecutionLimit,
+}
+
+impl Default for ExecutionLimit {
+	fn default() -> Self {
+		ExecutionLimit::new(DEFAULT_MAX_EXECUTIONS)
+	}
+}
+
+impl ExecutionLimit {
+	/// Creates limit allowing at most `max` simultaneous executions. Zero means no limit.
+	pub fn new(max: usize) -> Self {
+		ExecutionLimit::with_queue_timeout(max, EXECUTION_QUEUE_TIMEOUT_MS)
+	}
+
+	/// Creates limit allowing at most `max` simultaneous executions, which waits up to
+	/// `queue_timeout_ms` for a free slot.
+	pub fn with_queue_timeout(max: usize, queue_timeout_ms: u64) -> Self {
+		ExecutionLimit {
+			max: max,
+			queue_timeout: Duration::from_millis(queue_timeout_ms),
+			running: Mutex::new(0),
+			released: Condvar::new(),
+		}
+	}
+
+	/// Takes an execution slot, waiting for one to become free if all are taken.
+	/// Returns `None` if no slot was freed within the queue timeout.
+	pub fn acquire(&self) -> Option<ExecutionPermit> {
+		let deadline = Instant::now() + self.queue_timeout;
+		let mut running = self.running.lock().unwrap();
+		while self.max != 0 && *running >= self.max {
+			let now = Instant::now();
+			if now >= deadline {
+				return None;
+			}
+			running = self.released.wait_timeout(running, deadline - now).unwrap().0;
+		}
+		*running += 1;
+		Some(ExecutionPermit { limit: self })
+	}
+
+	/// Number of executions currently running.
+	pub fn running(&self) -> usize {
+		*self.running.lock().unwrap()
+	}
+}
+
+impl<'a> Drop for ExecutionPermit<'a> {
+	fn drop(&mut self) {
+		*self.limit.running.lock().unwrap() -= 1;
+		self.limit.released.notify_one();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
+	use super::ExecutionLimit;
+
+	#[test]
+	fn should_reject_executions_over_limit() {
+		let limit = ExecutionLimit::with_queue_timeout(2, 10);
+		let first = limit.acquire();
+		let second = limit.acquire();
+		assert!(first.is_some());
+		assert!(second.is_some());
+		assert!(limit.acquire().is_none());
+		assert_eq!(limit.running(), 2);
+
+		drop(first);
+		assert_eq!(limit.running(), 1);
+		assert!(limit.acquire().is_some());
+	}
+
+	#[test]
+	fn should_queue_execution_until_slot_is_released() {
+		let limit = Arc::new(ExecutionLimit::with_queue_timeout(1, 5000));
+		let permit = limit.acquire();
+
+		let waiting = limit.clone();
+		let handle = thread::spawn(move || waiting.acquire().is_some());
+		thread::sleep(Duration::from_millis(50));
+		drop(permit);
+
+		assert!(handle.join().unwrap());
+		assert_eq!(limit.running(), 0);
+	}
+
+	#[test]
+	fn should_not_limit_with_zero_max() {
+		let limit = ExecutionLimit::with_queue_timeout(0, 0);
+		let permits = (0..100).map(|_| limit.acquire()).collect::<Vec<_>>();
+		assert!(permits.iter().all(Option::is_some));
+		assert_eq!(limit.running(), 100);
+	}
+}
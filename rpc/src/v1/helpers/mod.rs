@@ -17,7 +17,23 @@
 mod poll_manager;
 mod poll_filter;
 mod signing_queue;
+mod result_cache;
+mod content_hash;
+mod named_params;
+mod registry;
+mod accounts_filter;
+mod dapps;
+mod solc;
+mod execution_limit;
 
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::PollFilter;
 pub use self::signing_queue::{ConfirmationsQueue, SigningQueue};
+pub use self::result_cache::{ResultCache, ResultCacheStats, IMMUTABLE_RESULT_DEPTH, DEFAULT_RESULT_CACHE_SIZE};
+pub use self::content_hash::{hash_content, ContentHashError, FetchLimits};
+pub use self::named_params::named_params;
+pub use self::registry::{validate_name, address_lookup, content_lookup, decode_address, decode_word, NameError};
+pub use self::accounts_filter::AccountsFilter;
+pub use self::dapps::DappsProvider;
+pub use self::solc::{compile_solidity, CompileError, SolcConfig};
+pub use self::execution_limit::{ExecutionLimit, ExecutionPermit, DEFAULT_MAX_EXECUTIONS};
@@ -0,0 +1,86 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Named (by-name) parameters support.
+
+use jsonrpc_core::{Params, Value, Error};
+
+/// Converts parameters given as an object into positional parameters ordered as `names`.
+/// Positional parameters are returned unchanged, so handlers opting in accept both forms.
+///
+/// Fields missing from the object are passed as `null`. Trailing missing fields are omitted,
+/// so handlers with defaults for trailing parameters apply them. Unknown fields are rejected
+/// with invalid params error.
+pub fn named_params(params: Params, names: &[&str]) -> Result<Params, Error> {
+	match params {
+		Params::Map(mut map) => {
+			let mut values: Vec<Value> = names.iter().map(|name| map.remove(*name).unwrap_or(Value::Null)).collect();
+			if !map.is_empty() {
+				return Err(Error::invalid_params());
+			}
+			while values.last() == Some(&Value::Null) {
+				values.pop();
+			}
+			Ok(Params::Array(values))
+		},
+		params => Ok(params),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use jsonrpc_core::{Params, Value, Error};
+	use super::named_params;
+
+	fn map(fields: &[(&str, Value)]) -> Params {
+		let mut map = BTreeMap::new();
+		for &(ref name, ref value) in fields {
+			map.insert(name.to_string(), value.clone());
+		}
+		Params::Map(map)
+	}
+
+	#[test]
+	fn should_order_named_params() {
+		let params = map(&[("block", Value::String("latest".into())), ("address", Value::String("0x01".into()))]);
+		assert_eq!(
+			named_params(params, &["address", "block"]),
+			Ok(Params::Array(vec![Value::String("0x01".into()), Value::String("latest".into())]))
+		);
+	}
+
+	#[test]
+	fn should_pass_missing_params_as_null() {
+		let params = map(&[("block", Value::String("latest".into()))]);
+		assert_eq!(named_params(params, &["address", "block"]), Ok(Params::Array(vec![Value::Null, Value::String("latest".into())])));
+
+		let params = map(&[("address", Value::String("0x01".into()))]);
+		assert_eq!(named_params(params, &["address", "block"]), Ok(Params::Array(vec![Value::String("0x01".into())])));
+	}
+
+	#[test]
+	fn should_reject_unknown_params() {
+		let params = map(&[("address", Value::String("0x01".into())), ("other", Value::Bool(true))]);
+		assert_eq!(named_params(params, &["address", "block"]), Err(Error::invalid_params()));
+	}
+
+	#[test]
+	fn should_keep_positional_params() {
+		let params = Params::Array(vec![Value::Bool(true)]);
+		assert_eq!(named_params(params, &["address"]), Ok(Params::Array(vec![Value::Bool(true)])));
+	}
+}
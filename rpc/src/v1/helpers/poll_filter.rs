@@ -11,8 +11,9 @@ pub type BlockNumber = u64;
 pub enum PollFilter {
 	/// Number of last block which client was notified about.
 	Block(BlockNumber),
-	/// Hashes of all transactions which client was notified about.
-	PendingTransaction(Vec<H256>),
+	/// Hashes of all transactions which client was notified about and
+	/// whether full transactions should be returned instead of hashes.
+	PendingTransaction(Vec<H256>, bool),
 	/// Number of From block number, pending logs and log filter iself.
 	Logs(BlockNumber, HashSet<Log>, Filter)
 }
@@ -0,0 +1,138 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Name registry lookups.
+
+use util::hash::{Address, H256, FixedHash};
+use util::numbers::U256;
+use util::sha3::Hashable;
+
+/// Maximal length of a registered name in bytes.
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// Registry key under which names store their address.
+const ADDRESS_KEY: &'static str = "A";
+/// Registry key under which names store their content hash.
+const CONTENT_KEY: &'static str = "CONTENT";
+
+/// Reasons a name can't be looked up.
+#[derive(Debug, PartialEq)]
+pub enum NameError {
+	/// Name is empty.
+	Empty,
+	/// Name is longer than `MAX_NAME_LENGTH`.
+	TooLong,
+	/// Name contains a character other than lowercase letters, digits, `-` and `.`.
+	InvalidCharacter(char),
+	/// Name contains an empty dot-separated label.
+	EmptyLabel,
+}
+
+/// Checks that `name` is well-formed before it's looked up in the registry.
+pub fn validate_name(name: &str) -> Result<(), NameError> {
+	if name.is_empty() {
+		return Err(NameError::Empty);
+	}
+	if name.len() > MAX_NAME_LENGTH {
+		return Err(NameError::TooLong);
+	}
+	if let Some(c) = name.chars().find(|c| match *c { 'a'...'z' | '0'...'9' | '-' | '.' => false, _ => true }) {
+		return Err(NameError::InvalidCharacter(c));
+	}
+	if name.split('.').any(|label| label.is_empty()) {
+		return Err(NameError::EmptyLabel);
+	}
+	Ok(())
+}
+
+fn encode_lookup(signature: &str, name: &str, key: &str) -> Vec<u8> {
+	let mut data = signature.sha3()[0..4].to_vec();
+	data.extend_from_slice(&name.sha3());
+	data.extend_from_slice(&H256::from(U256::from(64)));
+	data.extend_from_slice(&H256::from(U256::from(key.len())));
+	data.extend_from_slice(key.as_bytes());
+	let padding = (32 - key.len() % 32) % 32;
+	data.extend(::std::iter::repeat(0u8).take(padding));
+	data
+}
+
+/// Call data of `getAddress(bytes32,string)` returning address registered for `name`.
+pub fn address_lookup(name: &str) -> Vec<u8> {
+	encode_lookup("getAddress(bytes32,string)", name, ADDRESS_KEY)
+}
+
+/// Call data of `getData(bytes32,string)` returning content hash registered for `name`.
+pub fn content_lookup(name: &str) -> Vec<u8> {
+	encode_lookup("getData(bytes32,string)", name, CONTENT_KEY)
+}
+
+/// Decodes a single 32 byte word returned by the registry. Returns `None` for zero or malformed output.
+pub fn decode_word(output: &[u8]) -> Option<H256> {
+	if output.len() < 32 {
+		return None;
+	}
+	let word = H256::from_slice(&output[0..32]);
+	match word.is_zero() {
+		true => None,
+		false => Some(word),
+	}
+}
+
+/// Decodes an address returned by the registry. Returns `None` for zero or malformed output.
+pub fn decode_address(output: &[u8]) -> Option<Address> {
+	decode_word(output).map(Address::from).and_then(|address| match address.is_zero() {
+		true => None,
+		false => Some(address),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use util::hash::{Address, H256, FixedHash};
+	use super::{validate_name, address_lookup, decode_address, decode_word, NameError, MAX_NAME_LENGTH};
+
+	#[test]
+	fn should_validate_names() {
+		assert_eq!(validate_name("gavofyork"), Ok(()));
+		assert_eq!(validate_name("my-dapp.parity"), Ok(()));
+		assert_eq!(validate_name(""), Err(NameError::Empty));
+		assert_eq!(validate_name(&vec!["a"; MAX_NAME_LENGTH + 1].concat()), Err(NameError::TooLong));
+		assert_eq!(validate_name("Gav"), Err(NameError::InvalidCharacter('G')));
+		assert_eq!(validate_name("gav..parity"), Err(NameError::EmptyLabel));
+		assert_eq!(validate_name(".gav"), Err(NameError::EmptyLabel));
+	}
+
+	#[test]
+	fn should_encode_address_lookup() {
+		let data = address_lookup("gavofyork");
+		assert_eq!(data.len(), 4 + 4 * 32);
+		assert_eq!(data[4 + 2 * 32 + 31], 64);
+		assert_eq!(data[4 + 3 * 32 + 31], 1);
+		assert_eq!(data[4 + 4 * 32 - 32], b'A');
+	}
+
+	#[test]
+	fn should_decode_registry_output() {
+		let mut output = vec![0u8; 32];
+		assert_eq!(decode_word(&output), None);
+		assert_eq!(decode_address(&output), None);
+		assert_eq!(decode_word(&output[0..16]), None);
+
+		output[31] = 1;
+		assert_eq!(decode_word(&output), Some(H256::from(1)));
+		assert_eq!(decode_address(&output), Some(Address::from(1)));
+	}
+}
@@ -0,0 +1,217 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! LRU cache of rpc results which are known to never change.
+
+use std::sync::Mutex;
+use std::collections::{HashMap, BTreeMap};
+use jsonrpc_core::Value;
+
+/// Number of blocks below the best block after which results are considered immutable.
+pub const IMMUTABLE_RESULT_DEPTH: u64 = 64;
+
+/// Default number of cached results.
+pub const DEFAULT_RESULT_CACHE_SIZE: usize = 1024;
+
+/// Cache usage statistics.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ResultCacheStats {
+	/// Number of lookups answered from cache
+	pub hits: usize,
+	/// Number of lookups which had to be computed
+	pub misses: usize,
+	/// Number of currently cached results
+	pub size: usize,
+	/// Maximal number of cached results
+	pub capacity: usize,
+}
+
+struct Entries {
+	// value and its last use
+	values: HashMap<String, (Value, u64)>,
+	// keys by their last use, least recently used first
+	order: BTreeMap<u64, String>,
+	// incremented on every use
+	tick: u64,
+	hits: usize,
+	misses: usize,
+}
+
+impl Entries {
+	fn next_tick(&mut self) -> u64 {
+		self.tick += 1;
+		self.tick
+	}
+}
+
+/// Least recently used cache of results keyed by method name and params.
+///
+/// Only results which can never change (i.e. describing blocks deeper than
+/// `IMMUTABLE_RESULT_DEPTH`) should be inserted.
+pub struct ResultCache {
+	capacity: usize,
+	entries: Mutex<Entries>,
+}
+
+impl Default for ResultCache {
+	fn default() -> Self {
+		ResultCache::new(DEFAULT_RESULT_CACHE_SIZE)
+	}
+}
+
+impl ResultCache {
+	/// Creates new cache holding at most `capacity` results. Zero capacity disables the cache.
+	pub fn new(capacity: usize) -> Self {
+		ResultCache {
+			capacity: capacity,
+			entries: Mutex::new(Entries {
+				values: HashMap::new(),
+				order: BTreeMap::new(),
+				tick: 0,
+				hits: 0,
+				misses: 0,
+			}),
+		}
+	}
+
+	/// Builds cache key for given method and its params.
+	pub fn key(method: &str, params: &str) -> String {
+		format!("{}:{}", method, params)
+	}
+
+	/// Returns cached result and marks it as recently used.
+	pub fn get(&self, key: &str) -> Option<Value> {
+		if self.capacity == 0 {
+			return None;
+		}
+
+		let mut entries = self.entries.lock().unwrap();
+		let tick = entries.next_tick();
+		let found = entries.values.get_mut(key).map(|entry| {
+			let last_use = entry.1;
+			entry.1 = tick;
+			(entry.0.clone(), last_use)
+		});
+		match found {
+			Some((value, last_use)) => {
+				entries.hits += 1;
+				if let Some(k) = entries.order.remove(&last_use) {
+					entries.order.insert(tick, k);
+				}
+				Some(value)
+			},
+			None => {
+				entries.misses += 1;
+				None
+			}
+		}
+	}
+
+	/// Caches result, evicting least recently used one if the cache is full.
+	pub fn insert(&self, key: String, value: Value) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		let mut entries = self.entries.lock().unwrap();
+		let tick = entries.next_tick();
+		if let Some((_, last_use)) = entries.values.insert(key.clone(), (value, tick)) {
+			entries.order.remove(&last_use);
+		}
+		entries.order.insert(tick, key);
+
+		while entries.order.len() > self.capacity {
+			let oldest = *entries.order.keys().next().expect("order has more entries than capacity; qed");
+			if let Some(evicted) = entries.order.remove(&oldest) {
+				entries.values.remove(&evicted);
+			}
+		}
+	}
+
+	/// Removes all cached results.
+	pub fn clear(&self) {
+		let mut entries = self.entries.lock().unwrap();
+		entries.values.clear();
+		entries.order.clear();
+	}
+
+	/// Returns cache usage statistics.
+	pub fn stats(&self) -> ResultCacheStats {
+		let entries = self.entries.lock().unwrap();
+		ResultCacheStats {
+			hits: entries.hits,
+			misses: entries.misses,
+			size: entries.values.len(),
+			capacity: self.capacity,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use jsonrpc_core::Value;
+	use v1::helpers::{ResultCache, ResultCacheStats};
+
+	#[test]
+	fn should_count_hits_and_misses() {
+		let cache = ResultCache::new(2);
+		assert_eq!(cache.get("a"), None);
+		cache.insert("a".to_owned(), Value::U64(1));
+		assert_eq!(cache.get("a"), Some(Value::U64(1)));
+
+		assert_eq!(cache.stats(), ResultCacheStats {
+			hits: 1,
+			misses: 1,
+			size: 1,
+			capacity: 2,
+		});
+	}
+
+	#[test]
+	fn should_evict_least_recently_used() {
+		let cache = ResultCache::new(2);
+		cache.insert("a".to_owned(), Value::U64(1));
+		cache.insert("b".to_owned(), Value::U64(2));
+		// touch `a` so that `b` becomes the oldest entry
+		assert!(cache.get("a").is_some());
+		cache.insert("c".to_owned(), Value::U64(3));
+
+		assert_eq!(cache.get("b"), None);
+		assert_eq!(cache.get("a"), Some(Value::U64(1)));
+		assert_eq!(cache.get("c"), Some(Value::U64(3)));
+	}
+
+	#[test]
+	fn should_refresh_reinserted_result() {
+		let cache = ResultCache::new(2);
+		cache.insert("a".to_owned(), Value::U64(1));
+		cache.insert("b".to_owned(), Value::U64(2));
+		cache.insert("a".to_owned(), Value::U64(1));
+		cache.insert("c".to_owned(), Value::U64(3));
+
+		assert_eq!(cache.get("b"), None);
+		assert_eq!(cache.get("a"), Some(Value::U64(1)));
+		assert_eq!(cache.stats().size, 2);
+	}
+
+	#[test]
+	fn should_not_cache_when_disabled() {
+		let cache = ResultCache::new(0);
+		cache.insert("a".to_owned(), Value::U64(1));
+		assert_eq!(cache.get("a"), None);
+		assert_eq!(cache.stats().size, 0);
+	}
+}
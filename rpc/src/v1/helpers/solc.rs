@@ -0,0 +1,300 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compiles Solidity sources with an external `solc` binary.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, Condvar};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde_json::{self, Value};
+
+/// Default maximal time a compilation may take in milliseconds.
+pub const COMPILE_TIMEOUT_MS: u64 = 10_000;
+/// Default maximal size of the compiled source in bytes.
+pub const MAX_SOURCE_SIZE: usize = 256 * 1024;
+/// Maximal size of the compiler output kept in memory.
+const MAX_OUTPUT_SIZE: usize = 16 * 1024 * 1024;
+/// Interval in milliseconds at which the compiler process is checked for exit.
+const WAIT_POLL_MS: u64 = 10;
+
+/// Solidity compiler configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolcConfig {
+	/// Path to the `solc` binary.
+	pub path: String,
+	/// Time after which the compiler is killed in milliseconds.
+	pub timeout_ms: u64,
+	/// Maximal size of the source in bytes.
+	pub max_source_size: usize,
+}
+
+impl SolcConfig {
+	/// Creates configuration for the binary at `path` with default limits.
+	pub fn new(path: &str) -> Self {
+		SolcConfig {
+			path: path.to_owned(),
+			timeout_ms: COMPILE_TIMEOUT_MS,
+			max_source_size: MAX_SOURCE_SIZE,
+		}
+	}
+}
+
+/// Compiled contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledContract {
+	/// Hex-encoded bytecode without `0x` prefix.
+	pub code: String,
+	/// ABI definition.
+	pub abi: Value,
+}
+
+/// Compilation errors.
+#[derive(Debug)]
+pub enum CompileError {
+	/// Source is larger than the configured limit.
+	SourceTooLarge,
+	/// Compiler could not be started.
+	Unavailable(io::Error),
+	/// Compiler was killed after running for longer than the configured limit.
+	Timeout,
+	/// Compiler rejected the source. Contains its error output.
+	Failed(String),
+	/// Compiler output could not be understood.
+	InvalidOutput,
+}
+
+/// Reads `reader` to the end, keeping at most `MAX_OUTPUT_SIZE` bytes. The rest is discarded
+/// so that the compiler doesn't block on a full pipe.
+fn read_bounded<R: Read>(mut reader: R) -> String {
+	let mut output = Vec::new();
+	let mut buf = [0u8; 4096];
+	loop {
+		match reader.read(&mut buf) {
+			Ok(0) | Err(_) => break,
+			Ok(read) => if output.len() + read <= MAX_OUTPUT_SIZE {
+				output.extend_from_slice(&buf[0..read]);
+			},
+		}
+	}
+	String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Compiles `source` and returns contracts by name.
+/// The compiler runs in the temporary directory with an empty environment and is killed
+/// once `config.timeout_ms` passes.
+pub fn compile_solidity(config: &SolcConfig, source: &str) -> Result<BTreeMap<String, CompiledContract>, CompileError> {
+	if source.len() > config.max_source_size {
+		return Err(CompileError::SourceTooLarge);
+	}
+
+	let mut child = try!(Command::new(&config.path)
+		.arg("--combined-json")
+		.arg("abi,bin")
+		.env_clear()
+		.current_dir(env::temp_dir())
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(CompileError::Unavailable));
+
+	let mut stdin = child.stdin.take().expect("stdin is piped; qed");
+	let stdout = child.stdout.take().expect("stdout is piped; qed");
+	let stderr = child.stderr.take().expect("stderr is piped; qed");
+	let child = Arc::new(Mutex::new(child));
+	let finished = Arc::new((Mutex::new(false), Condvar::new()));
+
+	let watchdog = {
+		let child = child.clone();
+		let finished = finished.clone();
+		let timeout = Duration::from_millis(config.timeout_ms);
+		thread::spawn(move || {
+			let &(ref lock, ref cvar) = &*finished;
+			let deadline = Instant::now() + timeout;
+			let mut done = lock.lock().unwrap();
+			while !*done {
+				let now = Instant::now();
+				if now >= deadline {
+					let _ = child.lock().unwrap().kill();
+					return true;
+				}
+				done = cvar.wait_timeout(done, deadline - now).unwrap().0;
+			}
+			false
+		})
+	};
+	let writer = {
+		let source = source.to_owned();
+		thread::spawn(move || {
+			let _ = stdin.write_all(source.as_bytes());
+		})
+	};
+	let errors = thread::spawn(move || read_bounded(stderr));
+
+	// stdout is closed once the compiler exits or is killed.
+	let output = read_bounded(stdout);
+	let status = wait(&child);
+	{
+		let &(ref lock, ref cvar) = &*finished;
+		*lock.lock().unwrap() = true;
+		cvar.notify_one();
+	}
+	let timed_out = watchdog.join().unwrap_or(false);
+	let _ = writer.join();
+	let errors = errors.join().unwrap_or_else(|_| String::new());
+
+	if timed_out {
+		return Err(CompileError::Timeout);
+	}
+	match status {
+		Ok(ref status) if status.success() => parse_output(&output),
+		_ => Err(CompileError::Failed(errors)),
+	}
+}
+
+/// Waits for the child to exit. The lock is only held while polling,
+/// so the watchdog can kill a child which closed its stdout but keeps running.
+fn wait(child: &Mutex<Child>) -> io::Result<ExitStatus> {
+	loop {
+		if let Some(status) = try!(child.lock().unwrap().try_wait()) {
+			return Ok(status);
+		}
+		thread::sleep(Duration::from_millis(WAIT_POLL_MS));
+	}
+}
+
+/// Parses `solc --combined-json abi,bin` output.
+fn parse_output(output: &str) -> Result<BTreeMap<String, CompiledContract>, CompileError> {
+	let output: Value = try!(serde_json::from_str(output).map_err(|_| CompileError::InvalidOutput));
+	let contracts = try!(output.find("contracts").and_then(Value::as_object).ok_or(CompileError::InvalidOutput));
+	let mut compiled = BTreeMap::new();
+	for (name, contract) in contracts {
+		let code = try!(contract.find("bin").and_then(Value::as_string).ok_or(CompileError::InvalidOutput));
+		// solc emits the ABI as a JSON encoded string
+		let abi = match contract.find("abi") {
+			Some(&Value::String(ref abi)) => try!(serde_json::from_str(abi).map_err(|_| CompileError::InvalidOutput)),
+			Some(abi) => abi.clone(),
+			None => return Err(CompileError::InvalidOutput),
+		};
+		compiled.insert(name.clone(), CompiledContract {
+			code: code.to_owned(),
+			abi: abi,
+		});
+	}
+	Ok(compiled)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{compile_solidity, parse_output, CompileError, SolcConfig};
+
+	#[test]
+	fn rejects_large_source() {
+		let mut config = SolcConfig::new("/nonexistent/solc");
+		config.max_source_size = 4;
+		match compile_solidity(&config, "contract A {}") {
+			Err(CompileError::SourceTooLarge) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn reports_missing_compiler() {
+		match compile_solidity(&SolcConfig::new("/nonexistent/solc"), "contract A {}") {
+			Err(CompileError::Unavailable(_)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn parses_combined_json() {
+		let output = r#"{"contracts":{"A":{"abi":"[{\"type\":\"function\",\"name\":\"f\"}]","bin":"6060"}},"version":"0.3.5"}"#;
+		let compiled = parse_output(output).unwrap();
+		let contract = compiled.get("A").unwrap();
+		assert_eq!(contract.code, "6060");
+		assert!(contract.abi.is_array());
+
+		match parse_output("{}") {
+			Err(CompileError::InvalidOutput) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[cfg(unix)]
+	fn fake_solc(dir: &::devtools::RandomTempPath, script: &str) -> SolcConfig {
+		use std::fs::{self, File};
+		use std::io::Write;
+		use std::os::unix::fs::PermissionsExt;
+
+		let path = dir.new_in("solc");
+		let mut file = File::create(&path).unwrap();
+		file.write_all(script.as_bytes()).unwrap();
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+		SolcConfig::new(&path)
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn runs_compiler() {
+		let dir = ::devtools::RandomTempPath::create_dir();
+		let config = fake_solc(&dir, "#!/bin/sh\nwhile read line; do :; done\necho '{\"contracts\":{\"A\":{\"abi\":\"[]\",\"bin\":\"60606040\"}}}'\n");
+
+		let compiled = compile_solidity(&config, "contract A {}\n").unwrap();
+		assert_eq!(compiled.get("A").unwrap().code, "60606040");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn reports_compiler_errors() {
+		let dir = ::devtools::RandomTempPath::create_dir();
+		let config = fake_solc(&dir, "#!/bin/sh\necho 'Error: Expected identifier' >&2\nexit 1\n");
+
+		match compile_solidity(&config, "contract {}") {
+			Err(CompileError::Failed(ref errors)) => assert!(errors.contains("Expected identifier")),
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn kills_slow_compiler() {
+		let dir = ::devtools::RandomTempPath::create_dir();
+		let mut config = fake_solc(&dir, "#!/bin/sh\nwhile :; do :; done\n");
+		config.timeout_ms = 100;
+
+		match compile_solidity(&config, "contract A {}") {
+			Err(CompileError::Timeout) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn kills_compiler_which_closed_its_output() {
+		let dir = ::devtools::RandomTempPath::create_dir();
+		let mut config = fake_solc(&dir, "#!/bin/sh\nexec >&- 2>&-\nwhile :; do :; done\n");
+		config.timeout_ms = 100;
+
+		match compile_solidity(&config, "contract A {}") {
+			Err(CompileError::Timeout) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+}
@@ -18,6 +18,8 @@
 
 extern crate ethash;
 
+use std::cmp;
+use std::collections::BTreeMap;
 use std::sync::{Arc, Weak, Mutex};
 use std::ops::Deref;
 use ethsync::{SyncProvider, SyncState};
@@ -25,21 +27,28 @@ use ethcore::miner::{MinerService, ExternalMinerService};
 use jsonrpc_core::*;
 use util::numbers::*;
 use util::sha3::*;
-use util::rlp::{encode, decode, UntrustedRlp, View};
+use util::rlp::{encode, decode, UntrustedRlp, View, SHA3_EMPTY_LIST_RLP};
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{MiningBlockChainClient, BlockID, TransactionID, UncleID};
 use ethcore::block::IsBlock;
 use ethcore::views::*;
 use ethcore::ethereum::Ethash;
 use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
-use ethcore::log_entry::LogEntry;
+use ethcore::log_entry::{LogEntry, LocalizedLogEntry};
 use ethcore::filter::Filter as EthcoreFilter;
 use self::ethash::SeedHashCompute;
 use v1::traits::Eth;
-use v1::types::{Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo, Transaction, CallRequest, OptionalValue, Index, Filter, Log, Receipt};
-use v1::impls::{dispatch_transaction, error_codes};
+use v1::types::{Block, BlockTransactions, BlockNumber, Bytes, FeeHistory, SyncStatus, SyncInfo, Transaction, CallRequest, OptionalValue, Index, Filter, Log, Receipt, LogCursor, LogsPagination, LogsPage};
+use v1::impls::{dispatch_transaction, error_codes, execution_capacity_error};
+use v1::helpers::{ResultCache, IMMUTABLE_RESULT_DEPTH, AccountsFilter, named_params, compile_solidity, CompileError, SolcConfig, ExecutionLimit};
+use span::Span;
 use serde;
 
+/// Number of recent blocks sampled when suggesting a priority fee.
+const TIP_SAMPLE_SIZE: usize = 20;
+/// Maximal number of blocks returned by `eth_feeHistory`.
+const MAX_FEE_HISTORY_BLOCKS: usize = 1024;
+
 /// Eth rpc implementation.
 pub struct EthClient<C, S, M, EM> where
 	C: MiningBlockChainClient,
@@ -54,6 +63,12 @@ pub struct EthClient<C, S, M, EM> where
 	external_miner: Arc<EM>,
 	seed_compute: Mutex<SeedHashCompute>,
 	allow_pending_receipt_query: bool,
+	result_cache: Arc<ResultCache>,
+	tip_percentile: usize,
+	tip_cache: Mutex<Option<(H256, U256)>>,
+	accounts_filter: AccountsFilter,
+	solc: Option<SolcConfig>,
+	execution_limit: Arc<ExecutionLimit>,
 }
 
 impl<C, S, M, EM> EthClient<C, S, M, EM> where
@@ -63,7 +78,7 @@ impl<C, S, M, EM> EthClient<C, S, M, EM> where
 	EM: ExternalMinerService {
 
 	/// Creates new EthClient.
-	pub fn new(client: &Arc<C>, sync: &Arc<S>, accounts: &Arc<AccountProvider>, miner: &Arc<M>, em: &Arc<EM>, allow_pending_receipt_query: bool)
+	pub fn new(client: &Arc<C>, sync: &Arc<S>, accounts: &Arc<AccountProvider>, miner: &Arc<M>, em: &Arc<EM>, allow_pending_receipt_query: bool, result_cache: &Arc<ResultCache>, tip_percentile: usize, accounts_filter: AccountsFilter, solc: Option<SolcConfig>, execution_limit: &Arc<ExecutionLimit>)
 		-> EthClient<C, S, M, EM> {
 		EthClient {
 			client: Arc::downgrade(client),
@@ -73,7 +88,33 @@ impl<C, S, M, EM> EthClient<C, S, M, EM> where
 			external_miner: em.clone(),
 			seed_compute: Mutex::new(SeedHashCompute::new()),
 			allow_pending_receipt_query: allow_pending_receipt_query,
+			result_cache: result_cache.clone(),
+			tip_percentile: cmp::min(tip_percentile, 100),
+			tip_cache: Mutex::new(None),
+			accounts_filter: accounts_filter,
+			solc: solc,
+			execution_limit: execution_limit.clone(),
+		}
+	}
+
+	/// Returns true if block with given number is deep enough to never be reorganized.
+	fn is_immutable(&self, client: &C, number: u64) -> bool {
+		number + IMMUTABLE_RESULT_DEPTH <= client.chain_info().best_block_number
+	}
+
+	/// Returns block, caching the result under `cache_key` if the block is immutable.
+	fn cached_block(&self, cache_key: String, id: BlockID, include_txs: bool) -> Result<Value, Error> {
+		if let Some(value) = self.result_cache.get(&cache_key) {
+			return Ok(value);
+		}
+
+		let client = take_weak!(self.client);
+		let number = client.block_header(id.clone()).map(|bytes| HeaderView::new(&bytes).number());
+		let value = try!(self.block(id, include_txs));
+		if number.map_or(false, |n| self.is_immutable(&client, n)) {
+			self.result_cache.insert(cache_key, value.clone());
 		}
+		Ok(value)
 	}
 
 	fn block(&self, id: BlockID, include_txs: bool) -> Result<Value, Error> {
@@ -115,6 +156,51 @@ impl<C, S, M, EM> EthClient<C, S, M, EM> where
 		}
 	}
 
+	/// Returns the block currently being mined on top of the best block.
+	/// Hash and seal are unknown until the block is sealed.
+	fn pending_block(&self, include_txs: bool) -> Result<Value, Error> {
+		let client = take_weak!(self.client);
+		let miner = take_weak!(self.miner);
+		match (client.block_header(BlockID::Latest), client.block_total_difficulty(BlockID::Latest)) {
+			(Some(bytes), Some(total_difficulty)) => {
+				let parent = HeaderView::new(&bytes);
+				let transactions = miner.pending_transactions();
+				let receipts = miner.pending_receipts();
+				let gas_used = receipts.values().fold(U256::zero(), |acc, r| if r.gas_used > acc { r.gas_used } else { acc });
+				let logs_bloom = receipts.values().fold(H2048::new(), |acc, r| acc | r.log_bloom.clone());
+				let block = Block {
+					hash: OptionalValue::Null,
+					parent_hash: parent.sha3(),
+					uncles_hash: SHA3_EMPTY_LIST_RLP,
+					author: miner.author(),
+					miner: miner.author(),
+					state_root: H256::new(),
+					transactions_root: H256::new(),
+					receipts_root: H256::new(),
+					number: OptionalValue::Value(U256::from(parent.number() + 1)),
+					gas_used: gas_used,
+					gas_limit: parent.gas_limit(),
+					logs_bloom: logs_bloom,
+					timestamp: U256::from(parent.timestamp()),
+					difficulty: parent.difficulty(),
+					total_difficulty: total_difficulty + parent.difficulty(),
+					seal_fields: vec![],
+					uncles: vec![],
+					transactions: {
+						if include_txs {
+							BlockTransactions::Full(transactions.into_iter().map(From::from).collect())
+						} else {
+							BlockTransactions::Hashes(transactions.iter().map(|t| t.hash()).collect())
+						}
+					},
+					extra_data: Bytes::new(miner.extra_data()),
+				};
+				to_value(&block)
+			},
+			_ => Ok(Value::Null)
+		}
+	}
+
 	fn transaction(&self, id: TransactionID) -> Result<Value, Error> {
 		match take_weak!(self.client).transaction(id) {
 			Some(t) => to_value(&Transaction::from(t)),
@@ -122,6 +208,32 @@ impl<C, S, M, EM> EthClient<C, S, M, EM> where
 		}
 	}
 
+	fn pending_transaction(&self, index: usize) -> Result<Value, Error> {
+		let client = take_weak!(self.client);
+		let miner = take_weak!(self.miner);
+		let number = match client.block_header(BlockID::Latest) {
+			Some(bytes) => HeaderView::new(&bytes).number() + 1,
+			None => return Ok(Value::Null),
+		};
+		match miner.pending_transactions().into_iter().nth(index) {
+			Some(t) => {
+				let mut transaction = Transaction::from(t);
+				// the pending block has no hash yet, but its position is known
+				transaction.block_number = OptionalValue::Value(U256::from(number));
+				transaction.transaction_index = OptionalValue::Value(U256::from(index));
+				to_value(&transaction)
+			},
+			None => Ok(Value::Null)
+		}
+	}
+
+	fn raw_transaction(&self, id: TransactionID) -> Result<Value, Error> {
+		match take_weak!(self.client).transaction(id) {
+			Some(t) => to_value(&Bytes::new(encode(&t.signed).to_vec())),
+			None => Ok(Value::Null)
+		}
+	}
+
 	fn uncle(&self, id: UncleID) -> Result<Value, Error> {
 		let client = take_weak!(self.client);
 		match client.uncle(id).and_then(|u| client.block_total_difficulty(BlockID::Hash(u.parent_hash().clone())).map(|diff| (diff, u))) {
@@ -162,6 +274,26 @@ impl<C, S, M, EM> EthClient<C, S, M, EM> where
 		)
 	}
 
+	/// Suggests a priority fee: the configured percentile of fees paid over the cheapest
+	/// transaction of the same block in the last `TIP_SAMPLE_SIZE` blocks. Cached per best block.
+	fn suggested_tip(&self) -> Result<U256, Error> {
+		let client = take_weak!(self.client);
+		let best_block = client.chain_info().best_block_hash;
+		let mut cache = self.tip_cache.lock().unwrap();
+		if let Some((hash, tip)) = *cache {
+			if hash == best_block {
+				return Ok(tip);
+			}
+		}
+		let corpus = client.priority_fee_statistics(TIP_SAMPLE_SIZE);
+		let tip = match corpus.len() {
+			0 => U256::zero(),
+			n => corpus[self.tip_percentile * (n - 1) / 100],
+		};
+		*cache = Some((best_block, tip));
+		Ok(tip)
+	}
+
 	fn sign_call(&self, request: CallRequest) -> Result<SignedTransaction, Error> {
 		let client = take_weak!(self.client);
 		let from = request.from.unwrap_or(Address::zero());
@@ -218,11 +350,80 @@ fn from_params_default_third<F1, F2>(params: Params) -> Result<(F1, F2, BlockNum
 	}
 }
 
-fn make_unsupported_err() -> Error {
-	Error {
-		code: ErrorCode::ServerError(error_codes::UNSUPPORTED_REQUEST_CODE),
-		message: "Unsupported request.".into(),
-		data: None
+/// Minimal gas price, gas used ratio and priority fee percentiles of a single block.
+/// Blocks without transactions have zero minimal price and rewards.
+fn block_fees(block: &BlockView, percentiles: &[f64]) -> (U256, f64, Vec<U256>) {
+	let header = block.header_view();
+	let gas_limit = header.gas_limit();
+	let gas_used_ratio = match gas_limit.is_zero() {
+		true => 0.0,
+		false => header.gas_used().low_u64() as f64 / gas_limit.low_u64() as f64,
+	};
+
+	let mut prices = block.transaction_views().iter().map(|t| t.gas_price()).collect::<Vec<_>>();
+	prices.sort();
+	let base = prices.first().cloned().unwrap_or_else(U256::zero);
+	let rewards = percentiles.iter().map(|p| match prices.len() {
+		0 => U256::zero(),
+		n => prices[(p / 100.0 * (n - 1) as f64).round() as usize] - base,
+	}).collect();
+	(base, gas_used_ratio, rewards)
+}
+
+/// Narrows the query to a single page: the search starts at the cursor's block and stops
+/// once enough logs are found to fill the page and tell whether another one follows.
+fn page_filter<C>(client: &C, mut filter: EthcoreFilter, pagination: &LogsPagination) -> EthcoreFilter where C: MiningBlockChainClient {
+	// logs of the cursor's block preceding the cursor are fetched and skipped again
+	let skipped = match pagination.cursor {
+		Some(ref cursor) => {
+			let from = client.block_header(filter.from_block.clone()).map(|header| HeaderView::new(&header).number());
+			if from.map_or(false, |from| from < cursor.block_number) {
+				filter.from_block = BlockID::Number(cursor.block_number);
+			}
+			cursor.log_index
+		},
+		None => 0,
+	};
+	filter.limit = pagination.limit.map(|limit| limit.saturating_add(1).saturating_add(skipped));
+	filter
+}
+
+/// Selects single page of mined logs. Logs are expected to be sorted by block number and log index.
+/// Pending logs have no stable position and are never paginated.
+fn paginate_logs(logs: Vec<LocalizedLogEntry>, pagination: LogsPagination) -> LogsPage {
+	let limit = pagination.limit.unwrap_or(usize::max_value());
+	let mut logs = logs.into_iter()
+		.skip_while(|log| match pagination.cursor {
+			Some(ref cursor) => LogCursor::new(log.block_number, log.log_index) < *cursor,
+			None => false,
+		})
+		.take(limit.saturating_add(1))
+		.collect::<Vec<LocalizedLogEntry>>();
+
+	let next_cursor = match logs.len() > limit {
+		true => logs.pop().map(|log| LogCursor::new(log.block_number, log.log_index)),
+		false => None,
+	};
+
+	LogsPage {
+		logs: logs.into_iter().map(From::from).collect(),
+		next_cursor: next_cursor,
+	}
+}
+
+/// Error for a state query at a block that returned nothing: the block is unknown or its state has been pruned.
+fn state_unavailable_err<C>(client: &C, id: BlockID) -> Error where C: MiningBlockChainClient {
+	match client.block_header(id) {
+		Some(_) => Error {
+			code: ErrorCode::ServerError(error_codes::STATE_PRUNED),
+			message: "State of this block has been pruned. Run parity with --pruning=archive to keep historical state.".into(),
+			data: None
+		},
+		None => Error {
+			code: ErrorCode::ServerError(error_codes::UNKNOWN_BLOCK),
+			message: "Block is not known.".into(),
+			data: None
+		},
 	}
 }
 
@@ -234,6 +435,37 @@ fn no_work_err() -> Error {
 	}
 }
 
+fn no_author_err() -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::NO_AUTHOR),
+		message: "Author not configured. Run parity with --author to configure.".into(),
+		data: None
+	}
+}
+
+fn compiler_unavailable_err(data: Option<String>) -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::COMPILER_UNAVAILABLE),
+		message: "Solidity compiler not available. Run parity with --solc to configure.".into(),
+		data: data.map(Value::String),
+	}
+}
+
+fn compile_err(error: CompileError) -> Error {
+	let (message, data) = match error {
+		CompileError::Unavailable(e) => return compiler_unavailable_err(Some(format!("{}", e))),
+		CompileError::SourceTooLarge => ("Source exceeds the size limit.", None),
+		CompileError::Timeout => ("Compilation timed out.", None),
+		CompileError::Failed(errors) => ("Compilation failed.", Some(errors)),
+		CompileError::InvalidOutput => ("Unable to read compiler output.", None),
+	};
+	Error {
+		code: ErrorCode::ServerError(error_codes::COMPILATION_ERROR),
+		message: message.into(),
+		data: data.map(Value::String),
+	}
+}
+
 impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 	C: MiningBlockChainClient + 'static,
 	S: SyncProvider + 'static,
@@ -242,7 +474,7 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 
 	fn protocol_version(&self, params: Params) -> Result<Value, Error> {
 		match params {
-			Params::None => Ok(Value::String(format!("{}", take_weak!(self.sync).status().protocol_version).to_owned())),
+			Params::None => to_value(&U256::from(take_weak!(self.sync).status().protocol_version)),
 			_ => Err(Error::invalid_params())
 		}
 	}
@@ -275,7 +507,13 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 
 	fn author(&self, params: Params) -> Result<Value, Error> {
 		match params {
-			Params::None => to_value(&take_weak!(self.miner).author()),
+			Params::None => {
+				let author = take_weak!(self.miner).author();
+				if author.is_zero() {
+					return Err(no_author_err());
+				}
+				to_value(&author)
+			},
 			_ => Err(Error::invalid_params()),
 		}
 	}
@@ -301,9 +539,67 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 		}
 	}
 
+	fn max_priority_fee_per_gas(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => to_value(&try!(self.suggested_tip())),
+			_ => Err(Error::invalid_params())
+		}
+	}
+
+	fn fee_history(&self, params: Params) -> Result<Value, Error> {
+		let (block_count, newest, percentiles) = try!(match params_len(&params) {
+			2 => from_params::<(Index, BlockNumber)>(params).map(|(c, n)| (c, n, None)),
+			_ => from_params::<(Index, BlockNumber, Vec<f64>)>(params).map(|(c, n, p)| (c, n, Some(p))),
+		});
+		if let Some(ref percentiles) = percentiles {
+			let in_range = percentiles.iter().all(|p| *p >= 0.0 && *p <= 100.0);
+			let increasing = percentiles.windows(2).all(|w| w[0] <= w[1]);
+			if !in_range || !increasing {
+				return Err(Error::invalid_params());
+			}
+		}
+		let block_count = cmp::min(block_count.value(), MAX_FEE_HISTORY_BLOCKS);
+		let requested = percentiles.clone().unwrap_or_else(Vec::new);
+
+		let client = take_weak!(self.client);
+		let newest = match client.block_header(newest.into()) {
+			Some(header) => HeaderView::new(&header).number(),
+			None => return Ok(Value::Null),
+		};
+
+		let mut base_fee_per_gas = Vec::new();
+		let mut gas_used_ratio = Vec::new();
+		let mut reward = Vec::new();
+		let mut oldest = newest + 1;
+		// walk back until the window is full, genesis is reached or older blocks are not available
+		while base_fee_per_gas.len() < block_count && oldest > 0 {
+			let block = match client.block(BlockID::Number(oldest - 1)) {
+				Some(block) => block,
+				None => break,
+			};
+			let (base, ratio, rewards) = block_fees(&BlockView::new(&block), &requested);
+			base_fee_per_gas.push(base);
+			gas_used_ratio.push(ratio);
+			reward.push(rewards);
+			oldest -= 1;
+		}
+		base_fee_per_gas.reverse();
+		gas_used_ratio.reverse();
+		reward.reverse();
+		let next_base = base_fee_per_gas.last().cloned();
+		base_fee_per_gas.extend(next_base);
+
+		to_value(&FeeHistory {
+			oldest_block: U256::from(cmp::min(oldest, newest)),
+			base_fee_per_gas: base_fee_per_gas,
+			gas_used_ratio: gas_used_ratio,
+			reward: percentiles.map(|_| reward),
+		})
+	}
+
 	fn accounts(&self, _: Params) -> Result<Value, Error> {
 		let store = take_weak!(self.accounts);
-		to_value(&store.accounts())
+		to_value(&self.accounts_filter.apply(store.accounts()))
 	}
 
 	fn block_number(&self, params: Params) -> Result<Value, Error> {
@@ -314,29 +610,65 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 	}
 
 	fn balance(&self, params: Params) -> Result<Value, Error> {
-		from_params_default_second(params)
-			.and_then(|(address, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&take_weak!(self.miner).balance(take_weak!(self.client).deref(), &address)),
-				id => to_value(&try!(take_weak!(self.client).balance(&address, id.into()).ok_or_else(make_unsupported_err))),
+		named_params(params, &["address", "block"])
+			.and_then(from_params_default_second)
+			.and_then(|(address, block_number,)| {
+				let _span = Span::enter("state");
+				match block_number {
+					BlockNumber::Pending => to_value(&take_weak!(self.miner).balance(take_weak!(self.client).deref(), &address)),
+					id => {
+						let client = take_weak!(self.client);
+						let id: BlockID = id.into();
+						match client.balance(&address, id.clone()) {
+							Some(balance) => to_value(&balance),
+							None => Err(state_unavailable_err(&*client, id)),
+						}
+					},
+				}
 			})
 	}
 
 	fn storage_at(&self, params: Params) -> Result<Value, Error> {
-		from_params_default_third::<Address, U256>(params)
-			.and_then(|(address, position, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&U256::from(take_weak!(self.miner).storage_at(&*take_weak!(self.client), &address, &H256::from(position)))),
-				id => match take_weak!(self.client).storage_at(&address, &H256::from(position), id.into()) {
-					Some(s) => to_value(&U256::from(s)),
-					None => Err(make_unsupported_err()), // None is only returned on unsupported requests.
+		named_params(params, &["address", "position", "block"])
+			.and_then(from_params_default_third::<Address, U256>)
+			.and_then(|(address, position, block_number,)| {
+				let _span = Span::enter("state");
+				match block_number {
+					BlockNumber::Pending => to_value(&take_weak!(self.miner).storage_at(&*take_weak!(self.client), &address, &H256::from(position))),
+					id => {
+						let client = take_weak!(self.client);
+						let id: BlockID = id.into();
+						match client.storage_at(&address, &H256::from(position), id.clone()) {
+							Some(s) => to_value(&s),
+							None => Err(state_unavailable_err(&*client, id)),
+						}
+					},
 				}
 			})
 	}
 
 	fn transaction_count(&self, params: Params) -> Result<Value, Error> {
-		from_params_default_second(params)
-			.and_then(|(address, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&take_weak!(self.miner).nonce(take_weak!(self.client).deref(), &address)),
-				id => to_value(&take_weak!(self.client).nonce(&address, id.into())),
+		named_params(params, &["address", "block"])
+			.and_then(from_params_default_second)
+			.and_then(|(address, block_number,)| {
+				let _span = Span::enter("state");
+				match block_number {
+					BlockNumber::Pending => {
+						let miner = take_weak!(self.miner);
+						let nonce = miner.nonce(take_weak!(self.client).deref(), &address);
+						// Queued transactions that didn't make it into the pending block are counted as well.
+						let queued = miner.last_nonce(&address).map_or(nonce, |n| n + U256::one());
+						to_value(&cmp::max(nonce, queued))
+					},
+					id => {
+						let client = take_weak!(self.client);
+						let id: BlockID = id.into();
+						match client.nonce(&address, id.clone()) {
+							Some(nonce) => to_value(&nonce),
+							None => Err(state_unavailable_err(&*client, id)),
+						}
+					},
+				}
 			})
 	}
 
@@ -375,22 +707,37 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 	}
 
 	fn code_at(&self, params: Params) -> Result<Value, Error> {
-		from_params_default_second(params)
-			.and_then(|(address, block_number,)| match block_number {
-				BlockNumber::Pending => to_value(&take_weak!(self.miner).code(take_weak!(self.client).deref(), &address).map_or_else(Bytes::default, Bytes::new)),
-				BlockNumber::Latest => to_value(&take_weak!(self.client).code(&address).map_or_else(Bytes::default, Bytes::new)),
-				_ => Err(Error::invalid_params()),
+		named_params(params, &["address", "block"])
+			.and_then(from_params_default_second)
+			.and_then(|(address, block_number,)| {
+				let _span = Span::enter("state");
+				match block_number {
+					BlockNumber::Pending => to_value(&take_weak!(self.miner).code(take_weak!(self.client).deref(), &address).map_or_else(Bytes::default, Bytes::new)),
+					BlockNumber::Latest => to_value(&take_weak!(self.client).code(&address).map_or_else(Bytes::default, Bytes::new)),
+					_ => Err(Error::invalid_params()),
+				}
 			})
 	}
 
 	fn block_by_hash(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(H256, bool)>(params)
-			.and_then(|(hash, include_txs)| self.block(BlockID::Hash(hash), include_txs))
+			.and_then(|(hash, include_txs)| {
+				let key = ResultCache::key("eth_getBlockByHash", &format!("{:?},{}", hash, include_txs));
+				self.cached_block(key, BlockID::Hash(hash), include_txs)
+			})
 	}
 
 	fn block_by_number(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(BlockNumber, bool)>(params)
-			.and_then(|(number, include_txs)| self.block(number.into(), include_txs))
+			.and_then(|(number, include_txs)| match number {
+				BlockNumber::Num(n) => {
+					let key = ResultCache::key("eth_getBlockByNumber", &format!("{},{}", n, include_txs));
+					self.cached_block(key, number.into(), include_txs)
+				},
+				BlockNumber::Pending => self.pending_block(include_txs),
+				// latest block changes with every import
+				_ => self.block(number.into(), include_txs),
+			})
 	}
 
 	fn transaction_by_hash(&self, params: Params) -> Result<Value, Error> {
@@ -411,7 +758,26 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 
 	fn transaction_by_block_number_and_index(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(BlockNumber, Index)>(params)
-			.and_then(|(number, index)| self.transaction(TransactionID::Location(number.into(), index.value())))
+			.and_then(|(number, index)| match number {
+				BlockNumber::Pending => self.pending_transaction(index.value()),
+				_ => self.transaction(TransactionID::Location(number.into(), index.value())),
+			})
+	}
+
+	fn raw_transaction_by_hash(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H256,)>(params)
+			.and_then(|(hash,)| {
+				let miner = take_weak!(self.miner);
+				match miner.transaction(&hash) {
+					Some(pending_tx) => to_value(&Bytes::new(encode(&pending_tx).to_vec())),
+					None => self.raw_transaction(TransactionID::Hash(hash))
+				}
+			})
+	}
+
+	fn raw_transaction_by_block_hash_and_index(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(H256, Index)>(params)
+			.and_then(|(hash, index)| self.raw_transaction(TransactionID::Location(BlockID::Hash(hash), index.value())))
 	}
 
 	fn transaction_receipt(&self, params: Params) -> Result<Value, Error> {
@@ -421,9 +787,19 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 				match miner.pending_receipts().get(&hash) {
 					Some(receipt) if self.allow_pending_receipt_query => to_value(&Receipt::from(receipt.clone())),
 					_ => {
+						let key = ResultCache::key("eth_getTransactionReceipt", &format!("{:?}", hash));
+						if let Some(value) = self.result_cache.get(&key) {
+							return Ok(value);
+						}
+
 						let client = take_weak!(self.client);
 						let receipt = client.transaction_receipt(TransactionID::Hash(hash));
-						to_value(&receipt.map(Receipt::from))
+						let immutable = receipt.as_ref().map_or(false, |r| self.is_immutable(&client, r.block_number));
+						let value = try!(to_value(&receipt.map(Receipt::from)));
+						if immutable {
+							self.result_cache.insert(key, value.clone());
+						}
+						Ok(value)
 					}
 				}
 			})
@@ -441,12 +817,25 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 
 	fn compilers(&self, params: Params) -> Result<Value, Error> {
 		match params {
-			Params::None => to_value(&vec![] as &Vec<String>),
+			Params::None => to_value(&self.solc.as_ref().map_or_else(Vec::new, |_| vec!["solidity".to_owned()])),
 			_ => Err(Error::invalid_params())
 		}
 	}
 
 	fn logs(&self, params: Params) -> Result<Value, Error> {
+		if params_len(&params) == 2 {
+			return from_params::<(Filter, LogsPagination)>(params)
+				.and_then(|(filter, pagination)| {
+					if pagination.limit == Some(0) {
+						return Err(Error::invalid_params());
+					}
+					let client = take_weak!(self.client);
+					let filter = page_filter(client.deref(), filter.into(), &pagination);
+					let logs = client.logs(filter);
+					to_value(&paginate_logs(logs, pagination))
+				});
+		}
+
 		from_params::<(Filter,)>(params)
 			.and_then(|(filter,)| {
 				let include_pending = filter.to_block == Some(BlockNumber::Pending);
@@ -525,10 +914,12 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 		from_params_default_second(params)
 			.and_then(|(request, block_number,)| {
 				let signed = try!(self.sign_call(request));
+				let _permit = try!(self.execution_limit.acquire().ok_or_else(execution_capacity_error));
+				let _span = Span::enter("evm");
 				let r = match block_number {
 					BlockNumber::Pending => take_weak!(self.miner).call(take_weak!(self.client).deref(), &signed, Default::default()),
 					BlockNumber::Latest => take_weak!(self.client).call(&signed, Default::default()),
-					_ => panic!("{:?}", block_number),
+					_ => return Err(Error::invalid_params()),
 				};
 				to_value(&r.map(|e| Bytes(e.output)).unwrap_or(Bytes::new(vec![])))
 			})
@@ -538,6 +929,8 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 		from_params_default_second(params)
 			.and_then(|(request, block_number,)| {
 				let signed = try!(self.sign_call(request));
+				let _permit = try!(self.execution_limit.acquire().ok_or_else(execution_capacity_error));
+				let _span = Span::enter("evm");
 				let r = match block_number {
 					BlockNumber::Pending => take_weak!(self.miner).call(take_weak!(self.client).deref(), &signed, Default::default()),
 					BlockNumber::Latest => take_weak!(self.client).call(&signed, Default::default()),
@@ -555,7 +948,25 @@ impl<C, S, M, EM> Eth for EthClient<C, S, M, EM> where
 		rpc_unimplemented!()
 	}
 
-	fn compile_solidity(&self, _: Params) -> Result<Value, Error> {
-		rpc_unimplemented!()
+	fn compile_solidity(&self, params: Params) -> Result<Value, Error> {
+		let solc = match self.solc {
+			Some(ref solc) => solc,
+			None => return Err(compiler_unavailable_err(None)),
+		};
+		from_params::<(String,)>(params).and_then(|(source,)| {
+			let _span = Span::enter("solc");
+			let contracts = try!(compile_solidity(solc, &source).map_err(compile_err));
+			let contracts = contracts.into_iter().map(|(name, contract)| {
+				let mut info = BTreeMap::new();
+				info.insert("source".to_owned(), Value::String(source.clone()));
+				info.insert("language".to_owned(), Value::String("Solidity".to_owned()));
+				info.insert("abiDefinition".to_owned(), contract.abi);
+				let mut compiled = BTreeMap::new();
+				compiled.insert("code".to_owned(), Value::String(format!("0x{}", contract.code)));
+				compiled.insert("info".to_owned(), Value::Object(info));
+				(name, Value::Object(compiled))
+			}).collect::<BTreeMap<_, _>>();
+			Ok(Value::Object(contracts))
+		})
 	}
 }
@@ -25,7 +25,7 @@ use ethcore::miner::MinerService;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::client::{BlockChainClient, BlockID};
 use v1::traits::EthFilter;
-use v1::types::{BlockNumber, Index, Filter, Log};
+use v1::types::{BlockNumber, Index, Filter, Log, Transaction};
 use v1::helpers::{PollFilter, PollManager};
 use v1::impls::eth::pending_logs;
 
@@ -80,16 +80,15 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 	}
 
 	fn new_pending_transaction_filter(&self, params: Params) -> Result<Value, Error> {
-		match params {
-			Params::None => {
-				let mut polls = self.polls.lock().unwrap();
-				let pending_transactions = take_weak!(self.miner).pending_transactions_hashes();
-				let id = polls.create_poll(PollFilter::PendingTransaction(pending_transactions));
-
-				to_value(&U256::from(id))
-			},
-			_ => Err(Error::invalid_params())
-		}
+		let include_full = match params {
+			Params::None => false,
+			_ => try!(from_params::<(bool,)>(params)).0,
+		};
+		let mut polls = self.polls.lock().unwrap();
+		let pending_transactions = take_weak!(self.miner).pending_transactions_hashes();
+		let id = polls.create_poll(PollFilter::PendingTransaction(pending_transactions, include_full));
+
+		to_value(&U256::from(id))
 	}
 
 	fn filter_changes(&self, params: Params) -> Result<Value, Error> {
@@ -112,7 +111,30 @@ impl<C, M> EthFilter for EthFilterClient<C, M> where
 
 							to_value(&hashes)
 						},
-						PollFilter::PendingTransaction(ref mut previous_hashes) => {
+						PollFilter::PendingTransaction(ref mut previous_hashes, true) => {
+							// get pending transactions, hashes and bodies from the same snapshot
+							let current_transactions = take_weak!(self.miner).pending_transactions();
+							let current_hashes = current_transactions.iter().map(|t| t.hash()).collect::<Vec<H256>>();
+
+							let new_transactions = {
+								let previous_hashes_set = previous_hashes.iter().collect::<HashSet<_>>();
+
+								// find all new transactions
+								current_transactions
+									.into_iter()
+									.zip(current_hashes.iter())
+									.filter(|&(_, hash)| !previous_hashes_set.contains(hash))
+									.map(|(t, _)| Transaction::from(t))
+									.collect::<Vec<Transaction>>()
+							};
+
+							// save all hashes of pending transactions
+							*previous_hashes = current_hashes;
+
+							// return new transactions
+							to_value(&new_transactions)
+						},
+						PollFilter::PendingTransaction(ref mut previous_hashes, false) => {
 							// get hashes of pending transactions
 							let current_hashes = take_weak!(self.miner).pending_transactions_hashes();
 
@@ -16,19 +16,27 @@
 
 //! Ethcore-specific rpc implementation.
 use util::{RotatingLogger};
+use util::network::NetworkService;
 use util::network_settings::NetworkSettings;
-use util::misc::version_data;
+use util::misc::{version, version_data, short_sha};
 use std::sync::{Arc, Weak};
 use std::ops::Deref;
 use std::collections::{BTreeMap};
 use ethcore::client::{MiningBlockChainClient};
+use ethcore::transaction::{Transaction, Action};
+use util::numbers::*;
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
+use ethcore::service::SyncMessage;
 use v1::traits::Ethcore;
-use v1::types::{Bytes};
-use v1::helpers::{SigningQueue, ConfirmationsQueue};
+use v1::types::{Bytes, BlockNumber, Receipt, transactions_by_sender, gas_price_buckets, logarithmic_gas_price_boundaries};
+use v1::helpers::{SigningQueue, ConfirmationsQueue, ContentHashError, FetchLimits, hash_content, NameError, DappsProvider, ResultCache};
+use v1::helpers::{validate_name, address_lookup, content_lookup, decode_address, decode_word};
 use v1::impls::error_codes;
 
+/// Maximal number of gas price buckets requested from `parity_pendingTransactionsStats`.
+const MAX_GAS_PRICE_BUCKETS: usize = 256;
+
 /// Ethcore implementation.
 pub struct EthcoreClient<C, M> where
 	C: MiningBlockChainClient,
@@ -36,24 +44,93 @@ pub struct EthcoreClient<C, M> where
 
 	client: Weak<C>,
 	miner: Weak<M>,
+	net: Weak<NetworkService<SyncMessage>>,
 	logger: Arc<RotatingLogger>,
 	settings: Arc<NetworkSettings>,
 	confirmations_queue: Option<Arc<ConfirmationsQueue>>,
+	dapps: Option<Arc<DappsProvider>>,
+	fetch_limits: FetchLimits,
+	result_cache: Arc<ResultCache>,
 }
 
 impl<C, M> EthcoreClient<C, M> where C: MiningBlockChainClient, M: MinerService {
 	/// Creates new `EthcoreClient`.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>, logger: Arc<RotatingLogger>, settings: Arc<NetworkSettings>, queue: Option<Arc<ConfirmationsQueue>>) -> Self {
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, net: &Arc<NetworkService<SyncMessage>>, logger: Arc<RotatingLogger>, settings: Arc<NetworkSettings>, queue: Option<Arc<ConfirmationsQueue>>, dapps: Option<Arc<DappsProvider>>, fetch_limits: FetchLimits, result_cache: &Arc<ResultCache>) -> Self {
 		EthcoreClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			net: Arc::downgrade(net),
 			logger: logger,
 			settings: settings,
 			confirmations_queue: queue,
+			dapps: dapps,
+			fetch_limits: fetch_limits,
+			result_cache: result_cache.clone(),
 		}
 	}
 }
 
+fn content_hash_error(error: ContentHashError) -> Error {
+	let (code, message) = match error {
+		ContentHashError::TooLarge => (error_codes::FETCH_CONTENT_ERROR, "Content exceeds the size limit."),
+		ContentHashError::Timeout => (error_codes::FETCH_CONTENT_ERROR, "Fetching content timed out."),
+		ContentHashError::Blocked => (error_codes::FETCH_CONTENT_ERROR, "Fetching from a non-public address refused."),
+		_ => (error_codes::FETCH_CONTENT_ERROR, "Unable to fetch content."),
+	};
+	Error {
+		code: ErrorCode::ServerError(code),
+		message: message.into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
+fn malformed_name_error(error: NameError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::MALFORMED_NAME),
+		message: "Name is malformed.".into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
+fn unknown_block_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::UNKNOWN_BLOCK),
+		message: "Block is not known.".into(),
+		data: None,
+	}
+}
+
+fn network_not_started_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::NETWORK_NOT_STARTED),
+		message: "Network is not started yet.".into(),
+		data: None,
+	}
+}
+
+fn name_not_found_error(name: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::NAME_NOT_FOUND),
+		message: "Name is not registered.".into(),
+		data: Some(Value::String(name.to_owned())),
+	}
+}
+
+impl<C, M> EthcoreClient<C, M> where C: MiningBlockChainClient, M: MinerService {
+	fn registry_call(&self, client: &C, registrar: &Address, data: Vec<u8>) -> Vec<u8> {
+		let from = Address::zero();
+		let transaction = Transaction {
+			nonce: client.latest_nonce(&from),
+			action: Action::Call(registrar.clone()),
+			gas: U256::from(50_000_000),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: data,
+		}.fake_sign(from);
+		client.call(&transaction, Default::default()).map(|e| e.output).unwrap_or_else(|_| Vec::new())
+	}
+}
+
 impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: MiningBlockChainClient + 'static {
 
 	fn transactions_limit(&self, _: Params) -> Result<Value, Error> {
@@ -72,6 +149,10 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 		to_value(&take_weak!(self.miner).gas_floor_target())
 	}
 
+	fn gas_ceil_target(&self, _: Params) -> Result<Value, Error> {
+		to_value(&take_weak!(self.miner).gas_ceil_target())
+	}
+
 	fn dev_logs(&self, _params: Params) -> Result<Value, Error> {
 		let logs = self.logger.logs();
 		to_value(&logs.deref().as_slice())
@@ -135,4 +216,133 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 			Some(ref queue) => to_value(&queue.len()),
 		}
 	}
+
+	fn version_info(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => {
+				let mut semver = BTreeMap::new();
+				semver.insert("major".to_owned(), Value::U64(env!("CARGO_PKG_VERSION_MAJOR").parse().expect("Cargo version components are numeric; qed")));
+				semver.insert("minor".to_owned(), Value::U64(env!("CARGO_PKG_VERSION_MINOR").parse().expect("Cargo version components are numeric; qed")));
+				semver.insert("patch".to_owned(), Value::U64(env!("CARGO_PKG_VERSION_PATCH").parse().expect("Cargo version components are numeric; qed")));
+
+				let mut map = BTreeMap::new();
+				map.insert("version".to_owned(), Value::Object(semver));
+				map.insert("hash".to_owned(), Value::String(short_sha().to_owned()));
+				map.insert("client".to_owned(), Value::String(version()));
+				Ok(Value::Object(map))
+			},
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn future_transactions(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => to_value(&transactions_by_sender(take_weak!(self.miner).future_transactions())),
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn pending_transactions_stats(&self, params: Params) -> Result<Value, Error> {
+		let boundaries = match params {
+			Params::None => None,
+			_ => Some(try!(from_params::<(Vec<U256>,)>(params)).0),
+		};
+		if let Some(ref boundaries) = boundaries {
+			let ascending = boundaries.windows(2).all(|w| w[0] < w[1]);
+			if !ascending || boundaries.len() > MAX_GAS_PRICE_BUCKETS {
+				return Err(Error::invalid_params());
+			}
+		}
+		// a single snapshot, so all buckets come from the same state of the queue
+		let transactions = take_weak!(self.miner).pending_transactions();
+		let boundaries = boundaries.unwrap_or_else(|| logarithmic_gas_price_boundaries(&transactions));
+		to_value(&gas_price_buckets(&transactions, &boundaries))
+	}
+
+	fn hash_content(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String,)>(params).and_then(|(url,)| {
+			hash_content(&url, &self.fetch_limits).map_err(content_hash_error).and_then(|hash| to_value(&hash))
+		})
+	}
+
+	fn registry_address(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => to_value(&take_weak!(self.client).registrar_address()),
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn resolve_name(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String,)>(params).and_then(|(name,)| {
+			try!(validate_name(&name).map_err(malformed_name_error));
+			let client = take_weak!(self.client);
+			let registrar = try!(client.registrar_address().ok_or_else(|| name_not_found_error(&name)));
+			let address = decode_address(&self.registry_call(&*client, &registrar, address_lookup(&name)));
+			let content = decode_word(&self.registry_call(&*client, &registrar, content_lookup(&name)));
+			if address.is_none() && content.is_none() {
+				return Err(name_not_found_error(&name));
+			}
+
+			let mut map = BTreeMap::new();
+			map.insert("address".to_owned(), try!(to_value(&address)));
+			map.insert("content".to_owned(), try!(to_value(&content)));
+			Ok(Value::Object(map))
+		})
+	}
+
+	fn dapps_list(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => {},
+			_ => return Err(Error::invalid_params()),
+		}
+		let mut dapps = self.dapps.as_ref().map_or_else(Vec::new, |dapps| dapps.dapps());
+		let client = take_weak!(self.client);
+		if let Some(registrar) = client.registrar_address() {
+			for dapp in dapps.iter_mut().filter(|dapp| validate_name(&dapp.id).is_ok()) {
+				dapp.content_hash = decode_word(&self.registry_call(&*client, &registrar, content_lookup(&dapp.id)));
+			}
+		}
+		to_value(&dapps)
+	}
+
+	fn block_receipts(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(BlockNumber,)>(params).and_then(|(block,)| {
+			match take_weak!(self.client).localized_block_receipts(block.into()) {
+				Some(receipts) => to_value(&receipts.into_iter().map(Receipt::from).collect::<Vec<_>>()),
+				None => Err(unknown_block_error()),
+			}
+		})
+	}
+
+	fn chain(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => to_value(&take_weak!(self.client).spec_name()),
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn result_cache_stats(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => {
+				let stats = self.result_cache.stats();
+				let mut map = BTreeMap::new();
+				map.insert("hits".to_owned(), Value::U64(stats.hits as u64));
+				map.insert("misses".to_owned(), Value::U64(stats.misses as u64));
+				map.insert("size".to_owned(), Value::U64(stats.size as u64));
+				map.insert("capacity".to_owned(), Value::U64(stats.capacity as u64));
+				Ok(Value::Object(map))
+			},
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn enode(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => match take_weak!(self.net).external_url() {
+				Some(url) => to_value(&url),
+				None => Err(network_not_started_error()),
+			},
+			_ => Err(Error::invalid_params()),
+		}
+	}
 }
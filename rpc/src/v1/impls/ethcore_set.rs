@@ -23,6 +23,7 @@ use ethcore::miner::MinerService;
 use ethcore::service::SyncMessage;
 use v1::traits::EthcoreSet;
 use v1::types::{Bytes};
+use v1::impls::block_error;
 
 /// Ethcore-specific rpc interface for operations altering the settings.
 pub struct EthcoreSetClient<M> where
@@ -53,14 +54,21 @@ impl<M> EthcoreSet for EthcoreSetClient<M> where M: MinerService + 'static {
 
 	fn set_gas_floor_target(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(U256,)>(params).and_then(|(gas_floor_target,)| {
-			take_weak!(self.miner).set_gas_floor_target(gas_floor_target);
+			try!(take_weak!(self.miner).set_gas_floor_target(gas_floor_target).map_err(block_error));
+			to_value(&true)
+		})
+	}
+
+	fn set_gas_ceil_target(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(U256,)>(params).and_then(|(gas_ceil_target,)| {
+			try!(take_weak!(self.miner).set_gas_ceil_target(gas_ceil_target).map_err(block_error));
 			to_value(&true)
 		})
 	}
 
 	fn set_extra_data(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(Bytes,)>(params).and_then(|(extra_data,)| {
-			take_weak!(self.miner).set_extra_data(extra_data.to_vec());
+			try!(take_weak!(self.miner).set_extra_data(extra_data.to_vec()).map_err(block_error));
 			to_value(&true)
 		})
 	}
@@ -74,6 +82,9 @@ impl<M> EthcoreSet for EthcoreSetClient<M> where M: MinerService + 'static {
 
 	fn set_transactions_limit(&self, params: Params) -> Result<Value, Error> {
 		from_params::<(usize,)>(params).and_then(|(limit,)| {
+			if limit == 0 {
+				return Err(Error::invalid_params());
+			}
 			take_weak!(self.miner).set_transactions_limit(limit);
 			to_value(&true)
 		})
@@ -40,6 +40,8 @@ mod ethcore;
 mod ethcore_set;
 mod traces;
 mod rpc;
+mod txpool;
+mod shh;
 
 pub use self::web3::Web3Client;
 pub use self::eth::EthClient;
@@ -52,9 +54,11 @@ pub use self::ethcore::EthcoreClient;
 pub use self::ethcore_set::EthcoreSetClient;
 pub use self::traces::TracesClient;
 pub use self::rpc::RpcClient;
+pub use self::txpool::TxPoolClient;
+pub use self::shh::ShhClient;
 
 use v1::types::TransactionRequest;
-use ethcore::error::Error as EthcoreError;
+use ethcore::error::{Error as EthcoreError, BlockError};
 use ethcore::miner::{AccountDetails, MinerService};
 use ethcore::client::MiningBlockChainClient;
 use ethcore::transaction::{Action, SignedTransaction, Transaction};
@@ -66,12 +70,22 @@ use jsonrpc_core::{Error, ErrorCode, Value, to_value};
 
 mod error_codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
-	pub const UNSUPPORTED_REQUEST_CODE: i64 = -32000;
 	pub const NO_WORK_CODE: i64 = -32001;
 	pub const UNKNOWN_ERROR: i64 = -32002;
+	pub const NO_AUTHOR: i64 = -32003;
+	pub const EXECUTION_CAPACITY_EXCEEDED: i64 = -32005;
 	pub const TRANSACTION_ERROR: i64 = -32010;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const SIGNER_DISABLED: i64 = -32030;
+	pub const WHISPER_ERROR: i64 = -32040;
+	pub const FETCH_CONTENT_ERROR: i64 = -32050;
+	pub const NAME_NOT_FOUND: i64 = -32060;
+	pub const MALFORMED_NAME: i64 = -32061;
+	pub const UNKNOWN_BLOCK: i64 = -32070;
+	pub const STATE_PRUNED: i64 = -32071;
+	pub const COMPILER_UNAVAILABLE: i64 = -32080;
+	pub const COMPILATION_ERROR: i64 = -32081;
+	pub const NETWORK_NOT_STARTED: i64 = -32090;
 }
 
 fn dispatch_transaction<C, M>(client: &C, miner: &M, signed_transaction: SignedTransaction) -> Result<Value, Error>
@@ -142,6 +156,22 @@ fn signing_error(error: AccountError) -> Error {
 	}
 }
 
+fn execution_capacity_error() -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::EXECUTION_CAPACITY_EXCEEDED),
+		message: "Execution capacity exceeded. Too many calls are being executed, try again later.".into(),
+		data: None,
+	}
+}
+
+fn block_error(error: BlockError) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("{}", error),
+		data: None,
+	}
+}
+
 fn transaction_error(error: EthcoreError) -> Error {
 	use ethcore::error::TransactionError::*;
 
@@ -36,7 +36,7 @@ impl<S> NetClient<S> where S: SyncProvider {
 
 impl<S> Net for NetClient<S> where S: SyncProvider + 'static {
 	fn version(&self, _: Params) -> Result<Value, Error> {
-		Ok(Value::String(format!("{}", take_weak!(self.sync).status().network_id).to_owned()))
+		Ok(Value::String(format!("{}", take_weak!(self.sync).status().network_id)))
 	}
 
 	fn peer_count(&self, _params: Params) -> Result<Value, Error> {
@@ -44,8 +44,7 @@ impl<S> Net for NetClient<S> where S: SyncProvider + 'static {
 	}
 
 	fn is_listening(&self, _: Params) -> Result<Value, Error> {
-		// right now (11 march 2016), we are always listening for incoming connections
-		Ok(Value::Bool(true))
+		Ok(Value::Bool(take_weak!(self.sync).is_listening()))
 	}
 
 	fn start_network(&self, _: Params) -> Result<Value, Error> {
@@ -57,4 +56,13 @@ impl<S> Net for NetClient<S> where S: SyncProvider + 'static {
 		take_weak!(self.sync).stop_network();
 		Ok(Value::Bool(true))
 	}
+
+	fn remove_peer(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(String,)>(params).and_then(|(peer,)| {
+			match take_weak!(self.sync).remove_peer(&peer) {
+				Ok(removed) => to_value(&removed),
+				Err(_) => Err(Error::invalid_params()),
+			}
+		})
+	}
 }
@@ -19,7 +19,7 @@
 use std::sync::{Arc, Weak};
 use jsonrpc_core::*;
 use v1::traits::PersonalSigner;
-use v1::types::TransactionModification;
+use v1::types::{TransactionModification, ConfirmationRequest};
 use v1::impls::unlock_sign_and_dispatch;
 use v1::helpers::{SigningQueue, ConfirmationsQueue};
 use ethcore::account_provider::AccountProvider;
@@ -95,5 +95,19 @@ impl<C: 'static, M: 'static> PersonalSigner for SignerClient<C, M> where C: Mini
 			}
 		)
 	}
+
+	fn requests_to_confirm(&self, _params: Params) -> Result<Value, Error> {
+		let queue = take_weak!(self.queue);
+		let requests = queue.requests().into_iter().map(ConfirmationRequest::from).collect::<Vec<_>>();
+		to_value(&requests)
+	}
+
+	fn confirm_request(&self, params: Params) -> Result<Value, Error> {
+		self.confirm_transaction(params)
+	}
+
+	fn reject_request(&self, params: Params) -> Result<Value, Error> {
+		self.reject_transaction(params)
+	}
 }
 
@@ -18,22 +18,25 @@
 use std::collections::BTreeMap;
 use jsonrpc_core::*;
 use v1::traits::Rpc;
+use methods::MethodNames;
 
 /// RPC generic methods implementation.
 pub struct RpcClient {
 	modules: BTreeMap<String, String>,
 	valid_apis: Vec<String>,
+	methods: Option<MethodNames>,
 }
 
 impl RpcClient {
-	/// Creates new `RpcClient`.
-	pub fn new(modules: BTreeMap<String, String>) -> Self {
+	/// Creates new `RpcClient`. `methods` are listed by `rpc_methods`; it returns `null` if they are not known.
+	pub fn new(modules: BTreeMap<String, String>, methods: Option<MethodNames>) -> Self {
 		// geth 1.3.6 fails upon receiving unknown api
 		let valid_apis = vec!["web3", "eth", "net", "personal", "rpc"];
 
 		RpcClient {
 			modules: modules,
 			valid_apis: valid_apis.into_iter().map(|x| x.to_owned()).collect(),
+			methods: methods,
 		}
 	}
 }
@@ -59,4 +62,11 @@ impl Rpc for RpcClient {
 			});
 		Ok(Value::Object(modules))
 	}
+
+	fn rpc_methods(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => to_value(&self.methods.as_ref().map(|methods| methods.list())),
+			_ => Err(Error::invalid_params()),
+		}
+	}
 }
@@ -0,0 +1,118 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper rpc implementation.
+
+use std::sync::{Arc, Weak, Mutex};
+use jsonrpc_core::*;
+use util::numbers::*;
+use whisper::{WhisperHost, MessageError, Topic, PROTOCOL_VERSION};
+use v1::traits::Shh;
+use v1::types::{ShhPostRequest, ShhFilterRequest, ShhMessage, Index, abridge_topics};
+use v1::helpers::PollManager;
+use v1::impls::error_codes;
+
+/// Whisper message filter.
+struct ShhFilter {
+	to: Option<H512>,
+	topics: Vec<Topic>,
+	/// Arrival position of the first message not returned yet.
+	cursor: u64,
+}
+
+/// Whisper rpc implementation.
+pub struct ShhClient {
+	whisper: Weak<WhisperHost>,
+	polls: Mutex<PollManager<ShhFilter>>,
+}
+
+impl ShhClient {
+	/// Creates new ShhClient.
+	pub fn new(whisper: &Arc<WhisperHost>) -> Self {
+		ShhClient {
+			whisper: Arc::downgrade(whisper),
+			polls: Mutex::new(PollManager::new()),
+		}
+	}
+}
+
+fn whisper_error(error: MessageError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::WHISPER_ERROR),
+		message: "Could not post whisper message.".into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
+impl Shh for ShhClient {
+	fn version(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => Ok(Value::String(format!("{}", PROTOCOL_VERSION))),
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn post(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(ShhPostRequest,)>(params)
+			.and_then(|(request,)| {
+				let whisper = take_weak!(self.whisper);
+				let topics = abridge_topics(&request.topics);
+				let ttl = request.ttl.low_u64();
+				try!(whisper.post(&request.payload.0, request.from.as_ref(), request.to.as_ref(), topics, ttl).map_err(whisper_error));
+				to_value(&true)
+			})
+	}
+
+	fn new_identity(&self, params: Params) -> Result<Value, Error> {
+		match params {
+			Params::None => {
+				let identity = try!(take_weak!(self.whisper).new_identity().map_err(whisper_error));
+				to_value(&identity)
+			},
+			_ => Err(Error::invalid_params()),
+		}
+	}
+
+	fn new_filter(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(ShhFilterRequest,)>(params)
+			.and_then(|(filter,)| {
+				let cursor = take_weak!(self.whisper).cursor();
+				let mut polls = self.polls.lock().unwrap();
+				let id = polls.create_poll(ShhFilter {
+					to: filter.to,
+					topics: filter.topics.as_ref().map_or_else(Vec::new, |t| abridge_topics(t)),
+					cursor: cursor,
+				});
+				to_value(&U256::from(id))
+			})
+	}
+
+	fn filter_changes(&self, params: Params) -> Result<Value, Error> {
+		from_params::<(Index,)>(params)
+			.and_then(|(index,)| {
+				let whisper = take_weak!(self.whisper);
+				let mut polls = self.polls.lock().unwrap();
+				match polls.poll_mut(&index.value()) {
+					None => Ok(Value::Array(vec![] as Vec<Value>)),
+					Some(filter) => {
+						let (messages, cursor) = whisper.messages(filter.cursor, filter.to.as_ref(), &filter.topics);
+						filter.cursor = cursor;
+						to_value(&messages.into_iter().map(ShhMessage::from).collect::<Vec<_>>())
+					},
+				}
+			})
+	}
+}
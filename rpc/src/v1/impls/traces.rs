@@ -25,19 +25,23 @@ use ethcore::miner::MinerService;
 use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
 use v1::traits::Traces;
 use v1::types::{TraceFilter, LocalizedTrace, Trace, BlockNumber, Index, CallRequest, Bytes, StateDiff, VMTrace};
+use v1::impls::execution_capacity_error;
+use v1::helpers::ExecutionLimit;
 
 /// Traces api implementation.
 pub struct TracesClient<C, M> where C: BlockChainClient, M: MinerService {
 	client: Weak<C>,
 	miner: Weak<M>,
+	execution_limit: Arc<ExecutionLimit>,
 }
 
 impl<C, M> TracesClient<C, M> where C: BlockChainClient, M: MinerService {
 	/// Creates new Traces client.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, execution_limit: &Arc<ExecutionLimit>) -> Self {
 		TracesClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			execution_limit: execution_limit.clone(),
 		}
 	}
 
@@ -113,6 +117,7 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 					state_diffing: flags.contains(&("stateDiff".to_owned())),
 				};
 				let signed = try!(self.sign_call(request));
+				let _permit = try!(self.execution_limit.acquire().ok_or_else(execution_capacity_error));
 				let r = take_weak!(self.client).call(&signed, analytics);
 				if let Ok(executed) = r {
 					// TODO maybe add other stuff to this?
@@ -32,7 +32,7 @@ impl Web3Client {
 impl Web3 for Web3Client {
 	fn client_version(&self, params: Params) -> Result<Value, Error> {
 		match params {
-			Params::None => Ok(Value::String(version().to_owned().replace("Parity/", "Parity//"))),
+			Params::None => Ok(Value::String(version())),
 			_ => Err(Error::invalid_params())
 		}
 	}
@@ -25,6 +25,7 @@ pub mod traits;
 pub mod tests;
 pub mod types;
 
-pub use self::traits::{Web3, Eth, EthFilter, EthSigning, Personal, PersonalSigner, Net, Ethcore, EthcoreSet, Traces, Rpc};
+pub use self::traits::{Web3, Eth, EthFilter, EthSigning, Personal, PersonalSigner, Net, Ethcore, EthcoreSet, Traces, Rpc, TxPool, Shh};
 pub use self::impls::*;
-pub use self::helpers::{SigningQueue, ConfirmationsQueue};
+pub use self::helpers::{SigningQueue, ConfirmationsQueue, ResultCache, ResultCacheStats, AccountsFilter, DappsProvider, FetchLimits, SolcConfig, ExecutionLimit};
+pub use self::types::{Dapp, DappSource};
@@ -17,11 +17,12 @@
 //! rpc integration tests.
 use std::sync::Arc;
 use std::str::FromStr;
+use std::ops::Deref;
 
 use ethcore::client::{BlockChainClient, Client, ClientConfig};
 use ethcore::ids::BlockID;
 use ethcore::spec::{Genesis, Spec};
-use ethcore::block::Block;
+use ethcore::block::{Block, IsBlock};
 use ethcore::views::BlockView;
 use ethcore::ethereum;
 use ethcore::miner::{MinerService, ExternalMiner, Miner};
@@ -35,6 +36,7 @@ use ethjson::blockchain::BlockChain;
 
 use v1::traits::eth::{Eth, EthSigning};
 use v1::impls::{EthClient, EthSigningUnsafeClient};
+use v1::helpers::{ResultCache, AccountsFilter, ExecutionLimit};
 use v1::tests::helpers::{TestSyncProvider, Config};
 
 fn account_provider() -> Arc<AccountProvider> {
@@ -64,7 +66,7 @@ fn make_spec(chain: &BlockChain) -> Spec {
 
 struct EthTester {
 	client: Arc<Client>,
-	_miner: Arc<MinerService>,
+	miner: Arc<Miner>,
 	accounts: Arc<AccountProvider>,
 	handler: IoHandler,
 }
@@ -103,7 +105,12 @@ impl EthTester {
 			&account_provider,
 			&miner_service,
 			&external_miner,
-			true
+			true,
+			&Arc::new(ResultCache::default()),
+			60,
+			AccountsFilter::All,
+			None,
+			&Arc::new(ExecutionLimit::default())
 		);
 		let eth_sign = EthSigningUnsafeClient::new(
 			&client,
@@ -116,7 +123,7 @@ impl EthTester {
 		handler.add_delegate(eth_sign.to_delegate());
 
 		EthTester {
-			_miner: miner_service,
+			miner: miner_service,
 			client: client,
 			accounts: account_provider,
 			handler: handler,
@@ -141,7 +148,7 @@ fn eth_get_balance() {
 		"params": ["0xaaaf5374fce5edbc8e2a8697c15331677e6ebaaa", "latest"],
 		"id": 1
 	}"#;
-	let res_latest = r#"{"jsonrpc":"2.0","result":"0x09","id":1}"#.to_owned();
+	let res_latest = r#"{"jsonrpc":"2.0","result":"0x9","id":1}"#.to_owned();
 	assert_eq!(tester.handler.handle_request(req_latest).unwrap(), res_latest);
 
 	// non-existant account
@@ -152,10 +159,41 @@ fn eth_get_balance() {
 		"id": 3
 	}"#;
 
-	let res_new_acc = r#"{"jsonrpc":"2.0","result":"0x00","id":3}"#.to_owned();
+	let res_new_acc = r#"{"jsonrpc":"2.0","result":"0x0","id":3}"#.to_owned();
 	assert_eq!(tester.handler.handle_request(req_new_acc).unwrap(), res_new_acc);
 }
 
+#[test]
+fn eth_get_balance_and_nonce_at_historical_blocks() {
+	let chain = extract_chain!("BlockchainTests/bcWalletTest", "wallet2outOf3txs");
+	let tester = EthTester::from_chain(&chain);
+	let best = tester.client.chain_info().best_block_number;
+	let request = |method: &str, block: String| format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "{}",
+		"params": ["0xaaaf5374fce5edbc8e2a8697c15331677e6ebaaa", "{}"],
+		"id": 1
+	}}"#, method, block);
+
+	for method in vec!["eth_getBalance", "eth_getTransactionCount"] {
+		for n in 0..best + 1 {
+			let hash = tester.client.block_hash(BlockID::Number(n)).unwrap();
+			let by_number = tester.handler.handle_request(&request(method, format!("0x{:x}", n))).unwrap();
+			let by_hash = tester.handler.handle_request(&request(method, format!("0x{:?}", hash))).unwrap();
+			assert!(by_number.contains("result"));
+			assert_eq!(by_number, by_hash);
+		}
+
+		let earliest = tester.handler.handle_request(&request(method, "earliest".to_owned()));
+		assert_eq!(earliest, tester.handler.handle_request(&request(method, "0x0".to_owned())));
+		let latest = tester.handler.handle_request(&request(method, "latest".to_owned()));
+		assert_eq!(latest, tester.handler.handle_request(&request(method, format!("0x{:x}", best))));
+
+		let unknown = tester.handler.handle_request(&request(method, format!("0x{:x}", best + 1))).unwrap();
+		assert!(unknown.contains(r#""code":-32070"#));
+	}
+}
+
 #[test]
 fn eth_block_number() {
 	let chain = extract_chain!("BlockchainTests/bcRPC_API_Test");
@@ -262,7 +300,7 @@ fn eth_transaction_count() {
 		"id": 17
 	}"#;
 
-	let res_after_latest = r#"{"jsonrpc":"2.0","result":"0x00","id":17}"#;
+	let res_after_latest = r#"{"jsonrpc":"2.0","result":"0x0","id":17}"#;
 
 	assert_eq!(&tester.handler.handle_request(&req_after_latest).unwrap(), res_after_latest);
 
@@ -274,11 +312,80 @@ fn eth_transaction_count() {
 		"id": 18
 	}"#;
 
-	let res_after_pending = r#"{"jsonrpc":"2.0","result":"0x01","id":18}"#;
+	let res_after_pending = r#"{"jsonrpc":"2.0","result":"0x1","id":18}"#;
 
 	assert_eq!(&tester.handler.handle_request(&req_after_pending).unwrap(), res_after_pending);
 }
 
+#[test]
+fn eth_call_pending_sees_pending_transactions() {
+	use util::crypto::Secret;
+	use ethcore::contract_address;
+
+	let secret = Secret::from_str("8a283037bb19c4fed7b1c569e40c7dcff366165eb869110a1b11532963eb9cb2").unwrap();
+	let tester = EthTester::from_spec_provider(|| Spec::load(TRANSACTION_COUNT_SPEC));
+	let address = tester.accounts.insert_account(secret, "").unwrap();
+	tester.accounts.unlock_account_permanently(address, "".into()).unwrap();
+	let contract = contract_address(&address, &U256::zero());
+
+	let req_call = |block: &str| r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"to": ""#.to_owned() + format!("0x{:?}", contract).as_ref() + r#""
+		}, ""# + block + r#""],
+		"id": 1
+	}"#;
+	let empty = r#"{"jsonrpc":"2.0","result":"0x","id":1}"#;
+	let answer = r#"{"jsonrpc":"2.0","result":"0x000000000000000000000000000000000000000000000000000000000000002a","id":1}"#;
+
+	assert_eq!(tester.handler.handle_request(&req_call("pending")).unwrap(), empty);
+
+	// deploys code returning 42 as a 32-byte word.
+	let req_deploy = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"gas": "0x30000",
+			"gasPrice": "0x01",
+			"data": "0x600a600c600039600a6000f3602a60005260206000f3"
+		}],
+		"id": 2
+	}"#;
+	tester.handler.handle_request(&req_deploy).unwrap();
+
+	assert_eq!(tester.handler.handle_request(&req_call("latest")).unwrap(), empty);
+	assert_eq!(tester.handler.handle_request(&req_call("pending")).unwrap(), answer);
+}
+
+#[test]
+fn eth_get_work_follows_gas_range_target() {
+	let tester = EthTester::from_spec_provider(|| Spec::load(TRANSACTION_COUNT_SPEC));
+	let req_work = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getWork",
+		"params": [],
+		"id": 1
+	}"#;
+	let sealing_work = || tester.miner.map_sealing_work(tester.client.deref(), |b| (b.hash(), b.block().header().gas_limit().clone())).unwrap();
+
+	// the genesis gas limit sits on the protocol minimum; a higher floor should pull the next block up.
+	tester.miner.set_gas_floor_target(U256::from(0x100000)).unwrap();
+	let res_work = tester.handler.handle_request(req_work).unwrap();
+	let (hash, gas_limit) = sealing_work();
+	assert!(res_work.contains(&format!("0x{}", hash.hex())));
+	assert_eq!(gas_limit, U256::from(0x5013f));
+
+	// lowering the ceiling back to the minimum should be reflected by the very next work package.
+	tester.miner.set_gas_floor_target(U256::from(0x50000)).unwrap();
+	tester.miner.set_gas_ceil_target(U256::from(0x50000)).unwrap();
+	let res_work = tester.handler.handle_request(req_work).unwrap();
+	let (hash, gas_limit) = sealing_work();
+	assert!(res_work.contains(&format!("0x{}", hash.hex())));
+	assert_eq!(gas_limit, U256::from(0x50000));
+}
+
 fn verify_transaction_counts(name: String, chain: BlockChain) {
 	struct PanicHandler(String);
 	impl Drop for PanicHandler {
@@ -18,7 +18,8 @@
 
 use util::{Address, H256, Bytes, U256, FixedHash, Uint};
 use util::standard::*;
-use ethcore::error::{Error, ExecutionError};
+use ethcore::error::{Error, ExecutionError, BlockError};
+use ethcore::{verify_extra_data, verify_gas_range_target, MAX_GAS_LIMIT};
 use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics};
 use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::transaction::SignedTransaction;
@@ -33,6 +34,8 @@ pub struct TestMinerService {
 	pub latest_closed_block: Mutex<Option<ClosedBlock>>,
 	/// Pre-existed pending transactions
 	pub pending_transactions: Mutex<HashMap<H256, SignedTransaction>>,
+	/// Pre-existed future transactions
+	pub future_transactions: Mutex<Vec<SignedTransaction>>,
 	/// Pre-existed pending receipts
 	pub pending_receipts: Mutex<BTreeMap<H256, Receipt>>,
 	/// Last nonces.
@@ -40,6 +43,7 @@ pub struct TestMinerService {
 
 	min_gas_price: RwLock<U256>,
 	gas_floor_target: RwLock<U256>,
+	gas_ceil_target: RwLock<U256>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
 	limit: RwLock<usize>,
@@ -51,10 +55,12 @@ impl Default for TestMinerService {
 			imported_transactions: Mutex::new(Vec::new()),
 			latest_closed_block: Mutex::new(None),
 			pending_transactions: Mutex::new(HashMap::new()),
+			future_transactions: Mutex::new(Vec::new()),
 			pending_receipts: Mutex::new(BTreeMap::new()),
 			last_nonces: RwLock::new(HashMap::new()),
 			min_gas_price: RwLock::new(U256::from(20_000_000)),
 			gas_floor_target: RwLock::new(U256::from(12345)),
+			gas_ceil_target: RwLock::new(U256::from(MAX_GAS_LIMIT)),
 			author: RwLock::new(Address::zero()),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
 			limit: RwLock::new(1024),
@@ -77,13 +83,22 @@ impl MinerService for TestMinerService {
 		*self.author.write().unwrap() = author;
 	}
 
-	fn set_extra_data(&self, extra_data: Bytes) {
+	fn set_extra_data(&self, extra_data: Bytes) -> Result<(), BlockError> {
+		try!(verify_extra_data(&extra_data, 32));
 		*self.extra_data.write().unwrap() = extra_data;
+		Ok(())
 	}
 
-	/// Set the gas limit we wish to target when sealing a new block.
-	fn set_gas_floor_target(&self, target: U256) {
+	fn set_gas_floor_target(&self, target: U256) -> Result<(), BlockError> {
+		try!(verify_gas_range_target((target, self.gas_ceil_target()), 5000.into()));
 		*self.gas_floor_target.write().unwrap() = target;
+		Ok(())
+	}
+
+	fn set_gas_ceil_target(&self, target: U256) -> Result<(), BlockError> {
+		try!(verify_gas_range_target((self.gas_floor_target(), target), 5000.into()));
+		*self.gas_ceil_target.write().unwrap() = target;
+		Ok(())
 	}
 
 	fn set_minimal_gas_price(&self, min_gas_price: U256) {
@@ -114,6 +129,10 @@ impl MinerService for TestMinerService {
 		*self.gas_floor_target.read().unwrap()
 	}
 
+	fn gas_ceil_target(&self) -> U256 {
+		*self.gas_ceil_target.read().unwrap()
+	}
+
 	/// Imports transactions to transaction queue.
 	fn import_transactions<T>(&self, _chain: &MiningBlockChainClient, transactions: Vec<SignedTransaction>, fetch_account: T) ->
 		Vec<Result<TransactionImportResult, Error>>
@@ -150,7 +169,7 @@ impl MinerService for TestMinerService {
 
 	/// Returns hashes of transactions currently in pending
 	fn pending_transactions_hashes(&self) -> Vec<H256> {
-		vec![]
+		self.pending_transactions.lock().unwrap().keys().cloned().collect()
 	}
 
 	/// Removes all transactions from the queue and restart mining operation.
@@ -184,6 +203,10 @@ impl MinerService for TestMinerService {
 		self.pending_transactions.lock().unwrap().values().cloned().collect()
 	}
 
+	fn future_transactions(&self) -> Vec<SignedTransaction> {
+		self.future_transactions.lock().unwrap().clone()
+	}
+
 	fn pending_receipts(&self) -> BTreeMap<H256, Receipt> {
 		self.pending_receipts.lock().unwrap().clone()
 	}
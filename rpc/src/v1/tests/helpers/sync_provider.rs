@@ -16,7 +16,7 @@
 
 //! Test implementation of SyncProvider.
 
-use util::U256;
+use util::{U256, UtilError};
 use ethsync::{SyncProvider, SyncStatus, SyncState};
 use std::sync::RwLock;
 
@@ -32,6 +32,10 @@ pub struct Config {
 pub struct TestSyncProvider {
 	/// Sync status.
 	pub status: RwLock<SyncStatus>,
+	/// Is network listening for incoming connections.
+	pub listening: RwLock<bool>,
+	/// Ids of connected peers.
+	pub peers: RwLock<Vec<String>>,
 }
 
 impl TestSyncProvider {
@@ -51,6 +55,8 @@ impl TestSyncProvider {
 				num_active_peers: 0,
 				mem_used: 0,
 			}),
+			listening: RwLock::new(true),
+			peers: RwLock::new(Vec::new()),
 		}
 	}
 }
@@ -65,5 +71,20 @@ impl SyncProvider for TestSyncProvider {
 
 	fn stop_network(&self) {
 	}
+
+	fn is_listening(&self) -> bool {
+		*self.listening.read().unwrap()
+	}
+
+	fn remove_peer(&self, peer: &str) -> Result<bool, UtilError> {
+		let mut peers = self.peers.write().unwrap();
+		match peers.iter().position(|p| p == peer) {
+			Some(index) => {
+				peers.remove(index);
+				Ok(true)
+			},
+			None => Ok(false),
+		}
+	}
 }
 
@@ -17,19 +17,22 @@
 use std::str::FromStr;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use jsonrpc_core::IoHandler;
 use util::hash::{Address, H256, FixedHash};
 use util::numbers::{Uint, U256};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{TestBlockChainClient, EachBlockWith, Executed, TransactionID};
+use ethcore::client::{BlockChainClient, TestBlockChainClient, EachBlockWith, Executed, TransactionID, BlockID, UncleID};
 use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
 use ethcore::receipt::LocalizedReceipt;
 use ethcore::transaction::{Transaction, Action};
-use ethcore::miner::{ExternalMiner, MinerService};
+use ethcore::miner::{ExternalMiner, Hashrates, MinerService};
 use ethsync::SyncState;
-use v1::{Eth, EthClient, EthSigning, EthSigningUnsafeClient};
+use v1::{Eth, EthClient, EthFilter, EthFilterClient, EthSigning, EthSigningUnsafeClient, ResultCache, AccountsFilter, SolcConfig, ExecutionLimit};
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService};
 use rustc_serialize::hex::ToHex;
+use serde_json;
+use serde_json::Value;
 
 fn blockchain_client() -> Arc<TestBlockChainClient> {
 	let client = TestBlockChainClient::new();
@@ -56,7 +59,9 @@ struct EthTester {
 	pub sync: Arc<TestSyncProvider>,
 	pub accounts_provider: Arc<AccountProvider>,
 	miner: Arc<TestMinerService>,
-	hashrates: Arc<RwLock<HashMap<H256, U256>>>,
+	hashrates: Arc<RwLock<Hashrates>>,
+	result_cache: Arc<ResultCache>,
+	execution_limit: Arc<ExecutionLimit>,
 	pub io: IoHandler,
 }
 
@@ -68,10 +73,14 @@ impl Default for EthTester {
 		let miner = miner_service();
 		let hashrates = Arc::new(RwLock::new(HashMap::new()));
 		let external_miner = Arc::new(ExternalMiner::new(hashrates.clone()));
-		let eth = EthClient::new(&client, &sync, &ap, &miner, &external_miner, true).to_delegate();
+		let result_cache = Arc::new(ResultCache::default());
+		let execution_limit = Arc::new(ExecutionLimit::with_queue_timeout(2, 0));
+		let eth = EthClient::new(&client, &sync, &ap, &miner, &external_miner, true, &result_cache, 60, AccountsFilter::All, None, &execution_limit).to_delegate();
+		let filter = EthFilterClient::new(&client, &miner).to_delegate();
 		let sign = EthSigningUnsafeClient::new(&client, &ap, &miner).to_delegate();
 		let io = IoHandler::new();
 		io.add_delegate(eth);
+		io.add_delegate(filter);
 		io.add_delegate(sign);
 
 		EthTester {
@@ -81,6 +90,8 @@ impl Default for EthTester {
 			miner: miner,
 			io: io,
 			hashrates: hashrates,
+			result_cache: result_cache,
+			execution_limit: execution_limit,
 		}
 	}
 }
@@ -88,7 +99,7 @@ impl Default for EthTester {
 #[test]
 fn rpc_eth_protocol_version() {
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_protocolVersion", "params": [], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"63","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x3f","id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
@@ -115,7 +126,7 @@ fn rpc_eth_syncing() {
 		}
 	}
 
-	let true_res = r#"{"jsonrpc":"2.0","result":{"currentBlock":"0x03e8","highestBlock":"0x09c4","startingBlock":"0x00"},"id":1}"#;
+	let true_res = r#"{"jsonrpc":"2.0","result":{"currentBlock":"0x3e8","highestBlock":"0x9c4","startingBlock":"0x0"},"id":1}"#;
 	assert_eq!(tester.io.handle_request(request), Some(true_res.to_owned()));
 
 	{
@@ -132,9 +143,9 @@ fn rpc_eth_syncing() {
 #[test]
 fn rpc_eth_hashrate() {
 	let tester = EthTester::default();
-	tester.hashrates.write().unwrap().insert(H256::from(0), U256::from(0xfffa));
-	tester.hashrates.write().unwrap().insert(H256::from(0), U256::from(0xfffb));
-	tester.hashrates.write().unwrap().insert(H256::from(1), U256::from(0x1));
+	tester.hashrates.write().unwrap().insert(H256::from(0), (U256::from(0xfffa), Instant::now()));
+	tester.hashrates.write().unwrap().insert(H256::from(0), (U256::from(0xfffb), Instant::now()));
+	tester.hashrates.write().unwrap().insert(H256::from(1), (U256::from(0x1), Instant::now()));
 
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_hashrate", "params": [], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"0xfffc","id":1}"#;
@@ -157,7 +168,7 @@ fn rpc_eth_submit_hashrate() {
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
 
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
-	assert_eq!(tester.hashrates.read().unwrap().get(&H256::from("0x59daa26581d0acd1fce254fb7e85952f4c09d0915afd33d3886cd914bc7d283c")).cloned(),
+	assert_eq!(tester.hashrates.read().unwrap().get(&H256::from("0x59daa26581d0acd1fce254fb7e85952f4c09d0915afd33d3886cd914bc7d283c")).map(|&(rate, _)| rate),
 		Some(U256::from(0x500_000)));
 }
 
@@ -196,7 +207,8 @@ fn rpc_eth_author() {
 		"id": 1
 	}"#;
 
-	assert_eq!(tester.io.handle_request(req), Some(make_res(Address::zero())));
+	let no_author = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Author not configured. Run parity with --author to configure.","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request(req), Some(no_author.to_owned()));
 
 	for i in 0..20 {
 		let addr = tester.accounts_provider.new_account(&format!("{}", i)).unwrap();
@@ -214,7 +226,7 @@ fn rpc_eth_mining() {
 	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 
-	tester.hashrates.write().unwrap().insert(H256::from(1), U256::from(0x1));
+	tester.hashrates.write().unwrap().insert(H256::from(1), (U256::from(0x1), Instant::now()));
 
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_mining", "params": [], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
@@ -224,11 +236,56 @@ fn rpc_eth_mining() {
 #[test]
 fn rpc_eth_gas_price() {
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x04a817c800","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x4a817c800","id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_max_priority_fee_per_gas() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(10, EachBlockWith::Transaction);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_maxPriorityFeePerGas", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_fee_history() {
+	fn fee_history(tester: &EthTester, request: &str) -> Value {
+		let response: Value = serde_json::from_str(&tester.io.handle_request(request).unwrap()).unwrap();
+		response.find("result").unwrap().clone()
+	}
+	fn hex(values: &[&str]) -> Value {
+		Value::Array(values.iter().map(|v| Value::String(v.to_string())).collect())
+	}
+	fn ratios(result: &Value) -> Vec<f64> {
+		result.find("gasUsedRatio").unwrap().as_array().unwrap().iter().map(|r| r.as_f64().unwrap()).collect()
+	}
+
+	let tester = EthTester::default();
+	tester.client.add_blocks(2, EachBlockWith::Transaction);
+
+	let result = fee_history(&tester, r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x2", "latest", [0, 50]], "id": 1}"#);
+	assert_eq!(result.find("oldestBlock"), Some(&Value::String("0x1".to_owned())));
+	assert_eq!(result.find("baseFeePerGas"), Some(&hex(&["0x1", "0x1", "0x1"])));
+	assert_eq!(ratios(&result), vec![0.0, 0.0]);
+	assert_eq!(result.find("reward"), Some(&Value::Array(vec![hex(&["0x0", "0x0"]), hex(&["0x0", "0x0"])])));
+
+	// window reaching past genesis is clamped
+	let result = fee_history(&tester, r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": [10, "latest"], "id": 1}"#);
+	assert_eq!(result.find("oldestBlock"), Some(&Value::String("0x0".to_owned())));
+	assert_eq!(result.find("baseFeePerGas"), Some(&hex(&["0x0", "0x1", "0x1", "0x1"])));
+	assert_eq!(ratios(&result), vec![0.0, 0.0, 0.0]);
+	assert_eq!(result.find("reward"), Some(&Value::Null));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x2", "latest", [50, 10]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_accounts() {
 	let tester = EthTester::default();
@@ -240,13 +297,37 @@ fn rpc_eth_accounts() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_accounts_filtered_for_remote_callers() {
+	let tester = EthTester::default();
+	let hidden = tester.accounts_provider.new_account("").unwrap();
+	let visible = tester.accounts_provider.new_account("").unwrap();
+	let external_miner = Arc::new(ExternalMiner::default());
+	let remote = |filter| {
+		let io = IoHandler::new();
+		io.add_delegate(EthClient::new(&tester.client, &tester.sync, &tester.accounts_provider, &tester.miner, &external_miner, true, &tester.result_cache, 60, filter, None, &tester.execution_limit).to_delegate());
+		io
+	};
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_accounts", "params": [], "id": 1}"#;
+	let hide_all = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	let whitelisted = r#"{"jsonrpc":"2.0","result":[""#.to_owned() + &format!("0x{:?}", visible) + r#""],"id":1}"#;
+
+	assert_eq!(remote(AccountsFilter::Only(vec![])).handle_request(request), Some(hide_all.to_owned()));
+	assert_eq!(remote(AccountsFilter::Only(vec![visible])).handle_request(request), Some(whitelisted));
+
+	let local = tester.io.handle_request(request).unwrap();
+	assert!(local.contains(&format!("0x{:?}", hidden)));
+	assert!(local.contains(&format!("0x{:?}", visible)));
+}
+
 #[test]
 fn rpc_eth_block_number() {
 	let tester = EthTester::default();
 	tester.client.add_blocks(10, EachBlockWith::Nothing);
 
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x0a","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0xa","id":1}"#;
 
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
@@ -262,11 +343,35 @@ fn rpc_eth_balance() {
 		"params": ["0x0000000000000000000000000000000000000001", "latest"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x05","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x5","id":1}"#;
 
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_balance_named_params() {
+	let tester = EthTester::default();
+	tester.client.set_balance(Address::from(1), U256::from(5));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBalance",
+		"params": {"address": "0x0000000000000000000000000000000000000001"},
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x5","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBalance",
+		"params": {"address": "0x0000000000000000000000000000000000000001", "blockNumber": "latest"},
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_balance_pending() {
 	let tester = EthTester::default();
@@ -282,7 +387,7 @@ fn rpc_eth_balance_pending() {
 	// the TestMinerService doesn't communicate with the the TestBlockChainClient in any way.
 	// if this returns zero, we know that the "pending" call is being properly forwarded to the
 	// miner.
-	let response = r#"{"jsonrpc":"2.0","result":"0x00","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
 
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
@@ -298,7 +403,7 @@ fn rpc_eth_storage_at() {
 		"params": ["0x0000000000000000000000000000000000000001", "0x4", "latest"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x07","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000007","id":1}"#;
 
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
@@ -311,11 +416,48 @@ fn rpc_eth_transaction_count() {
 		"params": ["0x0000000000000000000000000000000000000001", "latest"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x00","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_count_pending_includes_queued() {
+	let tester = EthTester::default();
+	tester.miner.last_nonces.write().unwrap().insert(Address::from(1), U256::from(4));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionCount",
+		"params": ["0x0000000000000000000000000000000000000001", "pending"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x5","id":1}"#;
+
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_state_at_unavailable_block() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(2, EachBlockWith::Nothing);
+	let hash = BlockChainClient::block_hash(&*tester.client, BlockID::Number(1)).unwrap();
+
+	// the test client keeps only the latest state
+	let pruned = r#"{"jsonrpc":"2.0","error":{"code":-32071,"message":"State of this block has been pruned. Run parity with --pruning=archive to keep historical state.","data":null},"id":1}"#;
+	let unknown = r#"{"jsonrpc":"2.0","error":{"code":-32070,"message":"Block is not known.","data":null},"id":1}"#;
+	for method in &["eth_getBalance", "eth_getTransactionCount"] {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "{}", "params": ["0x0000000000000000000000000000000000000001", "0x1"], "id": 1}}"#, method);
+		assert_eq!(tester.io.handle_request(&request), Some(pruned.to_owned()));
+
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "{}", "params": ["0x0000000000000000000000000000000000000001", "0x{}"], "id": 1}}"#, method, hash.hex());
+		assert_eq!(tester.io.handle_request(&request), Some(pruned.to_owned()));
+
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "{}", "params": ["0x0000000000000000000000000000000000000001", "0x10"], "id": 1}}"#, method);
+		assert_eq!(tester.io.handle_request(&request), Some(unknown.to_owned()));
+	}
+}
+
 #[test]
 fn rpc_eth_block_transaction_count_by_hash() {
 	let request = r#"{
@@ -337,7 +479,7 @@ fn rpc_eth_transaction_count_by_number() {
 		"params": ["latest"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x00","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
@@ -350,7 +492,7 @@ fn rpc_eth_transaction_count_by_number_pending() {
 		"params": ["pending"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x01","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
@@ -366,7 +508,7 @@ fn rpc_eth_pending_transaction_by_hash() {
 		tester.miner.pending_transactions.lock().unwrap().insert(H256::zero(), tx);
 	}
 
-	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":null,"blockNumber":null,"creates":null,"from":"0x0f65fe9276bc9a24ae7083ae28e2660ef72df99e","gas":"0x5208","gasPrice":"0x01","hash":"0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31","input":"0x","nonce":"0x00","to":"0x095e7baea6a6c7c4c2dfeb977efac326af552d87","transactionIndex":null,"value":"0x0a"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":null,"blockNumber":null,"creates":null,"from":"0x0f65fe9276bc9a24ae7083ae28e2660ef72df99e","gas":"0x5208","gasPrice":"0x1","hash":"0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31","input":"0x","nonce":"0x0","to":"0x095e7baea6a6c7c4c2dfeb977efac326af552d87","transactionIndex":null,"value":"0xa"},"id":1}"#;
 	let request = r#"{
 		"jsonrpc": "2.0",
 		"method": "eth_getTransactionByHash",
@@ -377,6 +519,38 @@ fn rpc_eth_pending_transaction_by_hash() {
 }
 
 
+#[test]
+fn rpc_eth_pending_transaction_filter() {
+	use util::*;
+	use ethcore::transaction::*;
+
+	let tester = EthTester::default();
+	let tx: SignedTransaction = decode(&FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_newPendingTransactionFilter", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_newPendingTransactionFilter", "params": [true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	tester.miner.pending_transactions.lock().unwrap().insert(tx.hash(), tx);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getFilterChanges", "params": ["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31"],"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getFilterChanges", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"blockHash":null,"blockNumber":null,"creates":null,"from":"0x0f65fe9276bc9a24ae7083ae28e2660ef72df99e","gas":"0x5208","gasPrice":"0x1","hash":"0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31","input":"0x","nonce":"0x0","to":"0x095e7baea6a6c7c4c2dfeb977efac326af552d87","transactionIndex":null,"value":"0xa"}],"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	// transactions are delivered only once
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getFilterChanges", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_uncle_count_by_block_hash() {
 	let request = r#"{
@@ -398,11 +572,58 @@ fn rpc_eth_uncle_count_by_block_number() {
 		"params": ["latest"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x00","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_counts_of_block_with_transaction_and_uncle() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::UncleAndTransaction);
+	let hash = tester.client.block_hash(BlockID::Number(1)).unwrap();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getBlockTransactionCountByHash", "params": ["0x{}"], "id": 1}}"#, hash.hex());
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockTransactionCountByNumber", "params": ["latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getUncleCountByBlockHash", "params": ["0x{}"], "id": 1}}"#, hash.hex());
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getUncleCountByBlockNumber", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_uncle_by_block_and_index() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::Uncle);
+	let hash = BlockChainClient::block_hash(&*tester.client, BlockID::Number(1)).unwrap();
+	let uncle = BlockChainClient::uncle(&*tester.client, UncleID(BlockID::Number(1), 0)).unwrap();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getUncleByBlockHashAndIndex", "params": ["0x{}", "0x0"], "id": 1}}"#, hash.hex());
+	let by_hash = tester.io.handle_request(&request).unwrap();
+	let response: Value = serde_json::from_str(&by_hash).unwrap();
+	let block = response.find("result").unwrap();
+	assert_eq!(block.find("hash"), Some(&Value::String(format!("0x{}", uncle.hash().hex()))));
+	assert_eq!(block.find("parentHash"), Some(&Value::String(format!("0x{}", uncle.parent_hash.hex()))));
+	assert_eq!(block.find("number"), Some(&Value::String("0x1".to_owned())));
+	assert_eq!(block.find("difficulty"), Some(&Value::String("0x1".to_owned())));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getUncleByBlockNumberAndIndex", "params": ["0x1", "0x0"], "id": 1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(by_hash));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getUncleByBlockNumberAndIndex", "params": ["0x1", "0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_code() {
 	let tester = EthTester::default();
@@ -454,6 +675,35 @@ fn rpc_eth_call() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_over_execution_capacity() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Executed {
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: None,
+		vm_trace: None,
+		state_diff: None,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_call", "params": [{"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"}, "latest"], "id": 1}"#;
+	let rejected = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Execution capacity exceeded. Too many calls are being executed, try again later.","data":null},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+	// executions of other requests occupy all slots
+	let permits = vec![tester.execution_limit.acquire(), tester.execution_limit.acquire()];
+	assert_eq!(tester.io.handle_request(request), Some(rejected.to_owned()));
+
+	drop(permits);
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(tester.execution_limit.running(), 0);
+}
+
 #[test]
 fn rpc_eth_call_default_block() {
 	let tester = EthTester::default();
@@ -641,6 +891,34 @@ fn rpc_eth_send_raw_transaction() {
 	assert_eq!(tester.io.handle_request(&req), Some(res));
 }
 
+#[test]
+fn rpc_eth_raw_transaction_roundtrip() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account("abcd").unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "abcd".into()).unwrap();
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x9184e72a000u64),
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		value: U256::from(0x9184e72au64),
+		data: vec![]
+	};
+	let signature = tester.accounts_provider.sign(address, t.hash()).unwrap();
+	let t = t.with_signature(signature);
+	let rlp = ::util::rlp::encode(&t).to_vec().to_hex();
+
+	let send = r#"{"jsonrpc": "2.0", "method": "eth_sendRawTransaction", "params": ["0x"#.to_owned() + &rlp + r#""], "id": 1}"#;
+	tester.io.handle_request(&send).unwrap();
+	let imported = tester.miner.imported_transactions.lock().unwrap()[0].clone();
+	tester.miner.pending_transactions.lock().unwrap().insert(imported.hash(), imported);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getRawTransactionByHash", "params": [""#.to_owned() + &format!("0x{:?}", t.hash()) + r#""], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x"#.to_owned() + &rlp + r#"","id":1}"#;
+	assert_eq!(tester.io.handle_request(&request), Some(response));
+}
+
 #[test]
 fn rpc_eth_transaction_receipt() {
 	let receipt = LocalizedReceipt {
@@ -665,7 +943,8 @@ fn rpc_eth_transaction_receipt() {
 			transaction_hash: H256::new(),
 			transaction_index: 0,
 			log_index: 1,
-		}]
+		}],
+		log_bloom: Default::default(),
 	};
 
 	let hash = H256::from_str("b903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238").unwrap();
@@ -678,7 +957,7 @@ fn rpc_eth_transaction_receipt() {
 		"params": ["0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x04510c","contractAddress":null,"cumulativeGasUsed":"0x20","gasUsed":"0x10","logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x04510c","data":"0x","logIndex":"0x01","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x00","type":"mined"}],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x00"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","contractAddress":null,"cumulativeGasUsed":"0x20","gasUsed":"0x10","logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","data":"0x","logIndex":"0x1","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","type":"mined"}],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0"},"id":1}"#;
 
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
@@ -698,6 +977,182 @@ fn rpc_eth_transaction_receipt_null() {
 	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_block_by_number_caches_immutable_blocks() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(100, EachBlockWith::Nothing);
+
+	let old_block = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["0x01", false], "id": 1}"#;
+	let recent_block = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["0x63", false], "id": 1}"#;
+
+	let response = tester.io.handle_request(old_block);
+	assert_eq!(tester.io.handle_request(old_block), response);
+	tester.io.handle_request(recent_block);
+	tester.io.handle_request(recent_block);
+
+	let stats = tester.result_cache.stats();
+	assert_eq!(stats.hits, 1);
+	assert_eq!(stats.misses, 3);
+	assert_eq!(stats.size, 1);
+}
+
+fn pending_block_tester() -> EthTester {
+	use util::*;
+	use ethcore::transaction::*;
+
+	let tester = EthTester::default();
+	let tx: SignedTransaction = decode(&FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
+	tester.miner.pending_transactions.lock().unwrap().insert(H256::zero(), tx);
+	tester
+}
+
+#[test]
+fn rpc_eth_pending_block_with_full_transactions() {
+	let tester = pending_block_tester();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["pending", true], "id": 1}"#;
+	let response: Value = serde_json::from_str(&tester.io.handle_request(request).unwrap()).unwrap();
+	let block = response.find("result").unwrap();
+
+	assert_eq!(block.find("hash"), Some(&Value::Null));
+	assert_eq!(block.find("number"), Some(&Value::String("0x1".to_owned())));
+	let transactions = block.find("transactions").unwrap().as_array().unwrap();
+	assert_eq!(transactions.len(), 1);
+	assert_eq!(transactions[0].find("hash"), Some(&Value::String("0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31".to_owned())));
+	assert_eq!(transactions[0].find("blockHash"), Some(&Value::Null));
+	assert_eq!(transactions[0].find("blockNumber"), Some(&Value::Null));
+	assert_eq!(transactions[0].find("transactionIndex"), Some(&Value::Null));
+}
+
+#[test]
+fn rpc_eth_pending_block_with_transaction_hashes() {
+	let tester = pending_block_tester();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["pending", false], "id": 1}"#;
+	let response: Value = serde_json::from_str(&tester.io.handle_request(request).unwrap()).unwrap();
+	let transactions = response.find_path(&["result", "transactions"]).unwrap().as_array().unwrap();
+
+	assert_eq!(transactions, &vec![Value::String("0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31".to_owned())]);
+}
+
+#[test]
+fn rpc_eth_transaction_by_block_and_index() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	let hash = BlockChainClient::block_hash(&*tester.client, BlockID::Number(1)).unwrap();
+
+	let by_hash = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getTransactionByBlockHashAndIndex", "params": ["0x{}", "0x0"], "id": 1}}"#, hash.hex());
+	let response: Value = serde_json::from_str(&tester.io.handle_request(&by_hash).unwrap()).unwrap();
+	let transaction = response.find("result").unwrap();
+	assert_eq!(transaction.find("blockHash"), Some(&Value::String(format!("0x{}", hash.hex()))));
+	assert_eq!(transaction.find("blockNumber"), Some(&Value::String("0x1".to_owned())));
+	assert_eq!(transaction.find("transactionIndex"), Some(&Value::String("0x0".to_owned())));
+
+	let by_number = r#"{"jsonrpc": "2.0", "method": "eth_getTransactionByBlockNumberAndIndex", "params": ["0x1", "0x0"], "id": 1}"#;
+	let by_number_response: Value = serde_json::from_str(&tester.io.handle_request(by_number).unwrap()).unwrap();
+	assert_eq!(by_number_response, response);
+}
+
+#[test]
+fn rpc_eth_transaction_by_block_and_index_out_of_range() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	let hash = BlockChainClient::block_hash(&*tester.client, BlockID::Number(1)).unwrap();
+
+	let by_hash = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getTransactionByBlockHashAndIndex", "params": ["0x{}", "0x1"], "id": 1}}"#, hash.hex());
+	let by_number = r#"{"jsonrpc": "2.0", "method": "eth_getTransactionByBlockNumberAndIndex", "params": ["0x1", "0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(tester.io.handle_request(&by_hash), Some(response.to_owned()));
+	assert_eq!(tester.io.handle_request(by_number), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_pending_transaction_by_block_number_and_index() {
+	let tester = pending_block_tester();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getTransactionByBlockNumberAndIndex", "params": ["pending", "0x0"], "id": 1}"#;
+	let response: Value = serde_json::from_str(&tester.io.handle_request(request).unwrap()).unwrap();
+	let transaction = response.find("result").unwrap();
+	assert_eq!(transaction.find("hash"), Some(&Value::String("0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31".to_owned())));
+	assert_eq!(transaction.find("blockHash"), Some(&Value::Null));
+	assert_eq!(transaction.find("blockNumber"), Some(&Value::String("0x1".to_owned())));
+	assert_eq!(transaction.find("transactionIndex"), Some(&Value::String("0x0".to_owned())));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getTransactionByBlockNumberAndIndex", "params": ["pending", "0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_block_by_number_full_transactions_match_by_hash() {
+	let tester = EthTester::default();
+	tester.client.add_blocks(1, EachBlockWith::Transaction);
+	let hash = BlockChainClient::block_hash(&*tester.client, BlockID::Number(1)).unwrap();
+
+	let by_hash = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": ["0x{}", true], "id": 1}}"#, hash.hex());
+	let expected = tester.io.handle_request(&by_hash);
+	assert!(expected.as_ref().map_or(false, |r| r.contains(r#""transactions":[{"#)));
+
+	for id in &[r#""0x1""#, r#""latest""#] {
+		let by_number = format!(r#"{{"jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [{}, true], "id": 1}}"#, id);
+		assert_eq!(tester.io.handle_request(&by_number), expected);
+	}
+}
+
+#[test]
+fn rpc_eth_logs() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_logs_paginated() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}, {"limit": 10, "cursor": "0x1:0x0"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"logs":[],"nextCursor":null},"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_logs_pages_through_mined_logs() {
+	let tester = EthTester::default();
+	let log = |block_number, log_index| LocalizedLogEntry {
+		block_number: block_number,
+		log_index: log_index,
+		..Default::default()
+	};
+	tester.client.set_logs(vec![log(1, 0), log(1, 1), log(2, 0)]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock": "earliest"}, {"limit": 2}], "id": 1}"#;
+	let response = tester.io.handle_request(request).unwrap();
+	assert_eq!(response.matches(r#""logIndex""#).count(), 2);
+	assert!(response.contains(r#""nextCursor":"0x2:0x0""#));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock": "earliest"}, {"limit": 2, "cursor": "0x2:0x0"}], "id": 1}"#;
+	let response = tester.io.handle_request(request).unwrap();
+	assert_eq!(response.matches(r#""logIndex""#).count(), 1);
+	assert!(response.contains(r#""nextCursor":null"#));
+}
+
+#[test]
+fn rpc_eth_logs_zero_limit() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}, {"limit": 0}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_logs_invalid_cursor() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}, {"cursor": "0x1"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_compilers() {
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_getCompilers", "params": [], "id": 1}"#;
@@ -716,12 +1171,34 @@ fn rpc_eth_compile_lll() {
 
 #[test]
 fn rpc_eth_compile_solidity() {
-	let request = r#"{"jsonrpc": "2.0", "method": "eth_compileSolidity", "params": [], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":null},"id":1}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_compileSolidity", "params": ["contract A {}"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32080,"message":"Solidity compiler not available. Run parity with --solc to configure.","data":null},"id":1}"#;
 
 	assert_eq!(EthTester::default().io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_compile_solidity_with_compiler_configured() {
+	let tester = EthTester::default();
+	let external_miner = Arc::new(ExternalMiner::default());
+	let solc = SolcConfig::new("/nonexistent/solc");
+	let io = IoHandler::new();
+	io.add_delegate(EthClient::new(&tester.client, &tester.sync, &tester.accounts_provider, &tester.miner, &external_miner, true, &tester.result_cache, 60, AccountsFilter::All, Some(solc), &tester.execution_limit).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getCompilers", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["solidity"],"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	// the configured binary can't be started
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_compileSolidity", "params": ["contract A {}"], "id": 1}"#;
+	let response = io.handle_request(request).unwrap();
+	assert!(response.contains(r#""code":-32080"#));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_compileSolidity", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_compile_serpent() {
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_compileSerpent", "params": [], "id": 1}"#;
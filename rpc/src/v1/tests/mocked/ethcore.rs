@@ -18,10 +18,20 @@ use std::sync::Arc;
 use jsonrpc_core::IoHandler;
 use v1::{Ethcore, EthcoreClient};
 use v1::tests::helpers::TestMinerService;
-use v1::helpers::ConfirmationsQueue;
-use ethcore::client::{TestBlockChainClient};
+use v1::helpers::{ConfirmationsQueue, DappsProvider, FetchLimits, ResultCache};
+use v1::types::{Dapp, DappSource};
+use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockID, TransactionID};
+use ethcore::receipt::LocalizedReceipt;
+use ethcore::service::SyncMessage;
 use util::log::RotatingLogger;
+use util::network::{NetworkConfiguration, NetworkService};
 use util::network_settings::NetworkSettings;
+use util::misc::{version, short_sha};
+use util::numbers::*;
+use util::crypto::KeyPair;
+use ethcore::transaction::{Transaction, Action};
+use serde_json;
+use serde_json::Value;
 
 fn miner_service() -> Arc<TestMinerService> {
 	Arc::new(TestMinerService::default())
@@ -31,6 +41,10 @@ fn client_service() -> Arc<TestBlockChainClient> {
 	Arc::new(TestBlockChainClient::default())
 }
 
+fn network_service() -> Arc<NetworkService<SyncMessage>> {
+	Arc::new(NetworkService::new(NetworkConfiguration::new()).unwrap())
+}
+
 fn logger() -> Arc<RotatingLogger> {
 	Arc::new(RotatingLogger::new("rpc=trace".to_owned()))
 }
@@ -48,7 +62,7 @@ fn settings() -> Arc<NetworkSettings> {
 }
 
 fn ethcore_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>) -> EthcoreClient<TestBlockChainClient, TestMinerService> {
-	EthcoreClient::new(client, miner, logger(), settings(), None)
+	EthcoreClient::new(client, miner, &network_service(), logger(), settings(), None, None, FetchLimits::default(), &Arc::new(ResultCache::default()))
 }
 
 #[test]
@@ -93,6 +107,19 @@ fn rpc_ethcore_gas_floor_target() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_gas_ceil_target() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_gasCeilTarget", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x7fffffffffffffff","id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_ethcore_min_gas_price() {
 	let miner = miner_service();
@@ -101,7 +128,7 @@ fn rpc_ethcore_min_gas_price() {
 	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_minGasPrice", "params": [], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":"0x01312d00","id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1312d00","id":1}"#;
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
@@ -113,7 +140,7 @@ fn rpc_ethcore_dev_logs() {
 	let logger = logger();
 	logger.append("a".to_owned());
 	logger.append("b".to_owned());
-	let ethcore = EthcoreClient::new(&client, &miner, logger.clone(), settings(), None).to_delegate();
+	let ethcore = EthcoreClient::new(&client, &miner, &network_service(), logger.clone(), settings(), None, None, FetchLimits::default(), &Arc::new(ResultCache::default())).to_delegate();
 	let io = IoHandler::new();
 	io.add_delegate(ethcore);
 
@@ -214,13 +241,106 @@ fn rpc_ethcore_node_name() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_ethcore_version_info() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_versionInfo", "params":[], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":{{"client":"{}","hash":"{}","version":{{"major":{},"minor":{},"patch":{}}}}},"id":1}}"#,
+		version(), short_sha(), env!("CARGO_PKG_VERSION_MAJOR"), env!("CARGO_PKG_VERSION_MINOR"), env!("CARGO_PKG_VERSION_PATCH"));
+
+	assert_eq!(io.handle_request(request), Some(response));
+}
+
+#[test]
+fn rpc_parity_pending_transactions_stats() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let keypair = KeyPair::create().unwrap();
+	for (nonce, gas_price) in vec![(0u64, 5u64), (1, 12), (2, 15)] {
+		let t = Transaction {
+			nonce: U256::from(nonce),
+			gas_price: U256::from(gas_price),
+			gas: U256::from(21_000),
+			action: Action::Create,
+			value: U256::zero(),
+			data: vec![]
+		}.sign(keypair.secret());
+		miner.pending_transactions.lock().unwrap().insert(t.hash(), t);
+	}
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsStats", "params":[["0xa"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"count":"0x1","maxGasPrice":"0xa","minGasPrice":"0x0","totalGas":"0x5208"},{"count":"0x2","maxGasPrice":null,"minGasPrice":"0xa","totalGas":"0xa410"}],"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsStats", "params":[], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request(request).unwrap()).unwrap();
+	let buckets = response.find("result").unwrap().as_array().unwrap();
+	// [0, 4), [4, 8) and from 8 on
+	assert_eq!(buckets.len(), 3);
+	assert_eq!(buckets[2].find("count").unwrap().as_string(), Some("0x2"));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsStats", "params":[["0x14", "0xa"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_future_transactions() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	// account nonce is 0, so both transactions are stuck behind a nonce gap
+	let keypair = KeyPair::create().unwrap();
+	for nonce in vec![2u64, 3] {
+		let t = Transaction {
+			nonce: U256::from(nonce),
+			gas_price: U256::from(0x9184e72a000u64),
+			gas: U256::from(0x76c0),
+			action: Action::Call(Address::from(1)),
+			value: U256::zero(),
+			data: vec![]
+		}.sign(keypair.secret());
+		miner.future_transactions.lock().unwrap().push(t);
+	}
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_futureTransactions", "params":[], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request(request).unwrap()).unwrap();
+	let result = response.find("result").unwrap().as_object().unwrap();
+
+	assert_eq!(result.len(), 1);
+	let by_nonce = result[&format!("0x{:?}", keypair.address())].as_object().unwrap();
+	assert_eq!(by_nonce.keys().cloned().collect::<Vec<_>>(), vec!["2".to_owned(), "3".to_owned()]);
+}
+
+#[test]
+fn rpc_parity_hash_content_rejects_non_http_urls() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_hashContent", "params":["file:///etc/passwd"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32050,"message":"Unable to fetch content.","data":"UnsupportedScheme"},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_ethcore_unsigned_transactions_count() {
 	let miner = miner_service();
 	let client = client_service();
 	let io = IoHandler::new();
 	let queue = Arc::new(ConfirmationsQueue::default());
-	let ethcore = EthcoreClient::new(&client, &miner, logger(), settings(), Some(queue)).to_delegate();
+	let ethcore = EthcoreClient::new(&client, &miner, &network_service(), logger(), settings(), Some(queue), None, FetchLimits::default(), &Arc::new(ResultCache::default())).to_delegate();
 	io.add_delegate(ethcore);
 
 	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_unsignedTransactionsCount", "params":[], "id": 1}"#;
@@ -241,3 +361,228 @@ fn rpc_ethcore_unsigned_transactions_count_when_signer_disabled() {
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_registry_address() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_registryAddress", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	client.set_registrar(Some(Address::from(0x42)));
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000042","id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_chain() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+	client.set_spec_name("Morden");
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_chain", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"Morden","id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_netChain", "params":[], "id": 1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_resolve_name() {
+	use ethcore::client::Executed;
+
+	let miner = miner_service();
+	let client = client_service();
+	client.set_registrar(Some(Address::from(0x42)));
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let mut output = vec![0u8; 32];
+	output[31] = 0x01;
+	client.set_execution_result(Executed {
+		gas: U256::zero(),
+		gas_used: U256::zero(),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output,
+		trace: None,
+		vm_trace: None,
+		state_diff: None,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resolveName", "params":["gavofyork"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"address":"0x0000000000000000000000000000000000000001","content":"0x0000000000000000000000000000000000000000000000000000000000000001"},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resolveName", "params":["Gav..ofyork"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32061,"message":"Name is malformed.","data":"InvalidCharacter('G')"},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_resolve_unregistered_name() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resolveName", "params":["gavofyork"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32060,"message":"Name is not registered.","data":"gavofyork"},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+struct TestDapps;
+
+impl DappsProvider for TestDapps {
+	fn dapps(&self) -> Vec<Dapp> {
+		vec![Dapp {
+			id: "wallet".into(),
+			name: "Wallet".into(),
+			version: "1.0.0".into(),
+			source: DappSource::Builtin,
+			content_hash: None,
+		}, Dapp {
+			id: "My Dapp".into(),
+			name: "Local dapp".into(),
+			version: "0.1.0".into(),
+			source: DappSource::Local,
+			content_hash: None,
+		}]
+	}
+}
+
+#[test]
+fn rpc_parity_dapps_list_disabled() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dappsList", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_dapps_list() {
+	use ethcore::client::Executed;
+
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(EthcoreClient::new(&client, &miner, &network_service(), logger(), settings(), None, Some(Arc::new(TestDapps)), FetchLimits::default(), &Arc::new(ResultCache::default())).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dappsList", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"wallet","name":"Wallet","version":"1.0.0","source":"builtin","contentHash":null},{"id":"My Dapp","name":"Local dapp","version":"0.1.0","source":"local","contentHash":null}],"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let mut output = vec![0u8; 32];
+	output[31] = 0x01;
+	client.set_registrar(Some(Address::from(0x42)));
+	client.set_execution_result(Executed {
+		gas: U256::zero(),
+		gas_used: U256::zero(),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output,
+		trace: None,
+		vm_trace: None,
+		state_diff: None,
+	});
+
+	// ids which are not valid names are never looked up
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"wallet","name":"Wallet","version":"1.0.0","source":"builtin","contentHash":"0x0000000000000000000000000000000000000000000000000000000000000001"},{"id":"My Dapp","name":"Local dapp","version":"0.1.0","source":"local","contentHash":null}],"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_get_block_receipts() {
+	let miner = miner_service();
+	let client = client_service();
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let block_hash = client.block_hash_delta_minus(1);
+	for index in 0..2 {
+		client.set_transaction_receipt(TransactionID::Location(BlockID::Latest, index), LocalizedReceipt {
+			transaction_hash: H256::from(index as u64),
+			transaction_index: index,
+			block_hash: block_hash.clone(),
+			block_number: 1,
+			cumulative_gas_used: U256::from(0x5208 * (index + 1)),
+			gas_used: U256::from(0x5208),
+			contract_address: None,
+			logs: vec![],
+			log_bloom: Default::default(),
+		});
+	}
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockReceipts", "params":["latest"], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request(request).unwrap()).unwrap();
+	let receipts = response.find("result").and_then(Value::as_array).unwrap();
+	assert_eq!(receipts.len(), 2);
+	assert_eq!(receipts[0].find("transactionIndex"), Some(&Value::String("0x0".into())));
+	assert_eq!(receipts[0].find("cumulativeGasUsed"), Some(&Value::String("0x5208".into())));
+	assert_eq!(receipts[1].find("transactionIndex"), Some(&Value::String("0x1".into())));
+	assert_eq!(receipts[1].find("cumulativeGasUsed"), Some(&Value::String("0xa410".into())));
+	assert!(receipts[1].find("logsBloom").is_some());
+
+	// genesis has no transactions
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockReceipts", "params":["earliest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_get_block_receipts_unknown_block() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockReceipts", "params":["0x10"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32070,"message":"Block is not known.","data":null},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_enode_before_network_started() {
+	let miner = miner_service();
+	let client = client_service();
+	let net = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(EthcoreClient::new(&client, &miner, &net, logger(), settings(), None, None, FetchLimits::default(), &Arc::new(ResultCache::default())).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_enode", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32090,"message":"Network is not started yet.","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_result_cache_stats() {
+	let miner = miner_service();
+	let client = client_service();
+	let cache = Arc::new(ResultCache::new(16));
+	cache.insert("eth_getBlockByNumber:[\"0x1\",false]".to_owned(), ::jsonrpc_core::Value::Null);
+	assert!(cache.get("eth_getBlockByNumber:[\"0x1\",false]").is_some());
+	assert!(cache.get("eth_getBlockByNumber:[\"0x2\",false]").is_none());
+	let io = IoHandler::new();
+	io.add_delegate(EthcoreClient::new(&client, &miner, &network_service(), logger(), settings(), None, None, FetchLimits::default(), &cache).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resultCacheStats", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"capacity":16,"hits":1,"misses":1,"size":1},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
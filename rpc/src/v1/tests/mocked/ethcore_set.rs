@@ -57,11 +57,40 @@ fn rpc_ethcore_set_gas_floor_target() {
 	let io = IoHandler::new();
 	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
 
-	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setGasFloorTarget", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setGasFloorTarget", "params":["0x47b760"], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
-	assert_eq!(miner.gas_floor_target(), U256::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap());
+	assert_eq!(miner.gas_floor_target(), U256::from(4700000));
+}
+
+#[test]
+fn rpc_parity_set_gas_ceil_target() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setGasCeilTarget", "params":["0x47b760"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(miner.gas_ceil_target(), U256::from(4700000));
+}
+
+#[test]
+fn rpc_parity_set_gas_floor_target_above_ceil() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+	miner.set_gas_ceil_target(U256::from(4700000)).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setGasFloorTarget", "params":["0x47b761"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Block error (Invalid gas limit: Value 4700001 out of bounds. Min=5000, Max=4700000)","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(miner.gas_floor_target(), U256::from(12345));
 }
 
 #[test]
@@ -78,6 +107,20 @@ fn rpc_ethcore_set_extra_data() {
 	assert_eq!(miner.extra_data(), "cd1722f3947def4cf144679da39c4c32bdc35681".from_hex().unwrap());
 }
 
+#[test]
+fn rpc_ethcore_set_extra_data_too_long() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_setExtraData", "params":["0x000000000000000000000000000000000000000000000000000000000000000000"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Block error (Extra block data too long. Value 33 out of bounds. Max=32)","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(miner.extra_data(), vec![1, 2, 3, 4]);
+}
+
 #[test]
 fn rpc_ethcore_set_author() {
 	let miner = miner_service();
@@ -105,3 +148,24 @@ fn rpc_ethcore_set_transactions_limit() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
+
+#[test]
+fn rpc_parity_set_transactions_limit_rejects_zero() {
+	let miner = miner_service();
+	let network = network_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_set_client(&miner, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsLimit", "params":[0], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+	let limit = miner.transactions_limit();
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(miner.transactions_limit(), limit);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionsLimit", "params":[512], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+	assert_eq!(miner.transactions_limit(), 512);
+}
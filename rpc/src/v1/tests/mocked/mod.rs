@@ -26,3 +26,5 @@ mod personal_signer;
 mod ethcore;
 mod ethcore_set;
 mod rpc;
+mod txpool;
+mod shh;
@@ -40,6 +40,22 @@ fn rpc_net_version() {
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_net_version_is_decimal() {
+	let sync = Arc::new(TestSyncProvider::new(Config {
+		network_id: U256::from(61),
+		num_peers: 120,
+	}));
+	let net = NetClient::new(&sync).to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(net);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "net_version", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"61","id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_net_peer_count() {
 	let sync = sync_provider();
@@ -65,3 +81,34 @@ fn rpc_net_listening() {
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_net_not_listening() {
+	let sync = sync_provider();
+	*sync.listening.write().unwrap() = false;
+	let net = NetClient::new(&sync).to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(net);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "net_listening", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_net_remove_peer() {
+	let sync = sync_provider();
+	sync.peers.write().unwrap().push("0xff".to_owned());
+	let net = NetClient::new(&sync).to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(net);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "net_removePeer", "params": ["0xff"], "id": 1}"#;
+	let removed = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	let not_connected = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(removed.to_owned()));
+	assert_eq!(io.handle_request(request), Some(not_connected.to_owned()));
+	assert!(sync.peers.read().unwrap().is_empty());
+}
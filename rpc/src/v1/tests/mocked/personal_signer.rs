@@ -85,13 +85,43 @@ fn should_return_list_of_transactions_in_queue() {
 
 	// when
 	let request = r#"{"jsonrpc":"2.0","method":"personal_transactionsToConfirm","params":[],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x01","transaction":{"data":null,"from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x01"}}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x1","transaction":{"data":null,"from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x1"}}],"id":1}"#;
 
 	// then
 	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
 }
 
 
+#[test]
+fn should_return_list_of_requests_with_kind_and_payload() {
+	// given
+	let tester = signer_tester();
+	tester.queue.add_request(TransactionRequest {
+		from: Address::from(1),
+		to: Some(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		gas_price: Some(U256::from(10_000)),
+		gas: Some(U256::from(10_000_000)),
+		value: Some(U256::from(1)),
+		data: None,
+		nonce: None,
+	});
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"parity_signerRequestsToConfirm","params":[],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x1","kind":"sendTransaction","payload":{"data":null,"from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x1"}}],"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"parity_rejectRequest","params":["0x1"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request(&request), Some(response.to_owned()));
+	assert_eq!(tester.queue.requests().len(), 0);
+}
+
 #[test]
 fn should_reject_transaction_from_queue_without_dispatching() {
 	// given
@@ -175,7 +205,7 @@ fn should_confirm_transaction_and_dispatch() {
 	let request = r#"{
 		"jsonrpc":"2.0",
 		"method":"personal_confirmTransaction",
-		"params":["0x01", {"gasPrice":"0x1000"}, "test"],
+		"params":["0x1", {"gasPrice":"0x1000"}, "test"],
 		"id":1
 	}"#;
 	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
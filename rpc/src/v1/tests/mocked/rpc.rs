@@ -17,6 +17,7 @@
 use std::collections::BTreeMap;
 use jsonrpc_core::IoHandler;
 use v1::{Rpc, RpcClient};
+use methods::MethodRegistry;
 
 
 fn rpc_client() -> RpcClient {
@@ -24,7 +25,7 @@ fn rpc_client() -> RpcClient {
 	modules.insert("rpc".to_owned(), "1.0".to_owned());
 	modules.insert("web3".to_owned(), "1.0".to_owned());
 	modules.insert("ethcore".to_owned(), "1.0".to_owned());
-	RpcClient::new(modules)
+	RpcClient::new(modules, None)
 }
 
 #[test]
@@ -50,3 +51,28 @@ fn rpc_modules() {
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_methods() {
+	let io = IoHandler::new();
+	let mut registry = MethodRegistry::new();
+	let rpc = RpcClient::new(BTreeMap::new(), Some(registry.names()));
+	registry.register(&io, None, rpc.to_delegate()).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "rpc_methods", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["modules","rpc_methods","rpc_modules"],"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_methods_unknown() {
+	let rpc = rpc_client().to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(rpc);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "rpc_methods", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
@@ -0,0 +1,95 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use jsonrpc_core::IoHandler;
+use serde_json;
+use serde_json::Value;
+use whisper::WhisperHost;
+use v1::{Shh, ShhClient};
+
+fn setup() -> (Arc<WhisperHost>, IoHandler) {
+	let whisper = WhisperHost::new(1);
+	let io = IoHandler::new();
+	io.add_delegate(ShhClient::new(&whisper).to_delegate());
+	(whisper, io)
+}
+
+/// Waits for posted messages to be sealed and enter the pool.
+fn wait_for_pool(whisper: &WhisperHost, cursor: u64) {
+	let deadline = Instant::now() + Duration::from_secs(30);
+	while whisper.cursor() < cursor {
+		assert!(Instant::now() < deadline, "posted messages were not sealed");
+		thread::sleep(Duration::from_millis(10));
+	}
+}
+
+#[test]
+fn rpc_shh_version() {
+	let (_whisper, io) = setup();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_version", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"2","id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_new_identity() {
+	let (whisper, io) = setup();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_newIdentity", "params": [], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request(request).unwrap()).unwrap();
+	let identity = serde_json::from_value(response.find("result").unwrap().clone()).unwrap();
+
+	assert!(whisper.has_identity(&identity));
+}
+
+#[test]
+fn rpc_shh_filter_changes() {
+	let (whisper, io) = setup();
+
+	let new_filter = r#"{"jsonrpc": "2.0", "method": "shh_newFilter", "params": [{"topics": ["0x6d79746f706963"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+	assert_eq!(io.handle_request(new_filter), Some(response.to_owned()));
+
+	let post = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"topics": ["0x6d79746f706963"], "payload": "0x1234", "ttl": "0x64"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request(post), Some(response.to_owned()));
+	wait_for_pool(&whisper, 1);
+
+	let changes = r#"{"jsonrpc": "2.0", "method": "shh_getFilterChanges", "params": ["0x00"], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request(changes).unwrap()).unwrap();
+	let messages = response.find("result").unwrap().as_array().unwrap();
+	assert_eq!(messages.len(), 1);
+	assert_eq!(messages[0].find("payload"), Some(&Value::String("0x1234".to_owned())));
+	assert_eq!(messages[0].find("from"), Some(&Value::Null));
+
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(io.handle_request(changes), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_post_from_unknown_identity() {
+	let (_whisper, io) = setup();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"from": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001", "topics": [], "payload": "0x1234", "ttl": "0x64"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32040,"message":"Could not post whisper message.","data":"UnknownIdentity"},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
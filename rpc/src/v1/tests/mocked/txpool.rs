@@ -0,0 +1,82 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use jsonrpc_core::IoHandler;
+use serde_json;
+use serde_json::Value;
+use util::numbers::*;
+use util::crypto::KeyPair;
+use ethcore::transaction::{Transaction, SignedTransaction, Action};
+use v1::{TxPool, TxPoolClient};
+use v1::tests::helpers::TestMinerService;
+
+fn miner_service() -> Arc<TestMinerService> {
+	Arc::new(TestMinerService::default())
+}
+
+fn transaction(keypair: &KeyPair, nonce: u64) -> SignedTransaction {
+	Transaction {
+		nonce: U256::from(nonce),
+		gas_price: U256::from(0x9184e72a000u64),
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from(1)),
+		value: U256::zero(),
+		data: vec![]
+	}.sign(keypair.secret())
+}
+
+#[test]
+fn rpc_txpool_status() {
+	let miner = miner_service();
+	let txpool = TxPoolClient::new(&miner).to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(txpool);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "txpool_status", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"pending":"0x0","queued":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_txpool_content_groups_by_sender_and_nonce() {
+	let miner = miner_service();
+	let keypair = KeyPair::create().unwrap();
+	let sender = format!("0x{:?}", keypair.address());
+	{
+		let mut pending = miner.pending_transactions.lock().unwrap();
+		for t in vec![transaction(&keypair, 0), transaction(&keypair, 1)] {
+			pending.insert(t.hash(), t);
+		}
+	}
+	miner.future_transactions.lock().unwrap().push(transaction(&keypair, 10));
+
+	let txpool = TxPoolClient::new(&miner).to_delegate();
+	let io = IoHandler::new();
+	io.add_delegate(txpool);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "txpool_content", "params": [], "id": 1}"#;
+	let response: Value = serde_json::from_str(&io.handle_request(request).unwrap()).unwrap();
+	let result = response.find("result").unwrap();
+
+	let pending = result.find("pending").unwrap().find(&sender).unwrap().as_object().unwrap();
+	assert_eq!(pending.keys().cloned().collect::<Vec<_>>(), vec!["0".to_owned(), "1".to_owned()]);
+	assert_eq!(pending["1"].find("nonce"), Some(&Value::String("0x1".to_owned())));
+
+	let queued = result.find("queued").unwrap().find(&sender).unwrap().as_object().unwrap();
+	assert_eq!(queued.keys().cloned().collect::<Vec<_>>(), vec!["10".to_owned()]);
+}
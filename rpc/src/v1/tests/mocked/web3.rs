@@ -24,7 +24,9 @@ fn rpc_web3_version() {
 	let io = IoHandler::new();
 	io.add_delegate(web3);
 
-	let v = version().to_owned().replace("Parity/", "Parity//");
+	let v = version();
+	assert!(v.starts_with("Parity/v"));
+	assert_eq!(v.split('/').count(), 4);
 
 	let request = r#"{"jsonrpc": "2.0", "method": "web3_clientVersion", "params": [], "id": 1}"#;
 	let response = r#"{"jsonrpc":"2.0","result":"VER","id":1}"#.to_owned().replace("VER", v.as_ref());
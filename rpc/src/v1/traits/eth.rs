@@ -38,6 +38,12 @@ pub trait Eth: Sized + Send + Sync + 'static {
 	/// Returns current gas_price.
 	fn gas_price(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns suggested priority fee (gas price uplift over the cheapest recently included transactions).
+	fn max_priority_fee_per_gas(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns minimal gas prices, gas used ratios and priority fee percentiles of recent blocks.
+	fn fee_history(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns accounts list.
 	fn accounts(&self, _: Params) -> Result<Value, Error>;
 
@@ -92,6 +98,12 @@ pub trait Eth: Sized + Send + Sync + 'static {
 	/// Returns transaction by given block number and index.
 	fn transaction_by_block_number_and_index(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns RLP of signed transaction with given hash.
+	fn raw_transaction_by_hash(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns RLP of signed transaction at given block hash and index.
+	fn raw_transaction_by_block_hash_and_index(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns transaction receipt.
 	fn transaction_receipt(&self, _: Params) -> Result<Value, Error>;
 
@@ -114,6 +126,9 @@ pub trait Eth: Sized + Send + Sync + 'static {
 	fn compile_serpent(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns logs matching given filter object.
+	///
+	/// Non-standard extension: when an optional second parameter `{ "limit", "cursor" }` is given,
+	/// only mined logs are returned as a page `{ "logs", "nextCursor" }`. `limit` must be non-zero.
 	fn logs(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns the hash of the current block, the seedHash, and the boundary condition to be met.
@@ -134,6 +149,8 @@ pub trait Eth: Sized + Send + Sync + 'static {
 		delegate.add_method("eth_coinbase", Eth::author);
 		delegate.add_method("eth_mining", Eth::is_mining);
 		delegate.add_method("eth_gasPrice", Eth::gas_price);
+		delegate.add_method("eth_maxPriorityFeePerGas", Eth::max_priority_fee_per_gas);
+		delegate.add_method("eth_feeHistory", Eth::fee_history);
 		delegate.add_method("eth_accounts", Eth::accounts);
 		delegate.add_method("eth_blockNumber", Eth::block_number);
 		delegate.add_method("eth_getBalance", Eth::balance);
@@ -152,6 +169,8 @@ pub trait Eth: Sized + Send + Sync + 'static {
 		delegate.add_method("eth_getTransactionByHash", Eth::transaction_by_hash);
 		delegate.add_method("eth_getTransactionByBlockHashAndIndex", Eth::transaction_by_block_hash_and_index);
 		delegate.add_method("eth_getTransactionByBlockNumberAndIndex", Eth::transaction_by_block_number_and_index);
+		delegate.add_method("eth_getRawTransactionByHash", Eth::raw_transaction_by_hash);
+		delegate.add_method("eth_getRawTransactionByBlockHashAndIndex", Eth::raw_transaction_by_block_hash_and_index);
 		delegate.add_method("eth_getTransactionReceipt", Eth::transaction_receipt);
 		delegate.add_method("eth_getUncleByBlockHashAndIndex", Eth::uncle_by_block_hash_and_index);
 		delegate.add_method("eth_getUncleByBlockNumberAndIndex", Eth::uncle_by_block_number_and_index);
@@ -176,7 +195,9 @@ pub trait EthFilter: Sized + Send + Sync + 'static {
 	/// Returns id of new block filter.
 	fn new_block_filter(&self, _: Params) -> Result<Value, Error>;
 
-	/// Returns id of new block filter.
+	/// Returns id of new pending transaction filter.
+	/// With optional `true` parameter the filter returns full transactions instead of hashes.
+	/// Full transactions are much larger, so only use it when the bodies are needed.
 	fn new_pending_transaction_filter(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns filter changes since last poll.
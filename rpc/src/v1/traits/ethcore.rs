@@ -30,6 +30,9 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns mining gas floor target.
 	fn gas_floor_target(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns mining gas ceiling target.
+	fn gas_ceil_target(&self, _: Params) -> Result<Value, Error>;
+
 	/// Returns minimal gas price for transaction to be included in queue.
 	fn min_gas_price(&self, _: Params) -> Result<Value, Error>;
 
@@ -64,14 +67,52 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns error when signer is disabled
 	fn unsigned_transactions_count(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns version of the running node.
+	fn version_info(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns transactions which can't be mined yet because of a nonce gap, grouped by sender and nonce.
+	fn future_transactions(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns number of pending transactions and gas they use grouped by gas price.
+	/// Buckets start at given gas prices or at powers of two if none are given.
+	fn pending_transactions_stats(&self, _: Params) -> Result<Value, Error>;
+
+	/// Fetches content at given URL and returns its hash.
+	fn hash_content(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns address of the name registry contract or `null` if the chain has no registry.
+	fn registry_address(&self, _: Params) -> Result<Value, Error>;
+
+	/// Resolves a registered name to its address and content hash.
+	fn resolve_name(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns dapps served by the node with content hashes registered for their ids.
+	/// Empty if the dapps server is disabled.
+	fn dapps_list(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns receipts of all transactions in given block.
+	fn block_receipts(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns name of the chain specification the node runs.
+	fn chain(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the enode URL other nodes can use to connect to this node.
+	fn enode(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns hits, misses, size and capacity of the cache of immutable block and receipt results.
+	fn result_cache_stats(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 
 		delegate.add_method("ethcore_extraData", Ethcore::extra_data);
 		delegate.add_method("ethcore_gasFloorTarget", Ethcore::gas_floor_target);
+		delegate.add_method("parity_gasFloorTarget", Ethcore::gas_floor_target);
+		delegate.add_method("parity_gasCeilTarget", Ethcore::gas_ceil_target);
 		delegate.add_method("ethcore_minGasPrice", Ethcore::min_gas_price);
 		delegate.add_method("ethcore_transactionsLimit", Ethcore::transactions_limit);
+		delegate.add_method("parity_transactionsLimit", Ethcore::transactions_limit);
 		delegate.add_method("ethcore_devLogs", Ethcore::dev_logs);
 		delegate.add_method("ethcore_devLogsLevels", Ethcore::dev_logs_levels);
 		delegate.add_method("ethcore_netChain", Ethcore::net_chain);
@@ -82,6 +123,19 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_defaultExtraData", Ethcore::default_extra_data);
 		delegate.add_method("ethcore_gasPriceStatistics", Ethcore::gas_price_statistics);
 		delegate.add_method("ethcore_unsignedTransactionsCount", Ethcore::unsigned_transactions_count);
+		delegate.add_method("ethcore_versionInfo", Ethcore::version_info);
+		delegate.add_method("ethcore_futureTransactions", Ethcore::future_transactions);
+		delegate.add_method("parity_pendingTransactionsStats", Ethcore::pending_transactions_stats);
+		delegate.add_method("parity_hashContent", Ethcore::hash_content);
+		delegate.add_method("ethcore_hashContent", Ethcore::hash_content);
+		delegate.add_method("parity_registryAddress", Ethcore::registry_address);
+		delegate.add_method("parity_resolveName", Ethcore::resolve_name);
+		delegate.add_method("parity_dappsList", Ethcore::dapps_list);
+		delegate.add_method("parity_getBlockReceipts", Ethcore::block_receipts);
+		delegate.add_method("parity_chain", Ethcore::chain);
+		delegate.add_method("parity_netChain", Ethcore::chain);
+		delegate.add_method("parity_enode", Ethcore::enode);
+		delegate.add_method("parity_resultCacheStats", Ethcore::result_cache_stats);
 
 		delegate
 	}
@@ -24,6 +24,8 @@ pub mod ethcore;
 pub mod ethcore_set;
 pub mod traces;
 pub mod rpc;
+pub mod txpool;
+pub mod shh;
 
 pub use self::web3::Web3;
 pub use self::eth::{Eth, EthFilter, EthSigning};
@@ -33,5 +35,7 @@ pub use self::ethcore::Ethcore;
 pub use self::ethcore_set::EthcoreSet;
 pub use self::traces::Traces;
 pub use self::rpc::Rpc;
+pub use self::txpool::TxPool;
+pub use self::shh::Shh;
 
 
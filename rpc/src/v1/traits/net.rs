@@ -36,12 +36,16 @@ pub trait Net: Sized + Send + Sync + 'static {
 	/// Stop the network.
 	fn stop_network(&self, _: Params) -> Result<Value, Error>;
 
+	/// Disconnects given peer. Returns true if the peer was connected.
+	fn remove_peer(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 		delegate.add_method("net_version", Net::version);
 		delegate.add_method("net_peerCount", Net::peer_count);
 		delegate.add_method("net_listening", Net::is_listening);
+		delegate.add_method("net_removePeer", Net::remove_peer);
 		delegate
 	}
 }
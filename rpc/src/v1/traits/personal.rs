@@ -60,12 +60,24 @@ pub trait PersonalSigner: Sized + Send + Sync + 'static {
 	/// Reject the transaction request.
 	fn reject_transaction(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns a list of all pending requests with their kind and decoded payload.
+	fn requests_to_confirm(&self, _: Params) -> Result<Value, Error>;
+
+	/// Confirm a specific request.
+	fn confirm_request(&self, _: Params) -> Result<Value, Error>;
+
+	/// Reject a specific request.
+	fn reject_request(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
 		delegate.add_method("personal_transactionsToConfirm", PersonalSigner::transactions_to_confirm);
 		delegate.add_method("personal_confirmTransaction", PersonalSigner::confirm_transaction);
 		delegate.add_method("personal_rejectTransaction", PersonalSigner::reject_transaction);
+		delegate.add_method("parity_signerRequestsToConfirm", PersonalSigner::requests_to_confirm);
+		delegate.add_method("parity_confirmRequest", PersonalSigner::confirm_request);
+		delegate.add_method("parity_rejectRequest", PersonalSigner::reject_request);
 		delegate
 	}
 }
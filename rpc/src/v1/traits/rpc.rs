@@ -28,6 +28,9 @@ pub trait Rpc: Sized + Send + Sync + 'static {
 	/// Returns supported modules for Geth 1.4.0
 	fn rpc_modules(&self, _: Params) -> Result<Value, Error>;
 
+	/// Returns names of all methods available on this server.
+	fn rpc_methods(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -35,6 +38,7 @@ pub trait Rpc: Sized + Send + Sync + 'static {
 		delegate.add_method("modules", Rpc::modules);
 		// Geth 1.4.0 compatibility
 		delegate.add_method("rpc_modules", Rpc::rpc_modules);
+		delegate.add_method("rpc_methods", Rpc::rpc_methods);
 		delegate
 	}
 }
@@ -20,7 +20,7 @@ use jsonrpc_core::*;
 
 /// Web3 rpc interface.
 pub trait Web3: Sized + Send + Sync + 'static {
-	/// Returns current client version.
+	/// Returns current client version, e.g. `Parity/v1.2.0-unstable/x86_64-linux-gnu/rustc1.9.0`.
 	fn client_version(&self, _: Params) -> Result<Value, Error>;
 
 	/// Returns sha3 of the given data
@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::str::FromStr;
 use serde::{Deserialize, Deserializer, Error};
 use serde::de::Visitor;
+use util::hash::H256;
 use ethcore::client::BlockID;
 
 /// Represents rpc api block number param.
@@ -28,7 +30,9 @@ pub enum BlockNumber {
 	/// Earliest block (genesis)
 	Earliest,
 	/// Pending block (being mined)
-	Pending
+	Pending,
+	/// Block with given hash
+	Hash(H256),
 }
 
 impl Deserialize for BlockNumber {
@@ -48,6 +52,7 @@ impl Visitor for BlockNumberVisitor {
 			"latest" => Ok(BlockNumber::Latest),
 			"earliest" => Ok(BlockNumber::Earliest),
 			"pending" => Ok(BlockNumber::Pending),
+			_ if value.starts_with("0x") && value.len() == 66 => H256::from_str(&value[2..]).map(BlockNumber::Hash).map_err(|_| Error::custom("invalid block hash")),
 			_ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16).map(BlockNumber::Num).map_err(|_| Error::custom("invalid block number")),
 			_ => value.parse::<u64>().map(BlockNumber::Num).map_err(|_| Error::custom("invalid block number"))
 		}
@@ -63,6 +68,7 @@ impl Into<BlockID> for BlockNumber {
 		match self {
 			BlockNumber::Num(n) => BlockID::Number(n),
 			BlockNumber::Earliest => BlockID::Earliest,
+			BlockNumber::Hash(hash) => BlockID::Hash(hash),
 			// TODO: change this once blockid support pendingst,
 			BlockNumber::Pending | BlockNumber::Latest => BlockID::Latest,
 		}
@@ -72,6 +78,7 @@ impl Into<BlockID> for BlockNumber {
 #[cfg(test)]
 mod tests {
 	use ethcore::client::BlockID;
+	use util::hash::H256;
 	use super::*;
 	use serde_json;
 
@@ -82,12 +89,23 @@ mod tests {
 		assert_eq!(deserialized, vec![BlockNumber::Num(10), BlockNumber::Num(10), BlockNumber::Latest, BlockNumber::Earliest, BlockNumber::Pending])
 	}
 
+	#[test]
+	fn block_hash_deserialization() {
+		let s = r#""0x000000000000000000000000000000000000000000000000000000000000000a""#;
+		let deserialized: BlockNumber = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, BlockNumber::Hash(H256::from(10)));
+
+		let s = r#""0x00000000000000000000000000000000000000000000000000000000000000""#;
+		assert!(serde_json::from_str::<BlockNumber>(s).is_err());
+	}
+
 	#[test]
 	fn block_number_into() {
 		assert_eq!(BlockID::Number(100), BlockNumber::Num(100).into());
 		assert_eq!(BlockID::Earliest, BlockNumber::Earliest.into());
 		assert_eq!(BlockID::Latest, BlockNumber::Latest.into());
 		assert_eq!(BlockID::Latest, BlockNumber::Pending.into());
+		assert_eq!(BlockID::Hash(H256::from(10)), BlockNumber::Hash(H256::from(10)).into());
 	}
 }
 
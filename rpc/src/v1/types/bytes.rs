@@ -89,5 +89,13 @@ mod tests {
 		let serialized = serde_json::to_string(&bytes).unwrap();
 		assert_eq!(serialized, r#""0x0123456789abcdef""#);
 	}
+
+	#[test]
+	fn test_data_is_even_length() {
+		assert_eq!(serde_json::to_string(&Bytes::new(vec![])).unwrap(), r#""0x""#);
+		assert_eq!(serde_json::to_string(&Bytes::new(vec![0])).unwrap(), r#""0x00""#);
+		assert_eq!(serde_json::to_string(&Bytes::new(vec![0x0a])).unwrap(), r#""0x0a""#);
+		assert_eq!(serde_json::to_string(&Bytes::new(vec![0; 32])).unwrap(), format!(r#""0x{}""#, ::std::iter::repeat("00").take(32).collect::<String>()));
+	}
 }
 
@@ -0,0 +1,75 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dapps returned by `parity_dappsList`.
+
+use serde::{Serialize, Serializer};
+use util::hash::H256;
+
+/// Where a dapp is served from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DappSource {
+	/// Bundled with the node.
+	Builtin,
+	/// Installed in the local dapps directory.
+	Local,
+}
+
+impl Serialize for DappSource {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			DappSource::Builtin => serializer.serialize_str("builtin"),
+			DappSource::Local => serializer.serialize_str("local"),
+		}
+	}
+}
+
+/// Dapp served by the node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Dapp {
+	/// Dapp id, used as its URL path
+	pub id: String,
+	/// Dapp name
+	pub name: String,
+	/// Dapp version
+	pub version: String,
+	/// Where the dapp is served from
+	pub source: DappSource,
+	/// Content hash registered on chain for the dapp id, `None` if the id is not registered
+	#[serde(rename="contentHash")]
+	pub content_hash: Option<H256>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::hash::H256;
+	use super::{Dapp, DappSource};
+
+	#[test]
+	fn should_serialize_dapp() {
+		let dapp = Dapp {
+			id: "wallet".into(),
+			name: "Wallet".into(),
+			version: "1.0.0".into(),
+			source: DappSource::Builtin,
+			content_hash: Some(H256::from(1)),
+		};
+		let serialized = serde_json::to_string(&dapp).unwrap();
+		assert_eq!(serialized, r#"{"id":"wallet","name":"Wallet","version":"1.0.0","source":"builtin","contentHash":"0x0000000000000000000000000000000000000000000000000000000000000001"}"#);
+	}
+}
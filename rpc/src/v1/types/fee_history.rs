@@ -0,0 +1,35 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fee history returned by `eth_feeHistory`.
+
+use util::numbers::U256;
+
+/// Fees paid in a range of blocks.
+#[derive(Debug, Serialize)]
+pub struct FeeHistory {
+	/// Number of the oldest block in the range
+	#[serde(rename="oldestBlock")]
+	pub oldest_block: U256,
+	/// Minimal gas price of each block, followed by the same value for the block after the newest one
+	#[serde(rename="baseFeePerGas")]
+	pub base_fee_per_gas: Vec<U256>,
+	/// Gas used divided by gas limit of each block
+	#[serde(rename="gasUsedRatio")]
+	pub gas_used_ratio: Vec<f64>,
+	/// Requested percentiles of priority fees paid in each block, `None` if no percentiles were requested
+	pub reward: Option<Vec<Vec<U256>>>,
+}
@@ -86,7 +86,8 @@ impl Into<EthFilter> for Filter {
 					VariadicValue::Multiple(t) => Some(t)
 				}).filter_map(|m| m).collect()).into_iter();
 				[iter.next(), iter.next(), iter.next(), iter.next()]
-			}
+			},
+			limit: None,
 		}
 	}
 }
@@ -50,6 +50,10 @@ impl Visitor for IndexVisitor {
 	fn visit_string<E>(&mut self, value: String) -> Result<Self::Value, E> where E: Error {
 		self.visit_str(value.as_ref())
 	}
+
+	fn visit_u64<E>(&mut self, value: u64) -> Result<Self::Value, E> where E: Error {
+		Ok(Index(value as usize))
+	}
 }
 
 #[cfg(test)]
@@ -59,9 +63,9 @@ mod tests {
 
 	#[test]
 	fn block_number_deserialization() {
-		let s = r#"["0xa", "10"]"#;
+		let s = r#"["0xa", "10", 10]"#;
 		let deserialized: Vec<Index> = serde_json::from_str(s).unwrap();
-		assert_eq!(deserialized, vec![Index(10), Index(10)]);
+		assert_eq!(deserialized, vec![Index(10), Index(10), Index(10)]);
 	}
 }
 
@@ -0,0 +1,131 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pagination of `eth_getLogs` results (parity extension, not part of the standard API).
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer, Error};
+use serde::de::Visitor;
+use v1::types::Log;
+
+/// Position of a mined log in the chain. Logs are ordered by block number
+/// and then by log index within the block, so a cursor stays valid while new blocks are imported.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct LogCursor {
+	/// Block number
+	pub block_number: u64,
+	/// Log index within the block
+	pub log_index: usize,
+}
+
+impl LogCursor {
+	/// Creates new cursor pointing at given log.
+	pub fn new(block_number: u64, log_index: usize) -> LogCursor {
+		LogCursor {
+			block_number: block_number,
+			log_index: log_index,
+		}
+	}
+}
+
+impl Serialize for LogCursor {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		serializer.serialize_str(&format!("0x{:x}:0x{:x}", self.block_number, self.log_index))
+	}
+}
+
+impl Deserialize for LogCursor {
+	fn deserialize<D>(deserializer: &mut D) -> Result<LogCursor, D::Error>
+	where D: Deserializer {
+		deserializer.deserialize(LogCursorVisitor)
+	}
+}
+
+struct LogCursorVisitor;
+
+fn parse_hex_u64(value: &str) -> Option<u64> {
+	match value.len() > 2 && &value[0..2] == "0x" {
+		true => u64::from_str_radix(&value[2..], 16).ok(),
+		false => None,
+	}
+}
+
+impl Visitor for LogCursorVisitor {
+	type Value = LogCursor;
+
+	fn visit_str<E>(&mut self, value: &str) -> Result<Self::Value, E> where E: Error {
+		let mut parts = value.split(':');
+		match (parts.next().and_then(parse_hex_u64), parts.next().and_then(parse_hex_u64), parts.next()) {
+			(Some(block_number), Some(log_index), None) => Ok(LogCursor::new(block_number, log_index as usize)),
+			_ => Err(Error::custom("invalid log cursor")),
+		}
+	}
+
+	fn visit_string<E>(&mut self, value: String) -> Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
+/// Optional second parameter of `eth_getLogs`.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct LogsPagination {
+	/// Maximal number of logs to return
+	pub limit: Option<usize>,
+	/// Position of the first log to return, as given by `nextCursor` of the previous page
+	pub cursor: Option<LogCursor>,
+}
+
+/// Single page of `eth_getLogs` results.
+#[derive(Debug, Serialize)]
+pub struct LogsPage {
+	/// Logs
+	pub logs: Vec<Log>,
+	/// Cursor of the first log of the next page, `None` if this is the last page
+	#[serde(rename="nextCursor")]
+	pub next_cursor: Option<LogCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{LogCursor, LogsPagination};
+
+	#[test]
+	fn log_cursor_serialization() {
+		let cursor = LogCursor::new(0x4510c, 3);
+		let serialized = serde_json::to_string(&cursor).unwrap();
+		assert_eq!(serialized, r#""0x4510c:0x3""#);
+		let deserialized: LogCursor = serde_json::from_str(&serialized).unwrap();
+		assert_eq!(deserialized, cursor);
+	}
+
+	#[test]
+	fn invalid_log_cursor_is_rejected() {
+		assert!(serde_json::from_str::<LogCursor>(r#""0x10""#).is_err());
+		assert!(serde_json::from_str::<LogCursor>(r#""10:3""#).is_err());
+		assert!(serde_json::from_str::<LogCursor>(r#""0x10:0x3:0x1""#).is_err());
+	}
+
+	#[test]
+	fn logs_pagination_deserialization() {
+		let s = r#"{"limit":10,"cursor":"0x1:0x0"}"#;
+		let deserialized: LogsPagination = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, LogsPagination {
+			limit: Some(10),
+			cursor: Some(LogCursor::new(1, 0)),
+		});
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::numbers::U256;
-use util::hash::{Address, H256};
+use util::hash::{Address, H256, H2048};
 use v1::types::Log;
 use ethcore::receipt::{Receipt as EthReceipt, LocalizedReceipt};
 
@@ -45,6 +45,9 @@ pub struct Receipt {
 	pub contract_address: Option<Address>,
 	/// Logs
 	pub logs: Vec<Log>,
+	/// Logs bloom
+	#[serde(rename="logsBloom")]
+	pub logs_bloom: H2048,
 }
 
 impl From<LocalizedReceipt> for Receipt {
@@ -58,6 +61,7 @@ impl From<LocalizedReceipt> for Receipt {
 			gas_used: r.gas_used,
 			contract_address: r.contract_address,
 			logs: r.logs.into_iter().map(From::from).collect(),
+			logs_bloom: r.log_bloom,
 		}
 	}
 }
@@ -73,6 +77,7 @@ impl From<EthReceipt> for Receipt {
 			gas_used: r.gas_used,
 			contract_address: None,
 			logs: r.logs.into_iter().map(From::from).collect(),
+			logs_bloom: r.log_bloom,
 		}
 	}
 }
@@ -86,7 +91,7 @@ mod tests {
 
 	#[test]
 	fn receipt_serialization() {
-		let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x00","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x04510c","cumulativeGasUsed":"0x20","gasUsed":"0x10","contractAddress":null,"logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x04510c","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x00","logIndex":"0x01","type":"mined"}]}"#;
+		let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","cumulativeGasUsed":"0x20","gasUsed":"0x10","contractAddress":null,"logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","logIndex":"0x1","type":"mined"}],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"}"#;
 
 		let receipt = Receipt {
 			transaction_hash: Some(H256::zero()),
@@ -109,7 +114,8 @@ mod tests {
 				transaction_index: Some(U256::zero()),
 				log_index: Some(U256::one()),
 				log_type: "mined".to_owned(),
-			}]
+			}],
+			logs_bloom: H2048::default(),
 		};
 
 		let serialized = serde_json::to_string(&receipt).unwrap();
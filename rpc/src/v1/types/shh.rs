@@ -0,0 +1,116 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+use std::collections::BTreeMap;
+use util::numbers::*;
+use whisper::{Message, abridge_topic};
+use v1::types::{Bytes, OptionalValue};
+
+/// Whisper message to post.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ShhPostRequest {
+	/// Identity of the sender, the message is signed if present
+	pub from: Option<H512>,
+	/// Recipient, the message is encrypted if present
+	pub to: Option<H512>,
+	/// Topics
+	pub topics: Vec<Bytes>,
+	/// Message payload
+	pub payload: Bytes,
+	/// Time to live in seconds
+	pub ttl: U256,
+}
+
+/// Whisper message filter.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ShhFilterRequest {
+	/// Only messages sent to this identity
+	pub to: Option<H512>,
+	/// Only messages carrying all of these topics
+	pub topics: Option<Vec<Bytes>>,
+}
+
+/// Whisper message received.
+#[derive(Debug, Serialize)]
+pub struct ShhMessage {
+	/// Envelope hash
+	pub hash: H256,
+	/// Sender
+	pub from: OptionalValue<H512>,
+	/// Recipient
+	pub to: OptionalValue<H512>,
+	/// Expiry time
+	pub expiry: U256,
+	/// Time to live
+	pub ttl: U256,
+	/// Abridged topics
+	pub topics: Vec<Bytes>,
+	/// Payload
+	pub payload: Bytes,
+	/// Proof-of-work bits
+	#[serde(rename="workProved")]
+	pub work_proved: U256,
+}
+
+impl From<Message> for ShhMessage {
+	fn from(m: Message) -> Self {
+		ShhMessage {
+			hash: m.hash,
+			from: m.from.map_or(OptionalValue::Null, OptionalValue::Value),
+			to: m.to.map_or(OptionalValue::Null, OptionalValue::Value),
+			expiry: U256::from(m.expiry),
+			ttl: U256::from(m.ttl),
+			topics: m.topics.into_iter().map(|t| Bytes::new(t.to_vec())).collect(),
+			payload: Bytes::new(m.payload),
+			work_proved: U256::from(m.work),
+		}
+	}
+}
+
+/// Converts full topics to abridged ones.
+pub fn abridge_topics(topics: &[Bytes]) -> Vec<H32> {
+	topics.iter().map(|t| abridge_topic(&t.0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::*;
+	use v1::types::Bytes;
+	use super::{ShhPostRequest, ShhFilterRequest};
+
+	#[test]
+	fn post_request_deserialization() {
+		let s = r#"{"topics":["0x6d796d657373616765"],"payload":"0x7b2274797065223a226d657373616765227d","ttl":"0x64","priority":"0x64"}"#;
+		let deserialized: ShhPostRequest = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, ShhPostRequest {
+			from: None,
+			to: None,
+			topics: vec![Bytes::new(b"mymessage".to_vec())],
+			payload: Bytes::new(br#"{"type":"message"}"#.to_vec()),
+			ttl: U256::from(100),
+		});
+	}
+
+	#[test]
+	fn filter_request_deserialization() {
+		let s = r#"{"topics":["0x12"]}"#;
+		let deserialized: ShhFilterRequest = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, ShhFilterRequest {
+			to: None,
+			topics: Some(vec![Bytes::new(vec![0x12])]),
+		});
+	}
+}
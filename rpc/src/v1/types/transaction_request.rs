@@ -49,6 +49,27 @@ pub struct TransactionConfirmation {
 	pub transaction: TransactionRequest,
 }
 
+/// Request waiting in the signer queue, as listed to external signers.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ConfirmationRequest {
+	/// Id of this confirmation
+	pub id: U256,
+	/// Kind of the request
+	pub kind: String,
+	/// Decoded request payload
+	pub payload: TransactionRequest,
+}
+
+impl From<TransactionConfirmation> for ConfirmationRequest {
+	fn from(c: TransactionConfirmation) -> Self {
+		ConfirmationRequest {
+			id: c.id,
+			kind: "sendTransaction".into(),
+			payload: c.transaction,
+		}
+	}
+}
+
 /// Possible modifications to the confirmed transaction sent by `SignerUI`
 #[derive(Debug, PartialEq, Deserialize)]
 pub struct TransactionModification {
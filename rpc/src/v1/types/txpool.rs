@@ -0,0 +1,198 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use util::numbers::*;
+use ethcore::transaction::SignedTransaction;
+use v1::types::Transaction;
+
+/// Transactions grouped by sender address and then by nonce (both as strings).
+pub type TransactionsBySender = BTreeMap<String, BTreeMap<String, Transaction>>;
+
+/// Transaction pool status (Geth `txpool_status` compatible)
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TxPoolStatus {
+	/// Number of transactions ready to be included in a block
+	pub pending: U256,
+	/// Number of transactions waiting for a nonce gap to be filled
+	pub queued: U256,
+}
+
+/// Transaction pool content (Geth `txpool_content` compatible)
+#[derive(Debug, Serialize)]
+pub struct TxPoolContent {
+	/// Transactions ready to be included in a block
+	pub pending: TransactionsBySender,
+	/// Transactions waiting for a nonce gap to be filled
+	pub queued: TransactionsBySender,
+}
+
+impl TxPoolContent {
+	/// Creates pool content out of pending and queued transactions.
+	pub fn new(pending: Vec<SignedTransaction>, queued: Vec<SignedTransaction>) -> Self {
+		TxPoolContent {
+			pending: transactions_by_sender(pending),
+			queued: transactions_by_sender(queued),
+		}
+	}
+}
+
+/// Number of transactions and the gas they use within a range of gas prices.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct GasPriceBucket {
+	/// Lowest gas price in the bucket
+	#[serde(rename="minGasPrice")]
+	pub min_gas_price: U256,
+	/// Gas price the next bucket starts at. `None` for the last bucket.
+	#[serde(rename="maxGasPrice")]
+	pub max_gas_price: Option<U256>,
+	/// Number of transactions in the bucket
+	pub count: U256,
+	/// Sum of gas limits of transactions in the bucket
+	#[serde(rename="totalGas")]
+	pub total_gas: U256,
+}
+
+/// Distributes transactions into buckets starting at given gas prices, which must be strictly ascending.
+/// Transactions priced below the first boundary are counted in an additional bucket starting at zero.
+pub fn gas_price_buckets(transactions: &[SignedTransaction], boundaries: &[U256]) -> Vec<GasPriceBucket> {
+	let mut starts = Vec::with_capacity(boundaries.len() + 1);
+	if boundaries.first() != Some(&U256::zero()) {
+		starts.push(U256::zero());
+	}
+	starts.extend_from_slice(boundaries);
+
+	let mut buckets = starts.iter().enumerate().map(|(i, start)| GasPriceBucket {
+		min_gas_price: start.clone(),
+		max_gas_price: starts.get(i + 1).cloned(),
+		count: U256::zero(),
+		total_gas: U256::zero(),
+	}).collect::<Vec<_>>();
+	for t in transactions {
+		// `starts` begins with zero, so there is always a bucket at or below the price
+		let index = match starts.binary_search(&t.gas_price) {
+			Ok(index) => index,
+			Err(index) => index - 1,
+		};
+		let bucket = &mut buckets[index];
+		bucket.count = bucket.count + U256::one();
+		bucket.total_gas = bucket.total_gas + t.gas;
+	}
+	buckets
+}
+
+/// Bucket boundaries at powers of two, covering gas prices of given transactions.
+pub fn logarithmic_gas_price_boundaries(transactions: &[SignedTransaction]) -> Vec<U256> {
+	let min = transactions.iter().map(|t| t.gas_price).min();
+	let max = transactions.iter().map(|t| t.gas_price).max();
+	let mut boundaries = Vec::new();
+	if let (Some(min), Some(max)) = (min, max) {
+		let mut boundary = match min.bits() {
+			0 => U256::one(),
+			bits => U256::one() << (bits - 1),
+		};
+		while boundary <= max {
+			boundaries.push(boundary);
+			if boundary.bits() == 256 {
+				break;
+			}
+			boundary = boundary << 1;
+		}
+	}
+	boundaries
+}
+
+/// Groups transactions by sender and nonce.
+pub fn transactions_by_sender(transactions: Vec<SignedTransaction>) -> TransactionsBySender {
+	let mut grouped = TransactionsBySender::new();
+	for t in transactions {
+		let sender = match t.sender() {
+			Ok(sender) => sender,
+			Err(_) => continue,
+		};
+		grouped.entry(format!("0x{:?}", sender))
+			.or_insert_with(BTreeMap::new)
+			.insert(format!("{}", t.nonce), Transaction::from(t));
+	}
+	grouped
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::*;
+	use ethcore::transaction::{Transaction, SignedTransaction, Action};
+	use super::{TxPoolStatus, gas_price_buckets, logarithmic_gas_price_boundaries};
+
+	fn transaction(gas_price: u64, gas: u64) -> SignedTransaction {
+		Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(gas),
+			gas_price: U256::from(gas_price),
+			nonce: U256::zero(),
+		}.fake_sign(Address::default())
+	}
+
+	#[test]
+	fn txpool_status_serialization() {
+		let status = TxPoolStatus {
+			pending: U256::from(10),
+			queued: U256::from(7),
+		};
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"pending":"0xa","queued":"0x7"}"#);
+	}
+
+	#[test]
+	fn gas_price_buckets_with_given_boundaries() {
+		let transactions = vec![transaction(5, 21_000), transaction(10, 50_000), transaction(15, 21_000), transaction(40, 100_000)];
+		let buckets = gas_price_buckets(&transactions, &[U256::from(10), U256::from(20)]);
+
+		assert_eq!(buckets.len(), 3);
+		assert_eq!(buckets[0].min_gas_price, U256::zero());
+		assert_eq!(buckets[0].max_gas_price, Some(U256::from(10)));
+		assert_eq!(buckets[0].count, U256::from(1));
+		assert_eq!(buckets[0].total_gas, U256::from(21_000));
+		assert_eq!(buckets[1].count, U256::from(2));
+		assert_eq!(buckets[1].total_gas, U256::from(71_000));
+		assert_eq!(buckets[2].min_gas_price, U256::from(20));
+		assert_eq!(buckets[2].max_gas_price, None);
+		assert_eq!(buckets[2].count, U256::from(1));
+		assert_eq!(buckets[2].total_gas, U256::from(100_000));
+	}
+
+	#[test]
+	fn logarithmic_boundaries_cover_all_prices() {
+		let transactions = vec![transaction(5, 21_000), transaction(40, 21_000)];
+		let boundaries = logarithmic_gas_price_boundaries(&transactions);
+		assert_eq!(boundaries, vec![U256::from(4), U256::from(8), U256::from(16), U256::from(32)]);
+		assert!(logarithmic_gas_price_boundaries(&[]).is_empty());
+
+		let buckets = gas_price_buckets(&transactions, &boundaries);
+		assert_eq!(buckets.len(), 5);
+		assert_eq!(buckets[1].count, U256::from(1));
+		assert_eq!(buckets[4].count, U256::from(1));
+	}
+
+	#[test]
+	fn gas_price_bucket_serialization() {
+		let buckets = gas_price_buckets(&[transaction(5, 21_000)], &[U256::from(10)]);
+		let serialized = serde_json::to_string(&buckets).unwrap();
+		assert_eq!(serialized, r#"[{"minGasPrice":"0x0","maxGasPrice":"0xa","count":"0x1","totalGas":"0x5208"},{"minGasPrice":"0xa","maxGasPrice":null,"count":"0x0","totalGas":"0x0"}]"#);
+	}
+}
@@ -26,7 +26,7 @@ use std::sync::Arc;
 use std::net::SocketAddr;
 use util::panics::{PanicHandler, OnPanicListener, MayPanic};
 use jsonrpc_core::{IoHandler, IoDelegate};
-use rpc::{Extendable, ConfirmationsQueue};
+use rpc::{Extendable, ConfirmationsQueue, AccessControlAllowOrigin, cors_domains};
 
 mod session;
 
@@ -53,6 +53,7 @@ pub struct ServerBuilder {
 	queue: Arc<ConfirmationsQueue>,
 	handler: Arc<IoHandler>,
 	authcodes_path: PathBuf,
+	cors: Option<Vec<AccessControlAllowOrigin>>,
 }
 
 impl Extendable for ServerBuilder {
@@ -68,13 +69,22 @@ impl ServerBuilder {
 			queue: queue,
 			handler: Arc::new(IoHandler::new()),
 			authcodes_path: authcodes_path,
+			cors: None,
 		}
 	}
 
+	/// Allow connections from other origins than the server itself. Origins are given and
+	/// matched as for HTTP JSON-RPC CORS domains, `*` and `null` included.
+	/// Connections from other origins are refused with `403 Forbidden`.
+	pub fn with_cors(mut self, cors: &[String]) -> Self {
+		self.cors = Some(cors_domains(cors));
+		self
+	}
+
 	/// Starts a new `WebSocket` server in separate thread.
 	/// Returns a `Server` handle which closes the server when droped.
 	pub fn start(self, addr: SocketAddr) -> Result<Server, ServerError> {
-		Server::start(addr, self.handler, self.queue, self.authcodes_path)
+		Server::start(addr, self.handler, self.queue, self.authcodes_path, self.cors)
 	}
 }
 
@@ -89,7 +99,7 @@ pub struct Server {
 impl Server {
 	/// Starts a new `WebSocket` server in separate thread.
 	/// Returns a `Server` handle which closes the server when droped.
-	fn start(addr: SocketAddr, handler: Arc<IoHandler>, queue: Arc<ConfirmationsQueue>, authcodes_path: PathBuf) -> Result<Server, ServerError> {
+	fn start(addr: SocketAddr, handler: Arc<IoHandler>, queue: Arc<ConfirmationsQueue>, authcodes_path: PathBuf, cors: Option<Vec<AccessControlAllowOrigin>>) -> Result<Server, ServerError> {
 		let config = {
 			let mut config = ws::Settings::default();
 			// It's also used for handling min-sysui requests (browser can make many of them in paralel)
@@ -102,7 +112,7 @@ impl Server {
 
 		// Create WebSocket
 		let origin = format!("{}", addr);
-		let ws = try!(ws::Builder::new().with_settings(config).build(session::Factory::new(handler, origin, authcodes_path)));
+		let ws = try!(ws::Builder::new().with_settings(config).build(session::Factory::new(handler, origin, cors, authcodes_path)));
 
 		let panic_handler = PanicHandler::new_in_arc();
 		let ph = panic_handler.clone();
@@ -18,6 +18,7 @@
 
 use ws;
 use sysui;
+use rpc::{self, AccessControlAllowOrigin};
 use authcode_store::AuthCodes;
 use std::path::{PathBuf, Path};
 use std::sync::Arc;
@@ -40,6 +41,18 @@ fn origin_is_allowed(self_origin: &str, header: Option<&Vec<u8>>) -> bool {
 	}
 }
 
+/// Checks request origin. Signer UI served by the server itself is always allowed.
+/// Other origins are checked against CORS domains, if configured, the same way as for HTTP JSON-RPC.
+fn request_is_allowed(self_origin: &str, cors: Option<&[AccessControlAllowOrigin]>, origin: Option<&Vec<u8>>, host: Option<&Vec<u8>>) -> bool {
+	if origin_is_allowed(self_origin, origin) || (origin.is_none() && origin_is_allowed(self_origin, host)) {
+		return true;
+	}
+	match (cors, origin.and_then(|o| String::from_utf8(o.clone()).ok())) {
+		(Some(domains), Some(ref origin)) => rpc::origin_is_allowed(domains, Some(origin)),
+		_ => false,
+	}
+}
+
 fn auth_is_valid(codes: &Path, protocols: ws::Result<Vec<&str>>) -> bool {
 	match protocols {
 		Ok(ref protocols) if protocols.len() == 1 => {
@@ -65,6 +78,7 @@ fn auth_is_valid(codes: &Path, protocols: ws::Result<Vec<&str>>) -> bool {
 pub struct Session {
 	out: ws::Sender,
 	self_origin: String,
+	cors: Option<Vec<AccessControlAllowOrigin>>,
 	authcodes_path: PathBuf,
 	handler: Arc<IoHandler>,
 }
@@ -75,9 +89,10 @@ impl ws::Handler for Session {
 		let host = req.header("host").or_else(|| req.header("Host"));
 
 		// Check request origin and host header.
-		if !origin_is_allowed(&self.self_origin, origin) && !(origin.is_none() && origin_is_allowed(&self.self_origin, host)) {
-			warn!(target: "signer", "Blocked connection to Signer API from untrusted origin.");
-			return Ok(ws::Response::forbidden(format!("You are not allowed to access system ui. Use: http://{}", self.self_origin)));
+		if !request_is_allowed(&self.self_origin, self.cors.as_ref().map(|c| &c[..]), origin, host) {
+			let origin = origin.map_or_else(String::new, |o| String::from_utf8_lossy(o).into_owned());
+			warn!(target: "signer", "Blocked connection to Signer API from untrusted origin: {}", origin);
+			return Ok(ws::Response::forbidden(format!("Origin {} is not allowed to access system ui. Use: http://{}", origin, self.self_origin)));
 		}
 
 		// Detect if it's a websocket request.
@@ -132,14 +147,16 @@ impl ws::Handler for Session {
 pub struct Factory {
 	handler: Arc<IoHandler>,
 	self_origin: String,
+	cors: Option<Vec<AccessControlAllowOrigin>>,
 	authcodes_path: PathBuf,
 }
 
 impl Factory {
-	pub fn new(handler: Arc<IoHandler>, self_origin: String, authcodes_path: PathBuf) -> Self {
+	pub fn new(handler: Arc<IoHandler>, self_origin: String, cors: Option<Vec<AccessControlAllowOrigin>>, authcodes_path: PathBuf) -> Self {
 		Factory {
 			handler: handler,
 			self_origin: self_origin,
+			cors: cors,
 			authcodes_path: authcodes_path,
 		}
 	}
@@ -153,7 +170,59 @@ impl ws::Factory for Factory {
 			out: sender,
 			handler: self.handler.clone(),
 			self_origin: self.self_origin.clone(),
+			cors: self.cors.clone(),
 			authcodes_path: self.authcodes_path.clone(),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use rpc::cors_domains;
+	use super::request_is_allowed;
+
+	const SELF_ORIGIN: &'static str = "127.0.0.1:8180";
+
+	fn header(value: &str) -> Option<Vec<u8>> {
+		Some(value.as_bytes().to_vec())
+	}
+
+	fn domains(domains: &[&str]) -> Vec<String> {
+		domains.iter().map(|d| d.to_string()).collect()
+	}
+
+	#[test]
+	fn should_allow_own_origin_without_cors() {
+		let host = header(SELF_ORIGIN);
+		assert!(request_is_allowed(SELF_ORIGIN, None, header("http://127.0.0.1:8180").as_ref(), host.as_ref()));
+		assert!(request_is_allowed(SELF_ORIGIN, None, None, host.as_ref()));
+		assert!(!request_is_allowed(SELF_ORIGIN, None, header("http://parity.io").as_ref(), host.as_ref()));
+	}
+
+	#[test]
+	fn should_check_origin_against_cors_domains() {
+		let cors = cors_domains(&domains(&["http://parity.io"]));
+		let host = header(SELF_ORIGIN);
+		assert!(request_is_allowed(SELF_ORIGIN, Some(&cors), header("http://parity.io").as_ref(), host.as_ref()));
+		assert!(!request_is_allowed(SELF_ORIGIN, Some(&cors), header("http://evil.com").as_ref(), host.as_ref()));
+		assert!(!request_is_allowed(SELF_ORIGIN, Some(&cors), header("null").as_ref(), host.as_ref()));
+		// own ui is still allowed
+		assert!(request_is_allowed(SELF_ORIGIN, Some(&cors), header("http://127.0.0.1:8180").as_ref(), host.as_ref()));
+	}
+
+	#[test]
+	fn should_allow_any_origin_with_wildcard() {
+		let cors = cors_domains(&domains(&["*"]));
+		let host = header(SELF_ORIGIN);
+		assert!(request_is_allowed(SELF_ORIGIN, Some(&cors), header("http://evil.com").as_ref(), host.as_ref()));
+		assert!(request_is_allowed(SELF_ORIGIN, Some(&cors), header("null").as_ref(), host.as_ref()));
+	}
+
+	#[test]
+	fn should_allow_null_origin_when_listed() {
+		let cors = cors_domains(&domains(&["null"]));
+		let host = header(SELF_ORIGIN);
+		assert!(request_is_allowed(SELF_ORIGIN, Some(&cors), header("null").as_ref(), host.as_ref()));
+		assert!(!request_is_allowed(SELF_ORIGIN, Some(&cors), header("http://parity.io").as_ref(), host.as_ref()));
+	}
+}
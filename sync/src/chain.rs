@@ -277,7 +277,7 @@ impl ChainSync {
 	pub fn status(&self) -> SyncStatus {
 		SyncStatus {
 			state: self.state.clone(),
-			protocol_version: 63,
+			protocol_version: PROTOCOL_VERSION,
 			network_id: self.network_id,
 			start_block_number: self.starting_block,
 			last_imported_block_number: Some(self.last_imported_block),
@@ -41,13 +41,13 @@
 //! use ethcore::miner::Miner;
 //!
 //! fn main() {
-//! 	let mut service = NetworkService::new(NetworkConfiguration::new()).unwrap();
+//! 	let service = Arc::new(NetworkService::new(NetworkConfiguration::new()).unwrap());
 //! 	service.start().unwrap();
 //! 	let dir = env::temp_dir();
 //! 	let client = Client::new(ClientConfig::default(), ethereum::new_frontier(true), &dir, Arc::new(Miner::default()), service.io().channel()).unwrap();
 //! 	let miner = Miner::new(false, ethereum::new_frontier(true));
 //! 	let sync = EthSync::new(SyncConfig::default(), client);
-//! 	EthSync::register(&mut service, sync);
+//! 	EthSync::register(&service, sync);
 //! }
 //! ```
 
@@ -71,7 +71,7 @@ use ethcore::client::Client;
 use ethcore::service::{SyncMessage, NetSyncMessage};
 use io::NetSyncIo;
 use util::io::IoChannel;
-use util::{NetworkIoMessage, NetworkError};
+use util::{NetworkIoMessage, NetworkError, UtilError};
 use chain::ChainSync;
 
 mod chain;
@@ -106,6 +106,10 @@ pub trait SyncProvider: Send + Sync {
 	fn start_network(&self);
 	/// Stop the network
 	fn stop_network(&self);
+	/// Returns true if the network is accepting incoming connections
+	fn is_listening(&self) -> bool;
+	/// Disconnect a peer given by its node id or enode URL. Returns true if the peer was connected.
+	fn remove_peer(&self, peer: &str) -> Result<bool, UtilError>;
 }
 
 /// Ethereum network protocol handler
@@ -116,6 +120,8 @@ pub struct EthSync {
 	sync: RwLock<ChainSync>,
 	/// IO communication chnnel.
 	io_channel: RwLock<IoChannel<NetSyncMessage>>,
+	/// Network service the protocol is registered with.
+	network: RwLock<Option<Weak<NetworkService<SyncMessage>>>>,
 }
 
 pub use self::chain::{SyncStatus, SyncState};
@@ -128,11 +134,13 @@ impl EthSync {
 			chain: chain,
 			sync: RwLock::new(sync),
 			io_channel: RwLock::new(IoChannel::disconnected()),
+			network: RwLock::new(None),
 		})
 	}
 
 	/// Register protocol with the network service
-	pub fn register(service: &NetworkService<SyncMessage>, sync: Arc<EthSync>) -> Result<(), NetworkError> {
+	pub fn register(service: &Arc<NetworkService<SyncMessage>>, sync: Arc<EthSync>) -> Result<(), NetworkError> {
+		*sync.network.write().unwrap() = Some(Arc::downgrade(service));
 		service.register_protocol(sync.clone(), "eth", &[62u8, 63u8])
 	}
 
@@ -160,6 +168,19 @@ impl SyncProvider for EthSync {
 	fn stop_network(&self) {
 		self.io_channel.read().unwrap().send(NetworkIoMessage::User(SyncMessage::StopNetwork)).expect("Error sending IO notification");
 	}
+
+	fn is_listening(&self) -> bool {
+		self.network.read().unwrap().as_ref()
+			.and_then(|network| network.upgrade())
+			.map_or(false, |network| network.is_listening())
+	}
+
+	fn remove_peer(&self, peer: &str) -> Result<bool, UtilError> {
+		match self.network.read().unwrap().as_ref().and_then(|network| network.upgrade()) {
+			Some(network) => network.remove_peer(peer),
+			None => Ok(false),
+		}
+	}
 }
 
 impl NetworkProtocolHandler<SyncMessage> for EthSync {
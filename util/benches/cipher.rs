@@ -0,0 +1,68 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! benchmarking for connection ciphers
+//! should be started with:
+//! ```bash
+//! multirust run nightly cargo bench
+//! ```
+
+#![feature(test)]
+
+extern crate test;
+extern crate ethcore_util;
+
+use test::Bencher;
+use ethcore_util::network::CipherBackend;
+
+fn bench_frame_cipher(b: &mut Bencher, backend: CipherBackend) {
+	let mut cipher = backend.frame_cipher(&[7u8; 32], &[0u8; 16]);
+	let input = vec![0u8; 16 * 1024];
+	let mut output = vec![0u8; 16 * 1024];
+	b.bytes = input.len() as u64;
+	b.iter(|| {
+		cipher.process(&input, &mut output);
+	});
+}
+
+fn bench_mac_cipher(b: &mut Bencher, backend: CipherBackend) {
+	let cipher = backend.mac_cipher(&[7u8; 32]);
+	let input = [0u8; 16];
+	let mut output = [0u8; 16];
+	b.iter(|| {
+		cipher.encrypt_block(&input, &mut output);
+	});
+}
+
+#[bench]
+fn bench_frame_cipher_safe(b: &mut Bencher) {
+	bench_frame_cipher(b, CipherBackend::Safe);
+}
+
+#[bench]
+fn bench_frame_cipher_aesni(b: &mut Bencher) {
+	bench_frame_cipher(b, CipherBackend::AesNi);
+}
+
+#[bench]
+fn bench_mac_cipher_safe(b: &mut Bencher) {
+	bench_mac_cipher(b, CipherBackend::Safe);
+}
+
+#[bench]
+fn bench_mac_cipher_aesni(b: &mut Bencher) {
+	bench_mac_cipher(b, CipherBackend::AesNi);
+}
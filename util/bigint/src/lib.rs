@@ -19,5 +19,7 @@
 extern crate rustc_serialize;
 extern crate serde;
 #[macro_use] extern crate heapsize;
+#[cfg(test)]
+extern crate serde_json;
 
 pub mod uint;
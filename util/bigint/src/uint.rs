@@ -788,11 +788,13 @@ macro_rules! construct_uint {
 		impl serde::Serialize for $name {
 			fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
 			where S: serde::Serializer {
+				// QUANTITY encoding: no leading zeros, zero is `0x0`
 				let mut hex = "0x".to_owned();
 				let mut bytes = [0u8; 8 * $n_words];
 				self.to_raw_bytes(&mut bytes);
 				let len = cmp::max((self.bits() + 7) / 8, 1);
-				hex.push_str(bytes[bytes.len() - len..].to_hex().as_ref());
+				let digits = bytes[bytes.len() - len..].to_hex();
+				hex.push_str(if digits.starts_with('0') { &digits[1..] } else { &digits });
 				serializer.serialize_str(hex.as_ref())
 			}
 		}
@@ -1443,6 +1445,19 @@ mod tests {
 	use uint::{Uint, U128, U256, U512};
 	use std::str::FromStr;
 	use super::FromDecStrErr;
+	use serde_json;
+
+	#[test]
+	fn uint256_serializes_as_quantity() {
+		assert_eq!(serde_json::to_string(&U256::zero()).unwrap(), r#""0x0""#);
+		assert_eq!(serde_json::to_string(&U256::from(10)).unwrap(), r#""0xa""#);
+		assert_eq!(serde_json::to_string(&U256::from(0x400)).unwrap(), r#""0x400""#);
+		let big = U256::from_str("0fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+		assert_eq!(serde_json::to_string(&big).unwrap(), r#""0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff""#);
+
+		let deserialized: U256 = serde_json::from_str(r#""0xa""#).unwrap();
+		assert_eq!(deserialized, U256::from(10));
+	}
 
 	#[test]
 	pub fn uint256_from() {
@@ -0,0 +1,162 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! AES-256 ciphers used by encrypted connections.
+
+use rcrypto::aessafe::AesSafe256Encryptor;
+use rcrypto::blockmodes::CtrMode;
+use rcrypto::symmetriccipher::{BlockEncryptor, SynchronousStreamCipher};
+
+/// AES-256 in CTR mode used to encrypt and decrypt frames.
+pub trait FrameCipher: Send {
+	/// Encrypt or decrypt `input` into `output`. Both must have the same length.
+	fn process(&mut self, input: &[u8], output: &mut [u8]);
+}
+
+/// AES-256 single block encryption used to update frame MACs.
+pub trait MacCipher: Send {
+	/// Encrypt a 16 byte block.
+	fn encrypt_block(&self, input: &[u8], output: &mut [u8]);
+}
+
+/// AES implementation used by encrypted connections. Both produce the same output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherBackend {
+	/// Constant-time software implementation.
+	Safe,
+	/// Hardware accelerated implementation. Falls back to `Safe` on CPUs without AES-NI.
+	AesNi,
+}
+
+impl Default for CipherBackend {
+	fn default() -> Self {
+		CipherBackend::Safe
+	}
+}
+
+impl CipherBackend {
+	/// Returns the backend that is actually used on this CPU.
+	pub fn effective(&self) -> CipherBackend {
+		match *self {
+			CipherBackend::AesNi if aesni::supported() => CipherBackend::AesNi,
+			_ => CipherBackend::Safe,
+		}
+	}
+
+	/// Create a CTR mode cipher with given key and initial counter.
+	pub fn frame_cipher(&self, key: &[u8], iv: &[u8]) -> Box<FrameCipher> {
+		match self.effective() {
+			CipherBackend::AesNi => aesni::frame_cipher(key, iv),
+			CipherBackend::Safe => Box::new(CtrMode::new(AesSafe256Encryptor::new(key), iv.to_vec())),
+		}
+	}
+
+	/// Create a block cipher with given key.
+	pub fn mac_cipher(&self, key: &[u8]) -> Box<MacCipher> {
+		match self.effective() {
+			CipherBackend::AesNi => aesni::mac_cipher(key),
+			CipherBackend::Safe => Box::new(AesSafe256Encryptor::new(key)),
+		}
+	}
+}
+
+impl<A> FrameCipher for CtrMode<A> where A: BlockEncryptor + Send {
+	fn process(&mut self, input: &[u8], output: &mut [u8]) {
+		SynchronousStreamCipher::process(self, input, output);
+	}
+}
+
+impl MacCipher for AesSafe256Encryptor {
+	fn encrypt_block(&self, input: &[u8], output: &mut [u8]) {
+		BlockEncryptor::encrypt_block(self, input, output);
+	}
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod aesni {
+	use rcrypto::aes::KeySize;
+	use rcrypto::aesni::AesNiEncryptor;
+	use rcrypto::blockmodes::CtrMode;
+	use rcrypto::symmetriccipher::BlockEncryptor;
+	use rcrypto::util::supports_aesni;
+	use super::{FrameCipher, MacCipher};
+
+	pub fn supported() -> bool {
+		supports_aesni()
+	}
+
+	pub fn frame_cipher(key: &[u8], iv: &[u8]) -> Box<FrameCipher> {
+		Box::new(CtrMode::new(AesNiEncryptor::new(KeySize::KeySize256, key), iv.to_vec()))
+	}
+
+	pub fn mac_cipher(key: &[u8]) -> Box<MacCipher> {
+		Box::new(AesNiEncryptor::new(KeySize::KeySize256, key))
+	}
+
+	impl MacCipher for AesNiEncryptor {
+		fn encrypt_block(&self, input: &[u8], output: &mut [u8]) {
+			BlockEncryptor::encrypt_block(self, input, output);
+		}
+	}
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+mod aesni {
+	use super::{FrameCipher, MacCipher};
+
+	pub fn supported() -> bool {
+		false
+	}
+
+	pub fn frame_cipher(_key: &[u8], _iv: &[u8]) -> Box<FrameCipher> {
+		unreachable!("AES-NI is never reported as supported on this architecture; qed")
+	}
+
+	pub fn mac_cipher(_key: &[u8]) -> Box<MacCipher> {
+		unreachable!("AES-NI is never reported as supported on this architecture; qed")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CipherBackend;
+
+	#[test]
+	fn backends_produce_identical_output() {
+		let key = [7u8; 32];
+		let iv = [0u8; 16];
+		let input = (0..100u8).collect::<Vec<_>>();
+
+		let mut safe_out = vec![0u8; input.len()];
+		let mut fast_out = vec![0u8; input.len()];
+		CipherBackend::Safe.frame_cipher(&key, &iv).process(&input, &mut safe_out);
+		CipherBackend::AesNi.frame_cipher(&key, &iv).process(&input, &mut fast_out);
+		assert_eq!(safe_out, fast_out);
+		assert!(safe_out != input);
+
+		let mut safe_block = [0u8; 16];
+		let mut fast_block = [0u8; 16];
+		CipherBackend::Safe.mac_cipher(&key).encrypt_block(&input[0..16], &mut safe_block);
+		CipherBackend::AesNi.mac_cipher(&key).encrypt_block(&input[0..16], &mut fast_block);
+		assert_eq!(safe_block, fast_block);
+	}
+
+	#[test]
+	fn safe_backend_is_default() {
+		assert_eq!(CipherBackend::default(), CipherBackend::Safe);
+		assert_eq!(CipherBackend::Safe.effective(), CipherBackend::Safe);
+	}
+}
@@ -16,7 +16,7 @@
 
 use std::sync::Arc;
 use std::collections::VecDeque;
-use std::net::SocketAddr;
+use std::net::{SocketAddr, Shutdown};
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use mio::{Handler, Token, EventSet, EventLoop, PollOpt, TryRead, TryWrite};
 use mio::tcp::*;
@@ -29,16 +29,36 @@ use error::*;
 use io::{IoContext, StreamToken};
 use network::error::NetworkError;
 use network::handshake::Handshake;
+use network::node_table::NodeId;
+use network::cipher::{CipherBackend, FrameCipher, MacCipher};
 use network::stats::NetworkStats;
 use crypto;
-use rcrypto::blockmodes::*;
-use rcrypto::aessafe::*;
-use rcrypto::symmetriccipher::*;
-use rcrypto::buffer::*;
 use tiny_keccak::Keccak;
+use time;
 
 const ENCRYPTED_HEADER_LEN: usize = 32;
+const MAX_PAYLOAD_SIZE: usize = (1 << 24) - 1;
 const RECIEVE_PAYLOAD_TIMEOUT: u64 = 30000;
+/// Maximum number of bytes preallocated for the receive buffer in advance.
+const MAX_RECEIVE_PREALLOCATION: usize = 1024 * 1024;
+/// Maximum capacity of a receive buffer kept for reuse once a frame has been processed.
+/// Buffers grown by larger frames are released instead of staying allocated for the lifetime of the connection.
+const MAX_RETAINED_RECEIVE_BUFFER: usize = MAX_RECEIVE_PREALLOCATION;
+/// Maximum number of bytes held back by write coalescing. Reaching it writes the queue out right away.
+const MAX_COALESCED_BYTES: usize = 64 * 1024;
+/// Protocol version assumed for frames sent before the `Hello` exchange completes.
+const DEFAULT_FRAME_PROTOCOL_VERSION: u32 = 4;
+/// RLP encoded frame header data: `[capability-id, context-id]`, both zero.
+/// EIP-706 compresses only the payload, so the header data is the same for every protocol version.
+const FRAME_HEADER_DATA: [u8; 3] = [0xc2, 0x80, 0x80];
+
+/// Clear `buf` for reuse, releasing its allocation if the capacity exceeds `max`.
+fn reuse_buffer(buf: &mut Bytes, max: usize) {
+	buf.clear();
+	if buf.capacity() > max {
+		buf.shrink_to_fit();
+	}
+}
 
 pub trait GenericSocket : Read + Write {
 }
@@ -57,12 +77,95 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	rec_size: usize,
 	/// Send out packets FIFO
 	send_queue: VecDeque<Cursor<Bytes>>,
+	/// Bytes in `send_queue` which have not been written yet.
+	send_queue_bytes: usize,
 	/// Event flags this connection expects
 	interest: EventSet,
 	/// Shared network statistics
 	stats: Arc<NetworkStats>,
 	/// Registered flag
 	registered: AtomicBool,
+	/// Set once the event loop rejected the socket as closed. The connection should be dropped.
+	socket_closed: AtomicBool,
+	/// Time of the last write progress or of queueing data into an empty send queue.
+	last_write_ns: u64,
+	/// Events no longer requested after a half of the connection has been shut down.
+	disabled: EventSet,
+	/// How the socket is registered with the event loop.
+	poll_mode: PollMode,
+	/// Whether the last write attempt was not cut short by the socket, so data can be written
+	/// without waiting for a writable event.
+	socket_writable: bool,
+	/// Time in nanoseconds packets are held back to be written together. Zero disables coalescing.
+	coalesce_window_ns: u64,
+	/// Time when the first of the currently held back packets was sent, or zero.
+	coalesce_since_ns: u64,
+}
+
+/// How connection sockets are registered with the event loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollMode {
+	/// Edge-triggered. Readiness is reported once per change, so handlers must read until the socket would block.
+	Edge,
+	/// Edge-triggered, and disabled after each event until the socket is reregistered.
+	/// Guarantees a single event in flight per connection at the cost of a reregistration after every event.
+	Oneshot,
+	/// Level-triggered. Readiness is reported for as long as it lasts, so a handler which stops reading early
+	/// is woken up again. Costs extra wakeups for sockets that stay ready.
+	Level,
+}
+
+impl Default for PollMode {
+	fn default() -> Self {
+		PollMode::Edge
+	}
+}
+
+impl PollMode {
+	/// Event loop registration options for this mode.
+	pub fn poll_opt(&self) -> PollOpt {
+		match *self {
+			PollMode::Edge => PollOpt::edge(),
+			PollMode::Oneshot => PollOpt::edge() | PollOpt::oneshot(),
+			PollMode::Level => PollOpt::level(),
+		}
+	}
+
+	/// Check if the socket has to be reregistered after each event to receive further events.
+	pub fn needs_reregister(&self) -> bool {
+		*self == PollMode::Oneshot
+	}
+}
+
+/// Number of bytes in `buf` past its position.
+fn unwritten_len(buf: &Cursor<Bytes>) -> usize {
+	buf.get_ref().len().saturating_sub(buf.position() as usize)
+}
+
+/// Write the unwritten part of `buf` to the socket and advance its position past the written bytes.
+/// Returns the number of bytes written, or `None` if the socket would block. The count never exceeds the
+/// unwritten length, even if the socket reports more, so the position can't move past the end of the buffer.
+fn write_buf<S: Write>(socket: &mut S, buf: &mut Cursor<Bytes>) -> io::Result<Option<usize>> {
+	let len = buf.get_ref().len();
+	let position = ::std::cmp::min(buf.position() as usize, len);
+	if position == len {
+		return Ok(Some(0));
+	}
+	let written = match try!(socket.try_write(&buf.get_ref()[position..])) {
+		Some(size) => ::std::cmp::min(size, len - position),
+		None => return Ok(None),
+	};
+	buf.set_position((position + written) as u64);
+	Ok(Some(written))
+}
+
+/// Map socket errors caused by the remote peer closing the connection to specific network errors.
+fn map_io_error(e: io::Error) -> UtilError {
+	match e.kind() {
+		io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted | io::ErrorKind::BrokenPipe => From::from(NetworkError::ConnectionReset),
+		io::ErrorKind::UnexpectedEof => From::from(NetworkError::ConnectionClosed),
+		_ => From::from(e),
+	}
 }
 
 impl<Socket: GenericSocket> GenericConnection<Socket> {
@@ -72,10 +175,28 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 			warn!(target:"network", "Unexpected connection read start");
 		}
 		self.rec_size = size;
+		// Preallocate to avoid regrowing the buffer while reading a frame.
+		// Peer controls the size, so only reserve up to a limit eagerly.
+		let len = self.rec_buf.len();
+		let prealloc = ::std::cmp::min(size, MAX_RECEIVE_PREALLOCATION);
+		if prealloc > len {
+			self.rec_buf.reserve(prealloc - len);
+		}
 	}
 
 	/// Readable IO handler. Called when there is some data to be read.
-	pub fn readable(&mut self) -> io::Result<Option<Bytes>> {
+	pub fn readable(&mut self) -> Result<Option<Bytes>, UtilError> {
+		let mut buf = Bytes::new();
+		match try!(self.readable_into(&mut buf)) {
+			true => Ok(Some(buf)),
+			false => Ok(None),
+		}
+	}
+
+	/// Readable IO handler which does not allocate a new buffer per frame.
+	/// Returns `true` once a complete frame has been read. The frame is swapped into `buf`,
+	/// and the cleared previous contents of `buf` are reused for receiving the next frame.
+	pub fn readable_into(&mut self, buf: &mut Bytes) -> Result<bool, UtilError> {
 		if self.rec_size == 0 || self.rec_buf.len() >= self.rec_size {
 			warn!(target:"network", "Unexpected connection read");
 		}
@@ -88,26 +209,65 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
-						return Ok(Some(::std::mem::replace(&mut self.rec_buf, Bytes::new())))
+						buf.clear();
+						::std::mem::swap(&mut self.rec_buf, buf);
+						return Ok(true)
 					}
 					else if self.rec_buf.len() > self.rec_size {
 						warn!(target:"network", "Read past buffer {} bytes", self.rec_buf.len() - self.rec_size);
-						return Ok(Some(::std::mem::replace(&mut self.rec_buf, Bytes::new())))
+						buf.clear();
+						::std::mem::swap(&mut self.rec_buf, buf);
+						return Ok(true)
                     }
 				},
-				Ok(_) => return Ok(None),
+				Ok(_) => return Ok(false),
 				Err(e) => { 
 					debug!(target:"network", "Read error {} ({})", self.token, e);
-					return Err(e)
+					return Err(map_io_error(e))
 				}
 			}
         }
 	}	
 
-	/// Add a packet to send queue.
+	/// Send a packet. It is written right away when the socket is known to be writable and nothing is queued,
+	/// otherwise it is queued until the socket becomes writable. With a coalescing window set packets are
+	/// merged in the queue and held back until the window is over.
 	pub fn send<Message>(&mut self, io: &IoContext<Message>, data: Bytes) where Message: Send + Clone {
-		if !data.is_empty() {
-			self.send_queue.push_back(Cursor::new(data));
+		if self.disabled.is_writable() {
+			trace!(target:"network", "{}: Dropping {} bytes, write side is shut down", self.token, data.len());
+			return;
+		}
+		if !data.is_empty() && self.coalesce_window_ns != 0 {
+			if self.send_queue.is_empty() {
+				self.last_write_ns = time::precise_time_ns();
+			}
+			let held_back = self.coalesce_since_ns != 0;
+			if self.socket_writable && (self.send_queue.is_empty() || held_back) && !self.hold_back(data.len()) {
+				// Window is over: write the held back packets together with this one.
+				self.push_coalesced(data);
+				if self.write_front_now() {
+					if self.interest.is_writable() {
+						self.interest.remove(EventSet::writable());
+						io.update_registration(self.token).ok();
+					}
+					return;
+				}
+			} else {
+				self.push_coalesced(data);
+			}
+		} else if !data.is_empty() {
+			let mut buf = Cursor::new(data);
+			if self.send_queue.is_empty() {
+				self.last_write_ns = time::precise_time_ns();
+				// Skip the wait for a writable event while the socket is known to accept data.
+				if self.socket_writable {
+					self.write_now(&mut buf);
+					if (buf.position() as usize) == buf.get_ref().len() {
+						return;
+					}
+				}
+			}
+			self.enqueue(buf);
 		}
 		if !self.interest.is_writable() {
 			self.interest.insert(EventSet::writable());
@@ -115,65 +275,297 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		}
 	}
 
+	/// Check if a packet of `len` bytes should be held back to be written together with packets sent shortly after.
+	/// Starts the coalescing window if none is running.
+	fn hold_back(&mut self, len: usize) -> bool {
+		let now = time::precise_time_ns();
+		if self.coalesce_since_ns == 0 {
+			self.coalesce_since_ns = now;
+		}
+		now - self.coalesce_since_ns < self.coalesce_window_ns && self.pending_send_bytes() + len < MAX_COALESCED_BYTES
+	}
+
+	/// Append data to the last queued buffer so that it goes out in the same write.
+	fn push_coalesced(&mut self, data: Bytes) {
+		if let Some(buf) = self.send_queue.back_mut() {
+			self.send_queue_bytes += data.len();
+			buf.get_mut().extend_from_slice(&data);
+			return;
+		}
+		self.enqueue(Cursor::new(data));
+	}
+
+	/// Append a buffer to the send queue.
+	fn enqueue(&mut self, buf: Cursor<Bytes>) {
+		self.send_queue_bytes += unwritten_len(&buf);
+		self.send_queue.push_back(buf);
+	}
+
+	/// Take the front buffer off the send queue.
+	fn dequeue(&mut self) -> Option<Cursor<Bytes>> {
+		let buf = self.send_queue.pop_front();
+		if let Some(ref buf) = buf {
+			self.send_queue_bytes -= unwritten_len(buf);
+		}
+		buf
+	}
+
+	/// Write the front of the send queue without waiting for a writable event.
+	/// Returns `true` if the queue has been emptied.
+	fn write_front_now(&mut self) -> bool {
+		self.coalesce_since_ns = 0;
+		if let Some(mut buf) = self.dequeue() {
+			self.write_now(&mut buf);
+			if (buf.position() as usize) < buf.get_ref().len() {
+				self.send_queue_bytes += unwritten_len(&buf);
+				self.send_queue.push_front(buf);
+			}
+		}
+		self.send_queue.is_empty()
+	}
+
+	/// Set the time window in microseconds within which sent packets are merged and written with a single
+	/// write. Held back packets are written once the window is over, `MAX_COALESCED_BYTES` are queued,
+	/// the socket becomes writable or on `flush`, so latency sensitive packets should be followed by `flush`.
+	/// Zero, the default, disables coalescing.
+	pub fn set_coalesce_window(&mut self, window_us: u64) {
+		self.coalesce_window_ns = window_us * 1000;
+	}
+
+	/// Try to write `buf` without waiting for a writable event, advancing its position past the written part.
+	/// Write errors are left to be reported by the writable handler.
+	fn write_now(&mut self, buf: &mut Cursor<Bytes>) {
+		match write_buf(&mut self.socket, buf) {
+			Ok(Some(size)) => {
+				self.stats.inc_send(size);
+				trace!(target:"network", "{}: Wrote {} of {} bytes immediately", self.token, size, buf.get_ref().len());
+			},
+			Ok(None) => (),
+			Err(e) => debug!(target:"network", "Immediate write error {} ({})", self.token, e),
+		}
+		if (buf.position() as usize) < buf.get_ref().len() {
+			self.socket_writable = false;
+		}
+	}
+
+	/// Events to register with the event loop. Excludes events of a half which has been shut down.
+	pub fn interest(&self) -> EventSet {
+		let mut interest = self.interest;
+		interest.remove(self.disabled);
+		interest
+	}
+
+	/// Stop requesting events for the given half of the connection. Queued data is discarded
+	/// when the write half is disabled.
+	fn disable(&mut self, how: Shutdown) {
+		match how {
+			Shutdown::Read => self.disabled.insert(EventSet::readable()),
+			Shutdown::Write => self.disabled.insert(EventSet::writable()),
+			Shutdown::Both => self.disabled.insert(EventSet::readable() | EventSet::writable()),
+		}
+		if self.disabled.is_writable() {
+			self.send_queue.clear();
+			self.send_queue_bytes = 0;
+			self.interest.remove(EventSet::writable());
+		}
+	}
+
+	/// Set how the socket is registered with the event loop. Takes effect on the next registration update.
+	pub fn set_poll_mode(&mut self, mode: PollMode) {
+		self.poll_mode = mode;
+	}
+
+	/// Returns how the socket is registered with the event loop.
+	pub fn poll_mode(&self) -> PollMode {
+		self.poll_mode
+	}
+
+	/// Number of bytes of the expected frame received so far.
+	pub fn received_len(&self) -> usize {
+		self.rec_buf.len()
+	}
+
 	/// Check if this connection has data to be sent.
 	pub fn is_sending(&self) -> bool {
 		self.interest.is_writable()
 	}
 
+	/// Check if queued data has made no progress for more than `timeout_ms` milliseconds.
+	pub fn is_write_stalled(&self, timeout_ms: u64) -> bool {
+		!self.send_queue.is_empty() && time::precise_time_ns() - self.last_write_ns > timeout_ms * 1000_000
+	}
+
+	/// Number of queued bytes which have not been written to the socket yet.
+	pub fn pending_send_bytes(&self) -> usize {
+		self.send_queue_bytes
+	}
+
+	/// Reserve room for at least `additional` more packets in the send queue, e.g. for a peer
+	/// expected to receive a burst of packets. Connections start with the default queue capacity.
+	pub fn reserve_send(&mut self, additional: usize) {
+		self.send_queue.reserve(additional);
+	}
+
+	/// Number of packets in the send queue, including a partially written one.
+	/// Coalesced packets are counted as one.
+	pub fn pending_send_packets(&self) -> usize {
+		self.send_queue.len()
+	}
+
+	/// Try to write out the whole send queue right away instead of waiting for the socket to become writable.
+	/// Stops when the socket would block, leaving the remainder queued.
+	/// Returns the number of bytes still queued.
+	pub fn flush<Message>(&mut self, io: &IoContext<Message>) -> Result<usize, UtilError> where Message: Send + Clone {
+		let mut queued = self.pending_send_bytes();
+		while queued != 0 {
+			let progress = try!(self.writable_progress(io));
+			if progress.remaining == queued {
+				break;
+			}
+			queued = progress.remaining;
+		}
+		Ok(queued)
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, UtilError> where Message: Send + Clone {
+		self.writable_progress(io).map(|progress| progress.status)
+	}
+
+	/// Writable IO handler which also reports how many bytes were written by this call
+	/// and how many are still queued.
+	pub fn writable_progress<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteProgress, UtilError> where Message: Send + Clone {
+		let (status, written) = match self.send_queue.front_mut() {
+			None => {
+				self.socket_writable = true;
+				return Ok(WriteProgress::complete(0, 0));
+			},
+			Some(buf) => {
+				let unwritten = unwritten_len(buf);
+				// `write_buf` never reports more than the unwritten part, so `written <= unwritten`.
+				let written = match write_buf(&mut self.socket, buf) {
+					Ok(written) => written.unwrap_or(0),
+					Err(e) => return Err(map_io_error(e)),
+				};
+				self.stats.inc_send(written);
+				if written == unwritten {
+					trace!(target:"network", "{}: Wrote {} bytes", self.token, buf.get_ref().len());
+					(WriteStatus::Complete, written)
+				} else {
+					(WriteStatus::Ongoing, written)
+				}
+			},
+		};
+		if written != 0 {
+			self.send_queue_bytes -= written;
+			self.last_write_ns = time::precise_time_ns();
+			self.coalesce_since_ns = 0;
+		}
+		self.socket_writable = status == WriteStatus::Complete;
+		if status == WriteStatus::Complete {
+			// also drops buffers which had nothing left to write
+			self.send_queue.pop_front();
+		}
 		if self.send_queue.is_empty() {
-			return Ok(WriteStatus::Complete)
-		}
-		{
-			let buf = self.send_queue.front_mut().unwrap();
-			let send_size = buf.get_ref().len();
-			if (buf.position() as usize) >= send_size {
-				warn!(target:"net", "Unexpected connection data");
-				return Ok(WriteStatus::Complete)
-			}
-			match self.socket.try_write_buf(buf) {
-				Ok(Some(size)) if (buf.position() as usize) < send_size => {
-					self.stats.inc_send(size);
-					Ok(WriteStatus::Ongoing)
-				},
-				Ok(Some(size)) if (buf.position() as usize) == send_size => {
-					self.stats.inc_send(size);
-					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
-					Ok(WriteStatus::Complete)
-				},
-				Ok(Some(_)) => { panic!("Wrote past buffer");},
-				Ok(None) => Ok(WriteStatus::Ongoing),
-				Err(e) => try!(Err(e))
-			}
-		}.and_then(|r| {
-			if r == WriteStatus::Complete {
-				self.send_queue.pop_front();
-			}
-			if self.send_queue.is_empty() {
-				self.interest.remove(EventSet::writable());
-				try!(io.update_registration(self.token));
-			}
-			Ok(r)
+			self.interest.remove(EventSet::writable());
+			try!(io.update_registration(self.token));
+		}
+		Ok(WriteProgress {
+			status: status,
+			written: written,
+			remaining: self.pending_send_bytes(),
 		})
 	}
 }
 
+/// TCP keepalive settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeepaliveConfig {
+	/// Seconds of inactivity before the first probe is sent.
+	pub idle: u32,
+	/// Seconds between unanswered probes.
+	pub interval: u32,
+	/// Number of unanswered probes after which the connection is considered dead.
+	pub probes: u32,
+}
+
+impl Default for KeepaliveConfig {
+	fn default() -> Self {
+		KeepaliveConfig {
+			idle: 60,
+			interval: 10,
+			probes: 5,
+		}
+	}
+}
+
+/// Enable TCP keepalive on the socket. Options which can't be set on this platform are skipped with a warning.
+pub fn set_keepalive(socket: &TcpStream, config: &KeepaliveConfig) {
+	if let Err(e) = socket.set_keepalive(Some(config.idle)) {
+		warn!(target: "network", "Error enabling TCP keepalive: {:?}", e);
+		return;
+	}
+	if let Err(e) = set_keepalive_probes(socket, config.interval, config.probes) {
+		warn!(target: "network", "Error setting TCP keepalive interval and probe count: {:?}", e);
+	}
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_keepalive_probes(socket: &TcpStream, interval: u32, probes: u32) -> io::Result<()> {
+	use std::os::unix::io::AsRawFd;
+	use std::mem;
+	use libc::{self, c_int, c_void, socklen_t};
+
+	fn set_option(fd: c_int, name: c_int, value: u32) -> io::Result<()> {
+		let value = value as c_int;
+		let result = unsafe {
+			libc::setsockopt(fd, libc::IPPROTO_TCP, name, &value as *const c_int as *const c_void, mem::size_of::<c_int>() as socklen_t)
+		};
+		match result {
+			0 => Ok(()),
+			_ => Err(io::Error::last_os_error()),
+		}
+	}
+
+	let fd = socket.as_raw_fd();
+	try!(set_option(fd, libc::TCP_KEEPINTVL, interval));
+	set_option(fd, libc::TCP_KEEPCNT, probes)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_keepalive_probes(_socket: &TcpStream, _interval: u32, _probes: u32) -> io::Result<()> {
+	Err(io::Error::new(io::ErrorKind::Other, "not supported on this platform"))
+}
+
 /// Low level tcp connection
 pub type Connection = GenericConnection<TcpStream>;
 
 impl Connection {
 	/// Create a new connection with given id and socket.
 	pub fn new(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>) -> Connection {
+		Connection::with_interest(token, socket, stats, EventSet::hup() | EventSet::readable())
+	}
+
+	/// Create a new connection which initially registers for given events only, e.g. `EventSet::hup()`
+	/// for a connection that only writes. Writable interest is still added when data is queued.
+	pub fn with_interest(token: StreamToken, socket: TcpStream, stats: Arc<NetworkStats>, interest: EventSet) -> Connection {
 		Connection {
 			token: token,
 			socket: socket,
 			send_queue: VecDeque::new(),
 			rec_buf: Bytes::new(),
 			rec_size: 0,
-			interest: EventSet::hup() | EventSet::readable(),
+			interest: interest,
 			stats: stats,
 			registered: AtomicBool::new(false),
+			socket_closed: AtomicBool::new(false),
+			last_write_ns: 0,
+			disabled: EventSet::none(),
+			poll_mode: PollMode::default(),
+			socket_writable: false,
+			coalesce_window_ns: 0,
+			coalesce_since_ns: 0,
+			send_queue_bytes: 0,
 		}
 	}
 
@@ -182,6 +574,11 @@ impl Connection {
 		self.token
 	}
 
+	/// Enable TCP keepalive probes for this connection.
+	pub fn set_keepalive(&self, config: &KeepaliveConfig) {
+		set_keepalive(&self.socket, config)
+	}
+
 	/// Get remote peer address
 	pub fn remote_addr(&self) -> io::Result<SocketAddr> {
 		self.socket.peer_addr()
@@ -203,31 +600,68 @@ impl Connection {
 			interest: EventSet::hup(),
 			stats: self.stats.clone(),
 			registered: AtomicBool::new(false),
+			socket_closed: AtomicBool::new(false),
+			last_write_ns: self.last_write_ns,
+			disabled: self.disabled,
+			poll_mode: self.poll_mode,
+			socket_writable: false,
+			coalesce_window_ns: self.coalesce_window_ns,
+			coalesce_since_ns: 0,
+			send_queue_bytes: self.send_queue_bytes,
 		})
 	}
 
+	/// Shut down the read, write or both halves of the connection. Events for a closed half are
+	/// no longer requested on the next registration update.
+	pub fn shutdown(&mut self, how: Shutdown) -> io::Result<()> {
+		try!(self.socket.shutdown(how));
+		self.disable(how);
+		Ok(())
+	}
+
 	/// Register this connection with the IO event loop.
-	pub fn register_socket<Host: Handler>(&self, reg: Token, event_loop: &mut EventLoop<Host>) -> io::Result<()> {
-		if self.registered.load(AtomicOrdering::SeqCst) {
+	/// Returns `NetworkError::SocketClosed` if the socket turns out to be closed, see `update_socket`.
+	pub fn register_socket<Host: Handler>(&self, reg: Token, event_loop: &mut EventLoop<Host>) -> Result<(), NetworkError> {
+		if self.registered.load(AtomicOrdering::SeqCst) || self.is_socket_closed() {
 			return Ok(());
         }
 		trace!(target: "network", "connection register; token={:?}", reg);
-		if let Err(e) = event_loop.register(&self.socket, reg, self.interest, PollOpt::edge() /* | PollOpt::oneshot() */) { // TODO: oneshot is broken on windows
-			trace!(target: "network", "Failed to register {:?}, {:?}", reg, e);
-		}
 		self.registered.store(true, AtomicOrdering::SeqCst);
-		Ok(())
+		match event_loop.register(&self.socket, reg, self.interest(), self.poll_mode.poll_opt()) {
+			Ok(()) => Ok(()),
+			Err(e) => self.registration_failed(reg, e),
+		}
 	}
 
 	/// Update connection registration. Should be called at the end of the IO handler.
-	pub fn update_socket<Host: Handler>(&self, reg: Token, event_loop: &mut EventLoop<Host>) -> io::Result<()> {
+	/// Returns `NetworkError::SocketClosed` once if the socket is closed or no longer known to the event loop.
+	/// The connection should be dropped then; further calls do nothing.
+	pub fn update_socket<Host: Handler>(&self, reg: Token, event_loop: &mut EventLoop<Host>) -> Result<(), NetworkError> {
 		trace!(target: "network", "connection reregister; token={:?}", reg);
-		if !self.registered.load(AtomicOrdering::SeqCst) {
+		if self.is_socket_closed() {
+			Ok(())
+		} else if !self.registered.load(AtomicOrdering::SeqCst) {
 			self.register_socket(reg, event_loop)
         } else {
-			event_loop.reregister(&self.socket, reg, self.interest, PollOpt::edge() /* | PollOpt::oneshot() */ ).unwrap_or_else(|e| {  // TODO: oneshot is broken on windows
-				trace!(target: "network", "Failed to reregister {:?}, {:?}", reg, e);
-			});
+			match event_loop.reregister(&self.socket, reg, self.interest(), self.poll_mode.poll_opt()) {
+				Ok(()) => Ok(()),
+				Err(e) => self.registration_failed(reg, e),
+			}
+		}
+	}
+
+	/// Check if the event loop rejected the socket as closed.
+	pub fn is_socket_closed(&self) -> bool {
+		self.socket_closed.load(AtomicOrdering::SeqCst)
+	}
+
+	fn registration_failed(&self, reg: Token, e: io::Error) -> Result<(), NetworkError> {
+		if is_closed_socket_error(&e) {
+			debug!(target: "network", "Socket closed {:?}, {:?}", reg, e);
+			self.socket_closed.store(true, AtomicOrdering::SeqCst);
+			Err(NetworkError::SocketClosed)
+		} else {
+			trace!(target: "network", "Failed to register {:?}, {:?}", reg, e);
 			Ok(())
 		}
 	}
@@ -240,8 +674,17 @@ impl Connection {
 	}
 }
 
+/// Check if a registration error means the descriptor was closed. Closed descriptors are removed from
+/// the poll set, so updating their registration fails with `ENOENT` or `EBADF`.
+fn is_closed_socket_error(e: &io::Error) -> bool {
+	match e.raw_os_error() {
+		Some(code) => code == ::libc::EBADF || code == ::libc::ENOENT,
+		None => false,
+	}
+}
+
 /// Connection write status.
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WriteStatus {
 	/// Some data is still pending for current packet
 	Ongoing,
@@ -249,12 +692,314 @@ pub enum WriteStatus {
 	Complete
 }
 
+/// Result of a single write to the socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteProgress {
+	/// Whether the current packet has been sent completely.
+	pub status: WriteStatus,
+	/// Bytes written by this call.
+	pub written: usize,
+	/// Bytes still waiting in the send queue.
+	pub remaining: usize,
+}
+
+impl WriteProgress {
+	fn complete(written: usize, remaining: usize) -> WriteProgress {
+		WriteProgress {
+			status: WriteStatus::Complete,
+			written: written,
+			remaining: remaining,
+		}
+	}
+
+	/// Check if the current packet has been sent completely.
+	pub fn is_complete(&self) -> bool {
+		self.status == WriteStatus::Complete
+	}
+}
+
+/// Notable events on an encrypted connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionEvent {
+	/// Frame failed authentication.
+	AuthFailure,
+	/// Frame exceeded maximum size.
+	FrameTooLarge,
+	/// Peer violated the protocol.
+	ProtocolError,
+	/// Packet was successfully received.
+	PacketReceived,
+	/// Peer reset or closed the connection.
+	Closed,
+}
+
+impl ConnectionEvent {
+	/// Classify an error returned while reading a frame.
+	pub fn from_error(error: &UtilError) -> ConnectionEvent {
+		match *error {
+			UtilError::Network(NetworkError::Auth) |
+			UtilError::Network(NetworkError::AuthMacMismatch) |
+			UtilError::Network(NetworkError::EcdhFailed) => ConnectionEvent::AuthFailure,
+			UtilError::Network(NetworkError::FrameTooLarge) => ConnectionEvent::FrameTooLarge,
+			UtilError::Network(NetworkError::ConnectionReset) |
+			UtilError::Network(NetworkError::ConnectionClosed) => ConnectionEvent::Closed,
+			_ => ConnectionEvent::ProtocolError,
+		}
+	}
+}
+
+/// Observer of connection events. Can be used to keep peer reputation.
+/// Called from within the IO handler so implementations should be cheap.
+pub trait ConnectionObserver: Send + Sync {
+	/// Called when an event happens on the connection with given token.
+	fn on_event(&self, token: StreamToken, event: ConnectionEvent);
+}
+
+/// First message id assigned to negotiated capabilities.
+pub const PACKET_USER: u8 = 0x10;
+/// Last message id that can be assigned to capabilities.
+pub const PACKET_LAST: u8 = 0x7f;
+
+/// Message id of a devp2p packet. Raw ids are the RLP-encoded first byte of the packet data,
+/// so `Hello` (id 0) is `0x80` on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketKind {
+	/// Handshake with client version and capabilities.
+	Hello,
+	/// Disconnect with a reason.
+	Disconnect,
+	/// Keepalive request.
+	Ping,
+	/// Keepalive response.
+	Pong,
+	/// Request for known peers.
+	GetPeers,
+	/// Known peers.
+	Peers,
+	/// Sender switches to fresh egress keys after this packet.
+	Rekey,
+	/// Message belonging to a negotiated capability. Holds the absolute id, capability offsets start at `PACKET_USER`.
+	Capability(u8),
+	/// Reserved or invalid id.
+	Unknown(u8),
+}
+
+impl PacketKind {
+	/// Create typed id from the raw id.
+	pub fn from_raw(id: u8) -> PacketKind {
+		match id {
+			0x80 => PacketKind::Hello,
+			0x01 => PacketKind::Disconnect,
+			0x02 => PacketKind::Ping,
+			0x03 => PacketKind::Pong,
+			0x04 => PacketKind::GetPeers,
+			0x05 => PacketKind::Peers,
+			0x06 => PacketKind::Rekey,
+			PACKET_USER ... PACKET_LAST => PacketKind::Capability(id),
+			_ => PacketKind::Unknown(id),
+		}
+	}
+
+	/// Returns the raw id.
+	pub fn to_raw(&self) -> u8 {
+		match *self {
+			PacketKind::Hello => 0x80,
+			PacketKind::Disconnect => 0x01,
+			PacketKind::Ping => 0x02,
+			PacketKind::Pong => 0x03,
+			PacketKind::GetPeers => 0x04,
+			PacketKind::Peers => 0x05,
+			PacketKind::Rekey => 0x06,
+			PacketKind::Capability(id) | PacketKind::Unknown(id) => id,
+		}
+	}
+}
+
+impl From<u8> for PacketKind {
+	fn from(id: u8) -> PacketKind {
+		PacketKind::from_raw(id)
+	}
+}
+
 /// `RLPx` packet
 pub struct Packet {
+	/// Capability id from the frame header. Base devp2p messages always use zero, as does
+	/// every message from peers that don't multiplex capabilities over the frame header.
 	pub protocol: u16,
+	/// Message id within the protocol, e.g. `0` for `Hello` or `2` for `Ping`.
+	pub packet_id: u8,
+	/// Packet data, starting with the RLP-encoded message id.
 	pub data: Bytes,
 }
 
+impl Packet {
+	/// Create packet received with given frame header capability id.
+	/// Fails if `data` doesn't start with a valid message id.
+	pub fn new(protocol: u16, data: Bytes) -> Result<Packet, NetworkError> {
+		let packet_id = try!(decode_message_id(&data));
+		Ok(Packet {
+			protocol: protocol,
+			packet_id: packet_id,
+			data: data,
+		})
+	}
+
+	/// Typed message id of this packet. `None` if the packet is empty.
+	pub fn id(&self) -> Option<PacketKind> {
+		self.data.first().map(|id| PacketKind::from_raw(*id))
+	}
+
+	/// Check if this is a base devp2p message (`Hello`, `Disconnect`, `Ping`, ...)
+	/// rather than a message of a negotiated capability.
+	pub fn is_base(&self) -> bool {
+		self.protocol == 0 && self.packet_id < PACKET_USER
+	}
+}
+
+/// Decode the RLP-encoded message id at the start of packet data.
+fn decode_message_id(data: &[u8]) -> Result<u8, NetworkError> {
+	match data.first() {
+		Some(&0x80) => Ok(0),
+		Some(&id) if id < 0x80 => Ok(id),
+		// ids above 0x7f are encoded as one byte string
+		Some(&0x81) => match data.get(1) {
+			Some(&id) if id >= 0x80 => Ok(id),
+			_ => Err(NetworkError::DecodeError),
+		},
+		_ => Err(NetworkError::DecodeError),
+	}
+}
+
+/// Thresholds after which an encrypted connection switches to fresh keys.
+/// Re-keying is only used when both peers support it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RekeyPolicy {
+	/// Bytes of payload sent with the same keys.
+	pub max_bytes: u64,
+	/// Time in milliseconds the same keys are used for.
+	pub max_time_ms: u64,
+}
+
+impl Default for RekeyPolicy {
+	fn default() -> Self {
+		RekeyPolicy {
+			max_bytes: 1024 * 1024 * 1024,
+			max_time_ms: 60 * 60 * 1000,
+		}
+	}
+}
+
+/// Reconnect backoff settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackoffConfig {
+	/// Delay before reconnecting after the first failure, in milliseconds.
+	pub base_ms: u64,
+	/// Upper bound of the delay, in milliseconds.
+	pub max_ms: u64,
+	/// Fraction of the delay randomly added or subtracted, between 0 and 1.
+	pub jitter: f64,
+	/// Time in milliseconds a connection has to stay up to reset the backoff.
+	pub stable_ms: u64,
+}
+
+impl Default for BackoffConfig {
+	fn default() -> Self {
+		BackoffConfig {
+			base_ms: 1000,
+			max_ms: 5 * 60 * 1000,
+			jitter: 0.2,
+			stable_ms: 60 * 1000,
+		}
+	}
+}
+
+/// Exponential backoff of reconnect attempts to a single peer.
+/// The delay doubles with each consecutive failure up to the configured maximum
+/// and is reset once a connection stays up for `stable_ms`.
+#[derive(Debug, Clone)]
+pub struct ReconnectBackoff {
+	config: BackoffConfig,
+	/// Failed attempts or dropped connections since the last stable connection.
+	failures: u32,
+	/// Time of the earliest allowed attempt, in nanoseconds.
+	next_attempt_ns: u64,
+	/// Time the current connection was established, in nanoseconds.
+	connected_ns: Option<u64>,
+}
+
+impl ReconnectBackoff {
+	/// Create a new backoff allowing an immediate first attempt.
+	pub fn new(config: BackoffConfig) -> ReconnectBackoff {
+		ReconnectBackoff {
+			config: config,
+			failures: 0,
+			next_attempt_ns: 0,
+			connected_ns: None,
+		}
+	}
+
+	/// Number of failures since the last stable connection.
+	pub fn failures(&self) -> u32 {
+		self.failures
+	}
+
+	/// Delay before the next attempt in milliseconds, without jitter.
+	pub fn delay_ms(&self) -> u64 {
+		if self.failures == 0 {
+			return 0;
+		}
+		let factor = 1u64.checked_shl(self.failures - 1).unwrap_or(u64::max_value());
+		::std::cmp::min(self.config.base_ms.checked_mul(factor).unwrap_or(u64::max_value()), self.config.max_ms)
+	}
+
+	/// Check if an attempt may be made at `now_ns`.
+	pub fn can_attempt(&self, now_ns: u64) -> bool {
+		now_ns >= self.next_attempt_ns
+	}
+
+	/// Record a successfully established connection.
+	pub fn note_connected(&mut self, now_ns: u64) {
+		self.connected_ns = Some(now_ns);
+	}
+
+	/// Check if the current connection has been up long enough at `now_ns` to reset the backoff.
+	pub fn is_stable(&self, now_ns: u64) -> bool {
+		self.connected_ns.map_or(false, |connected_ns| now_ns.saturating_sub(connected_ns) >= self.config.stable_ms * 1000_000)
+	}
+
+	/// Record a failed attempt or a dropped connection at `now_ns`.
+	/// Returns the delay in milliseconds before the next attempt is allowed.
+	pub fn note_disconnected(&mut self, now_ns: u64) -> u64 {
+		if self.is_stable(now_ns) {
+			self.failures = 0;
+		}
+		self.connected_ns = None;
+		self.failures = self.failures.saturating_add(1);
+		let delay = ::std::cmp::min(self.jittered(self.delay_ms()), self.config.max_ms);
+		self.next_attempt_ns = now_ns.saturating_add(delay.saturating_mul(1000_000));
+		delay
+	}
+
+	fn jittered(&self, delay: u64) -> u64 {
+		let window = (delay as f64 * self.config.jitter) as u64;
+		if window == 0 {
+			return delay;
+		}
+		delay - window + ::rand::random::<u64>() % (window * 2 + 1)
+	}
+}
+
+/// Payload of the re-key packet: packet id and an empty list.
+const REKEY_PAYLOAD: [u8; 2] = [0x06, 0xc0];
+
+/// Derive the next AES and MAC secrets (`aes || mac`) from the current ones.
+fn next_secrets(secrets: &H512) -> H512 {
+	let mut next = H512::new();
+	secrets.sha3().copy_to(&mut next[0..32]);
+	next.sha3().copy_to(&mut next[32..64]);
+	next
+}
+
 /// Encrypted connection receiving state.
 enum EncryptedConnectionState {
 	/// Reading a header.
@@ -263,17 +1008,54 @@ enum EncryptedConnectionState {
 	Payload,
 }
 
+/// Receiving status of an encrypted connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStatus {
+	/// Waiting for the next frame header.
+	AwaitingHeader,
+	/// Frame header has been received and its payload is being read.
+	ReadingPayload {
+		/// Protocol id from the frame header.
+		protocol: u16,
+		/// Payload size from the frame header, excluding padding and MAC.
+		payload_len: usize,
+		/// Payload bytes not received yet.
+		remaining: usize,
+	},
+}
+
+/// Peer information exchanged in the `Hello` packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerInfo {
+	/// Remote node id.
+	pub id: NodeId,
+	/// Peer client id string.
+	pub client_version: String,
+	/// Peer RLPx protocol version.
+	pub protocol_version: u32,
+	/// Negotiated capabilities as protocol name and version.
+	pub capabilities: Vec<(String, u8)>,
+}
+
 /// Connection implementing `RLPx` framing
 /// https://github.com/ethereum/devp2p/blob/master/rlpx.md#framing
 pub struct EncryptedConnection {
 	/// Underlying tcp connection
 	pub connection: Connection,
+	/// AES implementation used for this connection
+	cipher: CipherBackend,
 	/// Egress data encryptor
-	encoder: CtrMode<AesSafe256Encryptor>,
-	/// Ingress data decryptor
-	decoder: CtrMode<AesSafe256Encryptor>,
+	encoder: Box<FrameCipher>,
 	/// Ingress data decryptor
-	mac_encoder: EcbEncryptor<AesSafe256Encryptor, EncPadding<NoPadding>>,
+	decoder: Box<FrameCipher>,
+	/// Egress MAC encryptor
+	egress_mac_encoder: Box<MacCipher>,
+	/// Ingress MAC encryptor
+	ingress_mac_encoder: Box<MacCipher>,
+	/// Egress AES and MAC secrets, used to derive the next keys on re-key.
+	egress_secrets: H512,
+	/// Ingress AES and MAC secrets, used to derive the next keys on re-key.
+	ingress_secrets: H512,
 	/// MAC for egress data
 	egress_mac: Keccak,
 	/// MAC for ingress data
@@ -284,12 +1066,33 @@ pub struct EncryptedConnection {
 	protocol_id: u16,
 	/// Payload expected to be received for the last header.
 	payload_len: usize,
+	/// Connection events observer.
+	observer: Option<Arc<ConnectionObserver>>,
+	/// Time when the idle timer fires, in nanoseconds.
+	idle_timeout_ns: Option<u64>,
+	/// Last received encrypted frame. Kept to reuse its allocation.
+	frame_buf: Bytes,
+	/// Re-key thresholds, if negotiated with the peer.
+	rekey: Option<RekeyPolicy>,
+	/// Payload bytes sent since the last egress re-key.
+	egress_rekey_bytes: u64,
+	/// Time of the last egress re-key, in nanoseconds.
+	egress_rekey_ns: u64,
+	/// Peer information, known after the `Hello` exchange.
+	peer_info: Option<PeerInfo>,
+	/// Protocol version negotiated with the peer.
+	protocol_version: u32,
+	/// Keepalive ping interval in seconds of each negotiated capability.
+	ping_intervals: Vec<(String, u64)>,
 }
 
 impl EncryptedConnection {
-	/// Create an encrypted connection out of the handshake.
-	pub fn new(handshake: &mut Handshake) -> Result<EncryptedConnection, UtilError> {
-		let shared = try!(crypto::ecdh::agree(handshake.ecdhe.secret(), &handshake.remote_ephemeral));
+	/// Create an encrypted connection out of the handshake, using given AES implementation.
+	/// All fallible steps run before the socket is taken over, so on error the handshake
+	/// keeps its connection and no partially keyed `EncryptedConnection` is created.
+	pub fn new(handshake: &mut Handshake, cipher: CipherBackend) -> Result<EncryptedConnection, UtilError> {
+		let shared = try!(crypto::ecdh::agree(handshake.ecdhe.secret(), &handshake.remote_ephemeral).map_err(|_| NetworkError::EcdhFailed));
+		let old_connection = try!(handshake.connection.try_clone());
 		let mut nonce_material = H512::new();
 		if handshake.originated {
 			handshake.remote_nonce.copy_to(&mut nonce_material[0..32]);
@@ -305,13 +1108,16 @@ impl EncryptedConnection {
 		key_material.sha3().copy_to(&mut key_material[32..64]);
 		key_material.sha3().copy_to(&mut key_material[32..64]);
 
-		let iv = vec![0u8; 16];
-		let encoder = CtrMode::new(AesSafe256Encryptor::new(&key_material[32..64]), iv);
-		let iv = vec![0u8; 16];
-		let decoder = CtrMode::new(AesSafe256Encryptor::new(&key_material[32..64]), iv);
+		let mut secrets = H512::new();
+		secrets[0..32].clone_from_slice(&key_material[32..64]);
+		let iv = [0u8; 16];
+		let encoder = cipher.frame_cipher(&key_material[32..64], &iv);
+		let decoder = cipher.frame_cipher(&key_material[32..64], &iv);
 
 		key_material.sha3().copy_to(&mut key_material[32..64]);
-		let mac_encoder = EcbEncryptor::new(AesSafe256Encryptor::new(&key_material[32..64]), NoPadding);
+		secrets[32..64].clone_from_slice(&key_material[32..64]);
+		let egress_mac_encoder = cipher.mac_cipher(&key_material[32..64]);
+		let ingress_mac_encoder = cipher.mac_cipher(&key_material[32..64]);
 
 		let mut egress_mac = Keccak::new_keccak256();
 		let mut mac_material = &H256::from_slice(&key_material[32..64]) ^ &handshake.remote_nonce;
@@ -323,18 +1129,30 @@ impl EncryptedConnection {
 		ingress_mac.update(&mac_material);
 		ingress_mac.update(if handshake.originated { &handshake.ack_cipher } else { &handshake.auth_cipher });
 
-		let old_connection = try!(handshake.connection.try_clone());
 		let connection = ::std::mem::replace(&mut handshake.connection, old_connection);
 		let mut enc = EncryptedConnection {
 			connection: connection,
+			cipher: cipher,
 			encoder: encoder,
 			decoder: decoder,
-			mac_encoder: mac_encoder,
+			egress_mac_encoder: egress_mac_encoder,
+			ingress_mac_encoder: ingress_mac_encoder,
+			egress_secrets: secrets.clone(),
+			ingress_secrets: secrets,
 			egress_mac: egress_mac,
 			ingress_mac: ingress_mac,
 			read_state: EncryptedConnectionState::Header,
 			protocol_id: 0,
-			payload_len: 0
+			payload_len: 0,
+			observer: None,
+			idle_timeout_ns: None,
+			frame_buf: Bytes::new(),
+			rekey: None,
+			egress_rekey_bytes: 0,
+			egress_rekey_ns: 0,
+			peer_info: None,
+			protocol_version: DEFAULT_FRAME_PROTOCOL_VERSION,
+			ping_intervals: Vec::new(),
 		};
 		enc.connection.expect(ENCRYPTED_HEADER_LEN);
 		Ok(enc)
@@ -342,116 +1160,331 @@ impl EncryptedConnection {
 
 	/// Send a packet
 	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, payload: &[u8]) -> Result<(), UtilError> where Message: Send + Clone {
-		let mut header = RlpStream::new();
-		let len = payload.len() as usize;
-		header.append_raw(&[(len >> 16) as u8, (len >> 8) as u8, len as u8], 1);
-		header.append_raw(&[0xc2u8, 0x80u8, 0x80u8], 1);
-		//TODO: ger rid of vectors here
-		let mut header = header.out();
-		let padding = (16 - (payload.len() % 16)) % 16;
-		header.resize(16, 0u8);
+		let mut packet = Bytes::new();
+		try!(self.write_packet(payload, &mut packet));
+		self.connection.send(io, packet);
+		Ok(())
+	}
 
-		let mut packet = vec![0u8; (32 + payload.len() + padding + 16)];
-		self.encoder.encrypt(&mut RefReadBuffer::new(&header), &mut RefWriteBuffer::new(&mut packet), false).expect("Invalid length or padding");
-		EncryptedConnection::update_mac(&mut self.egress_mac, &mut self.mac_encoder,  &packet[0..16]);
-		self.egress_mac.clone().finalize(&mut packet[16..32]);
-		self.encoder.encrypt(&mut RefReadBuffer::new(&payload), &mut RefWriteBuffer::new(&mut packet[32..(32 + len)]), padding == 0).expect("Invalid length or padding");
-		if padding != 0 {
-			let pad = [0u8; 16];
-			self.encoder.encrypt(&mut RefReadBuffer::new(&pad[0..padding]), &mut RefWriteBuffer::new(&mut packet[(32 + len)..(32 + len + padding)]), true).expect("Invalid length or padding");
+	/// Send a batch of packets. Frames are chained in order into a single send queue entry,
+	/// so the result on the wire is identical to calling `send_packet` for each payload.
+	pub fn send_packets<Message>(&mut self, io: &IoContext<Message>, payloads: &[&[u8]]) -> Result<(), UtilError> where Message: Send + Clone {
+		let mut packet = Bytes::with_capacity(payloads.iter().fold(0, |size, p| size + EncryptedConnection::frame_size(p.len())));
+		for payload in payloads {
+			try!(self.write_packet(payload, &mut packet));
 		}
-		self.egress_mac.update(&packet[32..(32 + len + padding)]);
-		EncryptedConnection::update_mac(&mut self.egress_mac, &mut self.mac_encoder, &[0u8; 0]);
-		self.egress_mac.clone().finalize(&mut packet[(32 + len + padding)..]);
 		self.connection.send(io, packet);
 		Ok(())
 	}
 
-	/// Decrypt and authenticate an incoming packet header. Prepare for receiving payload.
-	fn read_header(&mut self, header: &[u8]) -> Result<(), UtilError> {
-		if header.len() != ENCRYPTED_HEADER_LEN {
-			return Err(From::from(NetworkError::Auth));
-		}
-		EncryptedConnection::update_mac(&mut self.ingress_mac, &mut self.mac_encoder, &header[0..16]);
-		let mac = &header[16..];
-		let mut expected = H256::new();
-		self.ingress_mac.clone().finalize(&mut expected);
-		if mac != &expected[0..16] {
-			return Err(From::from(NetworkError::Auth));
-		}
+	/// Size of the encrypted frame for a payload of given length.
+	fn frame_size(len: usize) -> usize {
+		let padding = (16 - (len % 16)) % 16;
+		32 + len + padding + 16
+	}
 
-		let mut hdec = H128::new();
-		self.decoder.decrypt(&mut RefReadBuffer::new(&header[0..16]), &mut RefWriteBuffer::new(&mut hdec), false).expect("Invalid length or padding");
+	/// Start re-keying the connection. Must only be called once both peers agreed to re-key.
+	pub fn enable_rekey(&mut self, policy: RekeyPolicy) {
+		self.rekey = Some(policy);
+		self.egress_rekey_bytes = 0;
+		self.egress_rekey_ns = time::precise_time_ns();
+	}
 
-		let length = ((((hdec[0] as u32) << 8) + (hdec[1] as u32)) << 8) + (hdec[2] as u32);
-		let header_rlp = UntrustedRlp::new(&hdec[3..6]);
-		let protocol_id = try!(header_rlp.val_at::<u16>(0));
+	/// Check if re-keying has been negotiated for this connection.
+	pub fn is_rekey_enabled(&self) -> bool {
+		self.rekey.is_some()
+	}
 
-		self.payload_len = length as usize;
-		self.protocol_id = protocol_id;
-		self.read_state = EncryptedConnectionState::Payload;
+	/// Set the protocol version negotiated with the peer.
+	pub fn set_protocol_version(&mut self, version: u32) {
+		self.protocol_version = version;
+	}
 
-		let padding = (16 - (length % 16)) % 16;
-		let full_length = length + padding + 16;
+	/// Protocol version negotiated with the peer.
+	pub fn protocol_version(&self) -> u32 {
+		self.protocol_version
+	}
+
+	/// Store peer information received in the `Hello` packet.
+	pub fn set_peer_info(&mut self, info: PeerInfo) {
+		self.peer_info = Some(info);
+	}
+
+	/// Peer node id, client id, protocol version and negotiated capabilities.
+	/// `None` until the `Hello` exchange is complete.
+	pub fn peer_info(&self) -> Option<&PeerInfo> {
+		self.peer_info.as_ref()
+	}
+
+	/// Store keepalive ping intervals in seconds of the negotiated capabilities.
+	pub fn set_ping_intervals(&mut self, intervals: Vec<(String, u64)>) {
+		self.ping_intervals = intervals;
+	}
+
+	/// Keepalive ping interval in seconds of a negotiated capability.
+	pub fn ping_interval(&self, protocol: &str) -> Option<u64> {
+		self.ping_intervals.iter().find(|&&(ref p, _)| p == protocol).map(|&(_, interval)| interval)
+	}
+
+	/// Interval in seconds at which the connection is pinged. Pings keep all capabilities alive,
+	/// so the one needing the most frequent pings sets the pace. `None` before the `Hello` exchange.
+	pub fn keepalive_interval(&self) -> Option<u64> {
+		self.ping_intervals.iter().map(|&(_, interval)| interval).min()
+	}
+
+	/// Current receiving status. Useful for diagnosing connections stuck in the middle of a frame.
+	pub fn read_status(&self) -> ReadStatus {
+		match self.read_state {
+			EncryptedConnectionState::Header => ReadStatus::AwaitingHeader,
+			EncryptedConnectionState::Payload => ReadStatus::ReadingPayload {
+				protocol: self.protocol_id,
+				payload_len: self.payload_len,
+				remaining: self.payload_len.saturating_sub(self.connection.received_len()),
+			},
+		}
+	}
+
+	/// Current egress and ingress MAC digests, for comparing against the peer's when frames fail authentication.
+	/// The digests of a healthy connection match the peer's ingress and egress digests respectively.
+	///
+	/// Debugging aid only: MAC state is secret connection material and leaking it weakens the
+	/// connection's security. Only available with the `mac-debug` feature.
+	#[cfg(feature = "mac-debug")]
+	pub fn mac_digests(&self) -> (H256, H256) {
+		let mut egress = H256::new();
+		let mut ingress = H256::new();
+		self.egress_mac.clone().finalize(&mut egress);
+		self.ingress_mac.clone().finalize(&mut ingress);
+		(egress, ingress)
+	}
+
+	fn rekey_due(&self) -> bool {
+		match self.rekey {
+			Some(ref policy) => self.egress_rekey_bytes >= policy.max_bytes
+				|| time::precise_time_ns() - self.egress_rekey_ns >= policy.max_time_ms * 1000_000,
+			None => false,
+		}
+	}
+
+	/// Switch to the next egress keys.
+	fn rekey_egress(&mut self) {
+		self.egress_secrets = next_secrets(&self.egress_secrets);
+		self.encoder = self.cipher.frame_cipher(&self.egress_secrets[0..32], &[0u8; 16]);
+		self.egress_mac_encoder = self.cipher.mac_cipher(&self.egress_secrets[32..64]);
+		self.egress_rekey_bytes = 0;
+		self.egress_rekey_ns = time::precise_time_ns();
+		trace!(target: "network", "{}: Egress keys rotated", self.connection.token);
+	}
+
+	/// Switch to the next ingress keys.
+	fn rekey_ingress(&mut self) {
+		self.ingress_secrets = next_secrets(&self.ingress_secrets);
+		self.decoder = self.cipher.frame_cipher(&self.ingress_secrets[0..32], &[0u8; 16]);
+		self.ingress_mac_encoder = self.cipher.mac_cipher(&self.ingress_secrets[32..64]);
+		trace!(target: "network", "{}: Ingress keys rotated", self.connection.token);
+	}
+
+	/// Encrypt a packet and append the frame to `out`.
+	/// Announces and switches to fresh keys first if re-keying is due.
+	fn write_packet(&mut self, payload: &[u8], out: &mut Bytes) -> Result<(), NetworkError> {
+		if self.rekey_due() {
+			try!(self.write_frame(&REKEY_PAYLOAD, out));
+			self.rekey_egress();
+		}
+		try!(self.write_frame(payload, out));
+		self.egress_rekey_bytes += payload.len() as u64;
+		Ok(())
+	}
+
+	/// Encrypt a single frame and append it to `out`.
+	fn write_frame(&mut self, payload: &[u8], out: &mut Bytes) -> Result<(), NetworkError> {
+		let len = payload.len() as usize;
+		if len > MAX_PAYLOAD_SIZE {
+			return Err(NetworkError::FrameTooLarge);
+		}
+		let mut header = RlpStream::new();
+		header.append_raw(&[(len >> 16) as u8, (len >> 8) as u8, len as u8], 1);
+		header.append_raw(&FRAME_HEADER_DATA, 1);
+		//TODO: ger rid of vectors here
+		let mut header = header.out();
+		let padding = (16 - (payload.len() % 16)) % 16;
+		header.resize(16, 0u8);
+
+		let offset = out.len();
+		out.resize(offset + EncryptedConnection::frame_size(len), 0u8);
+		let packet = &mut out[offset..];
+		self.encoder.process(&header, &mut packet[0..16]);
+		EncryptedConnection::update_mac_with_header(&mut self.egress_mac, &*self.egress_mac_encoder, &packet[0..16]);
+		self.egress_mac.clone().finalize(&mut packet[16..32]);
+		self.encoder.process(&payload, &mut packet[32..(32 + len)]);
+		if padding != 0 {
+			let pad = [0u8; 16];
+			self.encoder.process(&pad[0..padding], &mut packet[(32 + len)..(32 + len + padding)]);
+		}
+		self.egress_mac.update(&packet[32..(32 + len + padding)]);
+		EncryptedConnection::update_mac_with_frame(&mut self.egress_mac, &*self.egress_mac_encoder);
+		self.egress_mac.clone().finalize(&mut packet[(32 + len + padding)..]);
+		Ok(())
+	}
+
+	/// Decrypt and authenticate an incoming packet header. Prepare for receiving payload.
+	fn read_header(&mut self, header: &[u8]) -> Result<(), UtilError> {
+		if header.len() != ENCRYPTED_HEADER_LEN {
+			return Err(From::from(NetworkError::DecodeError));
+		}
+		EncryptedConnection::update_mac_with_header(&mut self.ingress_mac, &*self.ingress_mac_encoder, &header[0..16]);
+		let mac = &header[16..];
+		let mut expected = H256::new();
+		self.ingress_mac.clone().finalize(&mut expected);
+		if mac != &expected[0..16] {
+			return Err(From::from(NetworkError::AuthMacMismatch));
+		}
+
+		let mut hdec = H128::new();
+		self.decoder.process(&header[0..16], &mut hdec);
+
+		let (length, protocol_id) = try!(decode_frame_header(&hdec));
+
+		self.payload_len = length as usize;
+		self.protocol_id = protocol_id;
+		self.read_state = EncryptedConnectionState::Payload;
+
+		let padding = (16 - (length % 16)) % 16;
+		let full_length = length + padding + 16;
 		self.connection.expect(full_length as usize);
 		Ok(())
 	}
 
-	/// Decrypt and authenticate packet payload.
-	fn read_payload(&mut self, payload: &[u8]) -> Result<Packet, UtilError> {
+	/// Decrypt and authenticate packet payload into `data`.
+	fn read_payload(&mut self, payload: &[u8], data: &mut Bytes) -> Result<(), UtilError> {
 		let padding = (16 - (self.payload_len  % 16)) % 16;
 		let full_length = self.payload_len + padding + 16;
 		if payload.len() != full_length {
-			return Err(From::from(NetworkError::Auth));
+			return Err(From::from(NetworkError::DecodeError));
 		}
 		self.ingress_mac.update(&payload[0..payload.len() - 16]);
-		EncryptedConnection::update_mac(&mut self.ingress_mac, &mut self.mac_encoder, &[0u8; 0]);
+		EncryptedConnection::update_mac_with_frame(&mut self.ingress_mac, &*self.ingress_mac_encoder);
 		let mac = &payload[(payload.len() - 16)..];
 		let mut expected = H128::new();
 		self.ingress_mac.clone().finalize(&mut expected);
 		if mac != &expected[..] {
-			return Err(From::from(NetworkError::Auth));
+			return Err(From::from(NetworkError::AuthMacMismatch));
 		}
 
-		let mut packet = vec![0u8; self.payload_len];
-		self.decoder.decrypt(&mut RefReadBuffer::new(&payload[0..self.payload_len]), &mut RefWriteBuffer::new(&mut packet), false).expect("Invalid length or padding");
+		// resizing a buffer reused by the caller does not reallocate for payloads up to its capacity
+		data.clear();
+		data.resize(self.payload_len, 0);
+		self.decoder.process(&payload[0..self.payload_len], data);
 		let mut pad_buf = [0u8; 16];
-		self.decoder.decrypt(&mut RefReadBuffer::new(&payload[self.payload_len..(payload.len() - 16)]), &mut RefWriteBuffer::new(&mut pad_buf), false).expect("Invalid length or padding");
-		Ok(Packet {
-			protocol: self.protocol_id,
-			data: packet
+		self.decoder.process(&payload[self.payload_len..(payload.len() - 16)], &mut pad_buf[0..padding]);
+		Ok(())
+	}
+
+	/// Returns the first 16 bytes of the current MAC digest and their encryption with the MAC secret.
+	fn mac_digest(mac: &Keccak, mac_encoder: &MacCipher) -> (H128, H128) {
+		let mut digest = H128::new();
+		mac.clone().finalize(&mut digest);
+		let mut encrypted = H128::new();
+		mac_encoder.encrypt_block(&digest, &mut encrypted);
+		(digest, encrypted)
+	}
+
+	/// Update MAC with an encrypted frame header.
+	/// https://github.com/ethereum/devp2p/blob/master/rlpx.md#mac
+	/// `header-mac-seed = aes(mac-secret, keccak256.digest(mac)[:16]) ^ header-ciphertext`
+	fn update_mac_with_header(mac: &mut Keccak, mac_encoder: &MacCipher, header_ciphertext: &[u8]) {
+		assert_eq!(header_ciphertext.len(), 16);
+		let (_, encrypted) = EncryptedConnection::mac_digest(mac, mac_encoder);
+		mac.update(&(encrypted ^ H128::from_slice(header_ciphertext)));
+	}
+
+	/// Update MAC once the frame ciphertext has been added to it. The digest is the seed in this case.
+	/// https://github.com/ethereum/devp2p/blob/master/rlpx.md#mac
+	/// `frame-mac-seed = aes(mac-secret, keccak256.digest(mac)[:16]) ^ keccak256.digest(mac)[:16]`
+	fn update_mac_with_frame(mac: &mut Keccak, mac_encoder: &MacCipher) {
+		let (digest, encrypted) = EncryptedConnection::mac_digest(mac, mac_encoder);
+		mac.update(&(encrypted ^ digest));
+	}
+
+	/// Time left in milliseconds until the idle timer fires or `None` if the timer is not set.
+	pub fn idle_timeout_remaining(&self) -> Option<u64> {
+		self.idle_timeout_ns.map(|deadline| {
+			let now = time::precise_time_ns();
+			if deadline > now { (deadline - now) / 1000_000 } else { 0 }
 		})
 	}
 
-	/// Update MAC after reading or writing any data.
-	fn update_mac(mac: &mut Keccak, mac_encoder: &mut EcbEncryptor<AesSafe256Encryptor, EncPadding<NoPadding>>, seed: &[u8]) {
-		let mut prev = H128::new();
-		mac.clone().finalize(&mut prev);
-		let mut enc = H128::new();
-		mac_encoder.encrypt(&mut RefReadBuffer::new(&prev), &mut RefWriteBuffer::new(&mut enc), true).unwrap();
-		mac_encoder.reset();
+	/// Set connection events observer.
+	pub fn set_observer(&mut self, observer: Arc<ConnectionObserver>) {
+		self.observer = Some(observer);
+	}
 
-		enc = enc ^ if seed.is_empty() { prev } else { H128::from_slice(seed) };
-		mac.update(&enc);
+	/// Notify the observer about an event.
+	pub fn notify(&self, event: ConnectionEvent) {
+		if let Some(ref observer) = self.observer {
+			observer.on_event(self.connection.token, event);
+		}
 	}
 
 	/// Readable IO handler. Tracker receive status and returns decoded packet if avaialable.
-	pub fn readable<Message>(&mut self, io: &IoContext<Message>) -> Result<Option<Packet>, UtilError> where Message: Send + Clone{
+	pub fn readable<Message>(&mut self, io: &IoContext<Message>) -> Result<Option<Packet>, UtilError> where Message: Send + Clone {
+		let mut data = Bytes::new();
+		match try!(self.readable_into(io, &mut data)) {
+			Some(protocol) => Ok(Some(try!(Packet::new(protocol, data)))),
+			None => Ok(None),
+		}
+	}
+
+	/// Readable IO handler which decrypts the payload into a caller-provided buffer.
+	/// Returns protocol id of the packet if one has been received; `data` then holds the payload.
+	/// Reusing `data` across calls avoids allocating for every received frame.
+	pub fn readable_into<Message>(&mut self, io: &IoContext<Message>, data: &mut Bytes) -> Result<Option<u16>, UtilError> where Message: Send + Clone {
+		let result = self.read_frame(io, data);
+		// a frame failing its MAC leaves ingress MAC and cipher state out of step, the stream can't be resynced
+		if let Err(UtilError::Network(NetworkError::AuthMacMismatch)) = result {
+			debug!(target: "network", "{}: Frame failed MAC check, dropping connection", self.connection.token);
+		}
+		if self.observer.is_some() {
+			match result {
+				Ok(Some(_)) => self.notify(ConnectionEvent::PacketReceived),
+				Err(ref e) => self.notify(ConnectionEvent::from_error(e)),
+				Ok(None) => {},
+			}
+		}
+		result
+	}
+
+	fn read_frame<Message>(&mut self, io: &IoContext<Message>, data: &mut Bytes) -> Result<Option<u16>, UtilError> where Message: Send + Clone {
 		try!(io.clear_timer(self.connection.token));
+		self.idle_timeout_ns = None;
 		if let EncryptedConnectionState::Header = self.read_state {
-			if let Some(data) = try!(self.connection.readable()) {
-				try!(self.read_header(&data));
+			if try!(self.connection.readable_into(&mut self.frame_buf)) {
+				let mut frame = ::std::mem::replace(&mut self.frame_buf, Bytes::new());
+				let result = self.read_header(&frame);
+				reuse_buffer(&mut frame, MAX_RETAINED_RECEIVE_BUFFER);
+				self.frame_buf = frame;
+				try!(result);
 				try!(io.register_timer(self.connection.token, RECIEVE_PAYLOAD_TIMEOUT));
+				self.idle_timeout_ns = Some(time::precise_time_ns() + RECIEVE_PAYLOAD_TIMEOUT * 1000_000);
 			}
 		};
 		if let EncryptedConnectionState::Payload = self.read_state {
-			match try!(self.connection.readable()) {
-				Some(data) => {
+			match try!(self.connection.readable_into(&mut self.frame_buf)) {
+				true => {
 					self.read_state = EncryptedConnectionState::Header;
 					self.connection.expect(ENCRYPTED_HEADER_LEN);
-					Ok(Some(try!(self.read_payload(&data))))
+					let mut frame = ::std::mem::replace(&mut self.frame_buf, Bytes::new());
+					let result = self.read_payload(&frame, data);
+					reuse_buffer(&mut frame, MAX_RETAINED_RECEIVE_BUFFER);
+					self.frame_buf = frame;
+					try!(result);
+					// the peer uses fresh keys for all frames following the re-key packet
+					if self.rekey.is_some() && self.protocol_id == 0 && data.first() == Some(&PacketKind::Rekey.to_raw()) {
+						self.rekey_ingress();
+					}
+					Ok(Some(self.protocol_id))
 				},
-				None => Ok(None)
+				false => Ok(None)
 			}
 		} else {
 			Ok(None)
@@ -465,10 +1498,26 @@ impl EncryptedConnection {
 	}
 }
 
+/// Decode frame length and protocol id from a decrypted frame header.
+/// Empty frames and malformed header data are rejected.
+fn decode_frame_header(hdec: &[u8]) -> Result<(u32, u16), NetworkError> {
+	let length = ((((hdec[0] as u32) << 8) + (hdec[1] as u32)) << 8) + (hdec[2] as u32);
+	if length == 0 {
+		return Err(NetworkError::Auth);
+	}
+	let header_rlp = UntrustedRlp::new(&hdec[3..6]);
+	let protocol_id = try!(header_rlp.val_at::<u16>(0).map_err(|_| NetworkError::Auth));
+	Ok((length, protocol_id))
+}
+
 #[test]
 pub fn test_encryption() {
 	use hash::*;
 	use std::str::FromStr;
+	use rcrypto::blockmodes::*;
+	use rcrypto::aessafe::*;
+	use rcrypto::symmetriccipher::*;
+	use rcrypto::buffer::*;
 	let key = H256::from_str("2212767d793a7a3d66f869ae324dd11bd17044b82c9f463b8a541a4d089efec5").unwrap();
 	let before = H128::from_str("12532abaec065082a3cf1da7d0136f15").unwrap();
 	let before2 = H128::from_str("7e99f682356fdfbc6b67a9562787b18a").unwrap();
@@ -487,6 +1536,63 @@ pub fn test_encryption() {
 	assert_eq!(got, after2);
 }
 
+/// Encrypted connections over both ends of a loopback TCP connection. Both use the same keys,
+/// so frames sent by one can be read by the other.
+#[cfg(test)]
+pub fn encrypted_pair() -> (EncryptedConnection, EncryptedConnection) {
+	use std::str::FromStr;
+	use std::net::SocketAddr;
+	use mio::tcp::{TcpListener, TcpStream};
+
+	let listener = TcpListener::bind(&SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+	let client = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+	let mut server = None;
+	for _ in 0..200 {
+		server = listener.accept().unwrap().map(|(socket, _)| socket);
+		if server.is_some() {
+			break;
+		}
+		::std::thread::sleep(::std::time::Duration::from_millis(5));
+	}
+	(encrypted(client), encrypted(server.expect("Loopback connection is accepted")))
+}
+
+#[cfg(test)]
+fn encrypted(socket: ::mio::tcp::TcpStream) -> EncryptedConnection {
+	use network::cipher::CipherBackend;
+	use tiny_keccak::Keccak;
+
+	let cipher = CipherBackend::Safe;
+	let key = [7u8; 32];
+	let iv = [0u8; 16];
+	let mut connection = EncryptedConnection {
+		connection: Connection::new(0, socket, Arc::new(NetworkStats::new())),
+		cipher: cipher,
+		encoder: cipher.frame_cipher(&key, &iv),
+		decoder: cipher.frame_cipher(&key, &iv),
+		egress_mac_encoder: cipher.mac_cipher(&key),
+		ingress_mac_encoder: cipher.mac_cipher(&key),
+		egress_secrets: ::hash::H512::new(),
+		ingress_secrets: ::hash::H512::new(),
+		egress_mac: Keccak::new_keccak256(),
+		ingress_mac: Keccak::new_keccak256(),
+		read_state: EncryptedConnectionState::Header,
+		protocol_id: 0,
+		payload_len: 0,
+		observer: None,
+		idle_timeout_ns: None,
+		frame_buf: Bytes::new(),
+		rekey: None,
+		egress_rekey_bytes: 0,
+		egress_rekey_ns: 0,
+		peer_info: None,
+		protocol_version: DEFAULT_FRAME_PROTOCOL_VERSION,
+		ping_intervals: Vec::new(),
+	};
+	connection.connection.expect(ENCRYPTED_HEADER_LEN);
+	connection
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -499,22 +1605,25 @@ mod tests {
 	use bytes::*;
 	use devtools::*;
 	use io::*;
+	use error::UtilError;
+	use network::error::NetworkError;
 
 	impl GenericSocket for TestSocket {}
 
 	struct TestBrokenSocket {
-		error: String
+		error: String,
+		kind: ErrorKind,
 	}
 
 	impl Read for TestBrokenSocket {
 		fn read(&mut self, _: &mut [u8]) -> Result<usize, Error> {
-			Err(Error::new(ErrorKind::Other, self.error.clone()))
+			Err(Error::new(self.kind, self.error.clone()))
 		}
 	}
 
 	impl Write for TestBrokenSocket {
 		fn write(&mut self, _: &[u8]) -> Result<usize, Error> {
-			Err(Error::new(ErrorKind::Other, self.error.clone()))
+			Err(Error::new(self.kind, self.error.clone()))
 		}
 
 		fn flush(&mut self) -> Result<(), Error> {
@@ -524,6 +1633,49 @@ mod tests {
 
 	impl GenericSocket for TestBrokenSocket {}
 
+	/// Socket reporting more bytes written than it was given.
+	struct TestOverreportingSocket;
+
+	impl Read for TestOverreportingSocket {
+		fn read(&mut self, _: &mut [u8]) -> Result<usize, Error> {
+			Ok(0)
+		}
+	}
+
+	impl Write for TestOverreportingSocket {
+		fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+			Ok(buf.len() + 100)
+		}
+
+		fn flush(&mut self) -> Result<(), Error> {
+			Ok(())
+		}
+	}
+
+	impl GenericSocket for TestOverreportingSocket {}
+
+	/// Connection over `socket` in the initial state, as created for a new peer.
+	fn test_connection<S: GenericSocket>(socket: S) -> GenericConnection<S> {
+		GenericConnection {
+			token: 999998888usize,
+			socket: socket,
+			send_queue: VecDeque::new(),
+			rec_buf: Bytes::new(),
+			rec_size: 0,
+			interest: EventSet::hup() | EventSet::readable(),
+			stats: Arc::<NetworkStats>::new(NetworkStats::new()),
+			registered: AtomicBool::new(false),
+			socket_closed: AtomicBool::new(false),
+			last_write_ns: 0,
+			disabled: EventSet::none(),
+			poll_mode: PollMode::default(),
+			socket_writable: false,
+			coalesce_window_ns: 0,
+			coalesce_since_ns: 0,
+			send_queue_bytes: 0,
+		}
+	}
+
 	type TestConnection = GenericConnection<TestSocket>;
 
 	impl Default for TestConnection {
@@ -534,16 +1686,7 @@ mod tests {
 
 	impl TestConnection {
 		pub fn new() -> Self {
-			TestConnection {
-				token: 999998888usize,
-				socket: TestSocket::new(),
-				send_queue: VecDeque::new(),
-				rec_buf: Bytes::new(),
-				rec_size: 0,
-				interest: EventSet::hup() | EventSet::readable(),
-				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
-				registered: AtomicBool::new(false),
-			}
+			test_connection(TestSocket::new())
 		}
 	}
 
@@ -557,16 +1700,7 @@ mod tests {
 
 	impl TestBrokenConnection {
 		pub fn new() -> Self {
-			TestBrokenConnection {
-				token: 999998888usize,
-				socket: TestBrokenSocket { error: "test broken socket".to_owned() },
-				send_queue: VecDeque::new(),
-				rec_buf: Bytes::new(),
-				rec_size: 0,
-				interest: EventSet::hup() | EventSet::readable(),
-				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
-				registered: AtomicBool::new(false),
-			}
+			test_connection(TestBrokenSocket { error: "test broken socket".to_owned(), kind: ErrorKind::Other })
 		}
 	}
 
@@ -581,6 +1715,28 @@ mod tests {
 		assert_eq!(1024, connection.rec_size);
 	}
 
+	#[test]
+	fn connection_expect_preallocates() {
+		let mut connection = TestConnection::new();
+		connection.expect(1024 * 1024);
+		assert!(connection.rec_buf.capacity() >= 1024 * 1024);
+
+		connection.socket.read_buffer = vec![1; 1024 * 1024];
+		let ptr = connection.rec_buf.as_ptr();
+		let status = connection.readable();
+		let data = status.unwrap().unwrap();
+		// read into the preallocated buffer without reallocation
+		assert_eq!(ptr, data.as_ptr());
+		assert_eq!(1024 * 1024, data.len());
+	}
+
+	#[test]
+	fn connection_expect_caps_preallocation() {
+		let mut connection = TestConnection::new();
+		connection.expect(16 * 1024 * 1024);
+		assert!(connection.rec_buf.capacity() < 16 * 1024 * 1024);
+	}
+
 	#[test]
 	fn connection_write_empty() {
 		let mut connection = TestConnection::new();
@@ -589,11 +1745,51 @@ mod tests {
 		assert!(WriteStatus::Complete == status.unwrap());
 	}
 
+	#[test]
+	fn connection_poll_mode() {
+		let mut connection = TestConnection::new();
+		assert_eq!(connection.poll_mode(), PollMode::Edge);
+		assert!(connection.poll_mode().poll_opt().is_edge());
+		assert!(!connection.poll_mode().needs_reregister());
+
+		connection.set_poll_mode(PollMode::Oneshot);
+		let opt = connection.poll_mode().poll_opt();
+		assert!(opt.is_edge() && opt.is_oneshot());
+		assert!(connection.poll_mode().needs_reregister());
+
+		connection.set_poll_mode(PollMode::Level);
+		let opt = connection.poll_mode().poll_opt();
+		assert!(opt.is_level() && !opt.is_oneshot());
+		assert!(!connection.poll_mode().needs_reregister());
+	}
+
+	#[test]
+	fn connection_half_duplex() {
+		use std::net::Shutdown;
+
+		let mut connection = TestConnection::new();
+		connection.send(&test_io(), vec![1u8; 10]);
+		assert!(connection.interest().is_writable());
+
+		connection.disable(Shutdown::Write);
+		assert!(!connection.interest().is_writable());
+		assert!(connection.interest().is_readable());
+		assert!(connection.send_queue.is_empty());
+
+		connection.send(&test_io(), vec![1u8; 10]);
+		assert!(connection.send_queue.is_empty());
+		assert!(!connection.interest().is_writable());
+
+		connection.disable(Shutdown::Read);
+		assert!(!connection.interest().is_readable());
+		assert!(connection.interest().is_hup());
+	}
+
 	#[test]
 	fn connection_write() {
 		let mut connection = TestConnection::new();
 		let data = Cursor::new(vec![0; 10240]);
-		connection.send_queue.push_back(data);
+		connection.enqueue(data);
 
 		let status = connection.writable(&test_io());
 		assert!(status.is_ok());
@@ -601,12 +1797,115 @@ mod tests {
 		assert_eq!(10240, connection.socket.write_buffer.len());
 	}
 
+	#[test]
+	fn connection_writes_immediately_when_writable() {
+		let mut connection = TestConnection::new();
+		connection.socket = TestSocket::new_buf(1024);
+
+		// not known to be writable before the first writable event
+		connection.send(&test_io(), vec![1u8; 10]);
+		assert_eq!(0, connection.socket.write_buffer.len());
+		assert!(connection.interest().is_writable());
+
+		connection.writable(&test_io()).unwrap();
+		assert_eq!(10, connection.socket.write_buffer.len());
+		assert!(!connection.interest().is_writable());
+
+		connection.send(&test_io(), vec![2u8; 100]);
+		assert_eq!(110, connection.socket.write_buffer.len());
+		assert!(connection.send_queue.is_empty());
+		assert!(!connection.interest().is_writable());
+
+		// partial write leaves the remainder queued
+		connection.send(&test_io(), vec![3u8; 2000]);
+		assert_eq!(1134, connection.socket.write_buffer.len());
+		assert_eq!(976, connection.pending_send_bytes());
+		assert!(connection.interest().is_writable());
+
+		connection.send(&test_io(), vec![4u8; 10]);
+		assert_eq!(1134, connection.socket.write_buffer.len());
+		assert_eq!(986, connection.pending_send_bytes());
+
+		connection.flush(&test_io()).unwrap();
+		assert_eq!(2120, connection.socket.write_buffer.len());
+		assert_eq!(&connection.socket.write_buffer[1134..2110], &vec![3u8; 976][..]);
+	}
+
+	#[test]
+	fn connection_write_coalescing_reduces_writes() {
+		let mut plain = TestConnection::new();
+		plain.writable(&test_io()).unwrap();
+		for i in 0..100 {
+			plain.send(&test_io(), vec![i as u8; 16]);
+		}
+		assert_eq!(100, plain.socket.write_calls);
+
+		let mut coalesced = TestConnection::new();
+		coalesced.set_coalesce_window(1_000_000);
+		coalesced.writable(&test_io()).unwrap();
+		for i in 0..100 {
+			coalesced.send(&test_io(), vec![i as u8; 16]);
+		}
+		assert_eq!(0, coalesced.socket.write_calls);
+		assert_eq!(1, coalesced.send_queue.len());
+		assert!(coalesced.interest().is_writable());
+
+		coalesced.writable(&test_io()).unwrap();
+		assert_eq!(1, coalesced.socket.write_calls);
+		assert_eq!(plain.socket.write_buffer, coalesced.socket.write_buffer);
+		assert!(!coalesced.interest().is_writable());
+	}
+
+	#[test]
+	fn connection_write_coalescing_is_bounded() {
+		let mut connection = TestConnection::new();
+		connection.set_coalesce_window(1_000_000);
+		connection.writable(&test_io()).unwrap();
+
+		connection.send(&test_io(), vec![1u8; 10]);
+		assert_eq!(0, connection.socket.write_calls);
+		// window is over
+		connection.coalesce_since_ns -= 2_000_000_000;
+		connection.send(&test_io(), vec![2u8; 10]);
+		assert_eq!(1, connection.socket.write_calls);
+		assert_eq!(20, connection.socket.write_buffer.len());
+		assert!(connection.send_queue.is_empty());
+		assert!(!connection.interest().is_writable());
+
+		// too much data held back
+		connection.send(&test_io(), vec![3u8; 10]);
+		connection.send(&test_io(), vec![4u8; super::MAX_COALESCED_BYTES]);
+		assert_eq!(2, connection.socket.write_calls);
+		assert_eq!(30 + super::MAX_COALESCED_BYTES, connection.socket.write_buffer.len());
+
+		// flush bypasses the window
+		connection.send(&test_io(), vec![5u8; 10]);
+		assert_eq!(2, connection.socket.write_calls);
+		connection.flush(&test_io()).unwrap();
+		assert_eq!(3, connection.socket.write_calls);
+		assert!(connection.send_queue.is_empty());
+	}
+
+	#[test]
+	fn connection_write_stall_is_detected() {
+		let mut connection = TestConnection::new();
+		assert!(!connection.is_write_stalled(0));
+
+		connection.send(&test_io(), vec![0; 1024]);
+		assert!(!connection.is_write_stalled(60_000));
+		connection.last_write_ns = ::time::precise_time_ns() - 2000_000_000;
+		assert!(connection.is_write_stalled(1000));
+
+		connection.writable(&test_io()).unwrap();
+		assert!(!connection.is_write_stalled(0));
+	}
+
 	#[test]
 	fn connection_write_is_buffered() {
 		let mut connection = TestConnection::new();
 		connection.socket = TestSocket::new_buf(1024);
 		let data = Cursor::new(vec![0; 10240]);
-		connection.send_queue.push_back(data);
+		connection.enqueue(data);
 
 		let status = connection.writable(&test_io());
 
@@ -615,11 +1914,147 @@ mod tests {
 		assert_eq!(1024, connection.socket.write_buffer.len());
 	}
 
+	#[test]
+	fn connection_write_partial_then_full() {
+		let mut connection = TestConnection::new();
+		connection.socket = TestSocket::new_buf(1000);
+		connection.enqueue(Cursor::new(vec![0; 1500]));
+
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert_eq!(WriteStatus::Ongoing, progress.status);
+		assert_eq!(1000, progress.written);
+		assert_eq!(500, progress.remaining);
+		assert_eq!(1000, connection.send_queue.front().unwrap().position());
+
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert_eq!(WriteStatus::Complete, progress.status);
+		assert_eq!(500, progress.written);
+		assert_eq!(0, progress.remaining);
+		assert!(connection.send_queue.is_empty());
+		assert_eq!(1500, connection.socket.write_buffer.len());
+
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert_eq!(WriteStatus::Complete, progress.status);
+		assert_eq!(0, progress.written);
+	}
+
+	#[test]
+	fn connection_write_drops_written_buffer() {
+		let mut connection = TestConnection::new();
+		let mut data = Cursor::new(vec![0; 10]);
+		data.set_position(20);
+		connection.enqueue(data);
+		connection.enqueue(Cursor::new(vec![1; 10]));
+
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert_eq!(WriteStatus::Complete, progress.status);
+		assert_eq!(0, progress.written);
+		assert_eq!(1, connection.send_queue.len());
+		assert!(connection.socket.write_buffer.is_empty());
+	}
+
+	#[test]
+	fn connection_write_overreporting_socket() {
+		let mut connection = test_connection(TestOverreportingSocket);
+		connection.enqueue(Cursor::new(vec![0; 10]));
+
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert_eq!(WriteStatus::Complete, progress.status);
+		assert_eq!(10, progress.written);
+		assert_eq!(0, connection.pending_send_bytes());
+	}
+
+	#[test]
+	fn connection_write_reports_progress() {
+		let mut connection = TestConnection::new();
+		connection.socket = TestSocket::new_buf(1024);
+		connection.enqueue(Cursor::new(vec![0; 1500]));
+		connection.enqueue(Cursor::new(vec![0; 100]));
+
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert!(!progress.is_complete());
+		assert_eq!(1024, progress.written);
+		assert_eq!(576, progress.remaining);
+
+		connection.socket.buf_size = 0;
+		let progress = connection.writable_progress(&test_io()).unwrap();
+		assert!(progress.is_complete());
+		assert_eq!(476, progress.written);
+		assert_eq!(100, progress.remaining);
+	}
+
+	#[test]
+	fn connection_pending_send_counters() {
+		let mut connection = TestConnection::new();
+		connection.socket = TestSocket::new_buf(1024);
+		assert_eq!(0, connection.pending_send_bytes());
+		assert_eq!(0, connection.pending_send_packets());
+
+		connection.send(&test_io(), vec![0; 1500]);
+		connection.send(&test_io(), vec![1; 100]);
+		assert_eq!(1600, connection.pending_send_bytes());
+		assert_eq!(2, connection.pending_send_packets());
+
+		connection.writable(&test_io()).unwrap();
+		assert_eq!(576, connection.pending_send_bytes());
+		assert_eq!(2, connection.pending_send_packets());
+
+		connection.socket.buf_size = 0;
+		connection.writable(&test_io()).unwrap();
+		assert_eq!(100, connection.pending_send_bytes());
+		assert_eq!(1, connection.pending_send_packets());
+
+		connection.writable(&test_io()).unwrap();
+		assert_eq!(0, connection.pending_send_bytes());
+		assert_eq!(0, connection.pending_send_packets());
+	}
+
+	#[test]
+	fn connection_reserve_send() {
+		let mut connection = TestConnection::new();
+		assert!(connection.send_queue.capacity() < 64);
+
+		connection.reserve_send(64);
+		assert!(connection.send_queue.capacity() >= 64);
+
+		let capacity = connection.send_queue.capacity();
+		for _ in 0..64 {
+			connection.send(&test_io(), vec![0; 10]);
+		}
+		assert_eq!(64, connection.pending_send_packets());
+		assert_eq!(capacity, connection.send_queue.capacity());
+	}
+
+	#[test]
+	fn connection_flush() {
+		let mut connection = TestConnection::new();
+		connection.socket = TestSocket::new_buf(1024);
+		connection.enqueue(Cursor::new(vec![0; 10240]));
+		connection.enqueue(Cursor::new(vec![1; 100]));
+
+		let remaining = connection.flush(&test_io());
+
+		assert_eq!(0, remaining.unwrap());
+		assert_eq!(10340, connection.socket.write_buffer.len());
+		assert!(connection.send_queue.is_empty());
+	}
+
+	#[test]
+	fn connection_flush_to_broken() {
+		let mut connection = TestBrokenConnection::new();
+		connection.enqueue(Cursor::new(vec![0; 10240]));
+
+		let remaining = connection.flush(&test_io());
+
+		assert!(remaining.is_err());
+		assert_eq!(1, connection.send_queue.len());
+	}
+
 	#[test]
 	fn connection_write_to_broken() {
 		let mut connection = TestBrokenConnection::new();
 		let data = Cursor::new(vec![0; 10240]);
-		connection.send_queue.push_back(data);
+		connection.enqueue(data);
 
 		let status = connection.writable(&test_io());
 
@@ -640,6 +2075,136 @@ mod tests {
 		assert_eq!(1024, connection.socket.cursor);
 	}
 
+	#[test]
+	fn packet_id_raw_roundtrip() {
+		assert_eq!(PacketKind::from_raw(0x80), PacketKind::Hello);
+		assert_eq!(PacketKind::from_raw(0x03), PacketKind::Pong);
+		assert_eq!(PacketKind::from_raw(0x10), PacketKind::Capability(0x10));
+		assert_eq!(PacketKind::from_raw(0x00), PacketKind::Unknown(0x00));
+		for id in 0..256u16 {
+			assert_eq!(PacketKind::from_raw(id as u8).to_raw(), id as u8);
+		}
+		let packet = Packet::new(0, vec![0x02, 0xc0]).unwrap();
+		assert_eq!(packet.id(), Some(PacketKind::Ping));
+	}
+
+	#[test]
+	fn frame_header_is_validated() {
+		// length 1, header list [0]
+		let header = [0u8, 0, 1, 0xc1, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+		assert_eq!(super::decode_frame_header(&header).unwrap(), (1, 0));
+
+		let empty = [0u8, 0, 0, 0xc1, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+		match super::decode_frame_header(&empty) {
+			Err(NetworkError::Auth) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+
+		// header claims a list longer than the header data
+		let malformed = [0u8, 0, 1, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+		match super::decode_frame_header(&malformed) {
+			Err(NetworkError::Auth) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	fn mac_with_secret() -> (::tiny_keccak::Keccak, Box<::network::cipher::MacCipher>) {
+		let mut mac = ::tiny_keccak::Keccak::new_keccak256();
+		mac.update(&[1u8; 32]);
+		(mac, ::network::cipher::CipherBackend::Safe.mac_cipher(&[2u8; 32]))
+	}
+
+	#[test]
+	fn mac_is_updated_with_header() {
+		use std::str::FromStr;
+		let (mut mac, encoder) = mac_with_secret();
+		super::EncryptedConnection::update_mac_with_header(&mut mac, &*encoder, &[3u8; 16]);
+
+		let mut digest = ::hash::H256::new();
+		mac.finalize(&mut digest);
+		assert_eq!(digest, ::hash::H256::from_str("96d13dff0bd3a852f838c9398b690ebe1821397f26cb3570d332d44f1ff88e29").unwrap());
+	}
+
+	#[test]
+	fn mac_is_updated_with_frame() {
+		use std::str::FromStr;
+		let (mut mac, encoder) = mac_with_secret();
+		mac.update(&[4u8; 16]);
+		super::EncryptedConnection::update_mac_with_frame(&mut mac, &*encoder);
+
+		let mut digest = ::hash::H256::new();
+		mac.finalize(&mut digest);
+		assert_eq!(digest, ::hash::H256::from_str("0edcb99d33f69a889175cd9587c8269de0a29acd09c2bc8bdc5cb6cccacc3db1").unwrap());
+	}
+
+	#[test]
+	fn rekey_derives_next_secrets() {
+		let packet = Packet::new(0, super::REKEY_PAYLOAD.to_vec()).unwrap();
+		assert_eq!(packet.id(), Some(PacketKind::Rekey));
+
+		let secrets = ::hash::H512::from_slice(&[1u8; 64]);
+		let next = super::next_secrets(&secrets);
+		assert!(next != secrets);
+		assert_eq!(next, super::next_secrets(&secrets));
+	}
+
+	#[test]
+	fn connection_read_into_reuses_buffer() {
+		let mut connection = TestConnection::new();
+		connection.expect(1024);
+		connection.socket.read_buffer = vec![99; 1024];
+		let mut buf = Vec::with_capacity(4096);
+		let ptr = buf.as_ptr();
+
+		let status = connection.readable_into(&mut buf);
+
+		assert_eq!(status.unwrap(), true);
+		assert_eq!(buf, vec![99; 1024]);
+		// the caller's buffer is now used for receiving the next frame
+		assert_eq!(connection.rec_buf.as_ptr(), ptr);
+		assert!(connection.rec_buf.is_empty());
+	}
+
+	#[test]
+	fn connection_frame_buffers_are_reused() {
+		let mut connection = TestConnection::new();
+		let mut frame = Bytes::new();
+		let sizes = [1024, 32, 512, 1024, 700];
+		let mut buffers = Vec::new();
+		for i in 0..100 {
+			let size = sizes[i % sizes.len()];
+			connection.expect(size);
+			connection.socket.read_buffer.extend(vec![i as u8; size]);
+			assert_eq!(connection.readable_into(&mut frame).unwrap(), true);
+			assert_eq!(frame, vec![i as u8; size]);
+			super::reuse_buffer(&mut frame, super::MAX_RETAINED_RECEIVE_BUFFER);
+			if i == sizes.len() {
+				// the receive buffer and `frame` are swapped on every read
+				buffers = vec![frame.as_ptr(), connection.rec_buf.as_ptr()];
+			}
+			if i > sizes.len() {
+				// no allocations once both buffers have grown to the largest frame
+				assert!(buffers.contains(&frame.as_ptr()));
+				assert!(buffers.contains(&connection.rec_buf.as_ptr()));
+			}
+		}
+	}
+
+	#[test]
+	fn large_frame_buffers_are_released() {
+		let mut buf = vec![1u8; super::MAX_RETAINED_RECEIVE_BUFFER + 1];
+		super::reuse_buffer(&mut buf, super::MAX_RETAINED_RECEIVE_BUFFER);
+		assert!(buf.is_empty());
+		assert!(buf.capacity() <= super::MAX_RETAINED_RECEIVE_BUFFER);
+
+		let mut buf = vec![1u8; 1024];
+		let ptr = buf.as_ptr();
+		super::reuse_buffer(&mut buf, super::MAX_RETAINED_RECEIVE_BUFFER);
+		assert!(buf.is_empty());
+		assert_eq!(buf.as_ptr(), ptr);
+		assert_eq!(buf.capacity(), 1024);
+	}
+
 	#[test]
 	fn connection_read_from_broken() {
 		let mut connection = TestBrokenConnection::new();
@@ -650,6 +2215,30 @@ mod tests {
 		assert_eq!(0, connection.rec_buf.len());
 	}
 
+	#[test]
+	fn connection_read_from_reset() {
+		let mut connection = TestBrokenConnection::new();
+		connection.socket.kind = ErrorKind::ConnectionReset;
+		connection.rec_size = 2048;
+
+		match connection.readable() {
+			Err(UtilError::Network(NetworkError::ConnectionReset)) => {},
+			other => panic!("Unexpected read result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn connection_write_to_closed() {
+		let mut connection = TestBrokenConnection::new();
+		connection.socket.kind = ErrorKind::BrokenPipe;
+		connection.enqueue(Cursor::new(vec![0; 10240]));
+
+		match connection.writable(&test_io()) {
+			Err(UtilError::Network(NetworkError::ConnectionReset)) => {},
+			other => panic!("Unexpected write result: {:?}", other),
+		}
+	}
+
 	#[test]
 	fn connection_read_nothing() {
 		let mut connection = TestConnection::new();
@@ -661,6 +2250,19 @@ mod tests {
 		assert_eq!(0, connection.rec_buf.len());
 	}
 
+	#[test]
+	fn connection_received_len() {
+		let mut connection = TestConnection::new();
+		connection.rec_size = 2048;
+		connection.rec_buf = vec![10; 1024];
+		connection.socket.read_buffer = vec![99; 512];
+		assert_eq!(1024, connection.received_len());
+
+		connection.readable().unwrap();
+
+		assert_eq!(1536, connection.received_len());
+	}
+
 	#[test]
 	fn connection_read_full() {
 		let mut connection = TestConnection::new();
@@ -672,4 +2274,346 @@ mod tests {
 		assert!(status.is_ok());
 		assert_eq!(0, connection.socket.cursor);
 	}
+
+	/// Encrypted frame for `payload`, taken from the send queue instead of being written out.
+	fn encrypted_frame(sender: &mut EncryptedConnection, payload: &[u8]) -> Bytes {
+		sender.send_packet(&test_io(), payload).unwrap();
+		sender.connection.dequeue().unwrap().into_inner()
+	}
+
+	/// Write `data` to the peer and read until `received` bytes of the current frame part arrived or a packet is complete.
+	fn deliver(sender: &mut EncryptedConnection, receiver: &mut EncryptedConnection, data: &[u8], received: usize) -> Option<Packet> {
+		sender.connection.socket.write_all(data).unwrap();
+		for _ in 0..200 {
+			if let Some(packet) = receiver.readable(&test_io()).unwrap() {
+				return Some(packet);
+			}
+			if receiver.connection.received_len() == received {
+				return None;
+			}
+			::std::thread::sleep(::std::time::Duration::from_millis(5));
+		}
+		panic!("Data was not received");
+	}
+
+	#[test]
+	fn packet_carries_protocol_and_message_id() {
+		let (mut sender, mut receiver) = encrypted_pair();
+
+		let frame = encrypted_frame(&mut sender, &[PacketKind::Ping.to_raw(), 0xc0]);
+		let ping = deliver(&mut sender, &mut receiver, &frame, 0).expect("Packet is complete");
+		assert_eq!(ping.protocol, 0);
+		assert_eq!(ping.packet_id, 0x02);
+		assert!(ping.is_base());
+
+		let frame = encrypted_frame(&mut sender, &[PacketKind::Hello.to_raw(), 0xc0]);
+		let hello = deliver(&mut sender, &mut receiver, &frame, 0).expect("Packet is complete");
+		assert_eq!(hello.packet_id, 0x00);
+		assert!(hello.is_base());
+
+		let frame = encrypted_frame(&mut sender, &[0x13, 0xc0]);
+		let message = deliver(&mut sender, &mut receiver, &frame, 0).expect("Packet is complete");
+		assert_eq!(message.protocol, 0);
+		assert_eq!(message.packet_id, 0x13);
+		assert!(!message.is_base());
+	}
+
+	#[test]
+	fn message_id_is_rlp_decoded() {
+		assert_eq!(super::decode_message_id(&[0x80, 0xc0]).ok(), Some(0));
+		assert_eq!(super::decode_message_id(&[0x7f]).ok(), Some(0x7f));
+		assert_eq!(super::decode_message_id(&[0x81, 0x90, 0xc0]).ok(), Some(0x90));
+		// non-canonical and multi-byte ids
+		assert!(super::decode_message_id(&[0x81, 0x10]).is_err());
+		assert!(super::decode_message_id(&[0x82, 0x01, 0x00]).is_err());
+		assert!(super::decode_message_id(&[]).is_err());
+	}
+
+	#[test]
+	fn header_split_across_reads_is_reassembled() {
+		let (mut sender, mut receiver) = encrypted_pair();
+		let payload = vec![0x10, 1, 2, 3, 4, 5];
+		let frame = encrypted_frame(&mut sender, &payload);
+		assert_eq!(frame.len(), 32 + 16 + 16);
+
+		// first segment ends within the header
+		assert!(deliver(&mut sender, &mut receiver, &frame[0..20], 20).is_none());
+		assert_eq!(receiver.read_status(), ReadStatus::AwaitingHeader);
+		// one byte short of a full header
+		assert!(deliver(&mut sender, &mut receiver, &frame[20..31], 31).is_none());
+		assert_eq!(receiver.read_status(), ReadStatus::AwaitingHeader);
+		// completing the header moves on to the payload
+		assert!(deliver(&mut sender, &mut receiver, &frame[31..32], 0).is_none());
+		assert_eq!(receiver.read_status(), ReadStatus::ReadingPayload { protocol: 0, payload_len: 6, remaining: 6 });
+
+		let packet = deliver(&mut sender, &mut receiver, &frame[32..], 0).expect("Packet is complete");
+		assert_eq!(packet.data, payload);
+		assert_eq!(receiver.read_status(), ReadStatus::AwaitingHeader);
+	}
+
+	#[test]
+	fn bad_mac_frame_is_an_auth_failure() {
+		let (mut sender, mut receiver) = encrypted_pair();
+		let mut frame = encrypted_frame(&mut sender, &[0x10, 1, 2, 3]);
+		let last = frame.len() - 1;
+		frame[last] ^= 0xff;
+		sender.connection.socket.write_all(&frame).unwrap();
+
+		for _ in 0..200 {
+			match receiver.readable(&test_io()) {
+				Ok(Some(_)) => panic!("Tampered frame was accepted"),
+				Ok(None) => ::std::thread::sleep(::std::time::Duration::from_millis(5)),
+				Err(e) => {
+					match e {
+						UtilError::Network(NetworkError::AuthMacMismatch) => {},
+						ref other => panic!("Unexpected error: {:?}", other),
+					}
+					assert_eq!(ConnectionEvent::from_error(&e), ConnectionEvent::AuthFailure);
+					return;
+				},
+			}
+		}
+		panic!("Data was not received");
+	}
+
+	#[test]
+	fn frame_header_data_is_the_same_for_every_protocol_version() {
+		let (mut legacy_sender, _) = encrypted_pair();
+		assert_eq!(legacy_sender.protocol_version(), 4);
+		let legacy = encrypted_frame(&mut legacy_sender, &[0x10, 1, 2]);
+
+		let (mut sender, mut receiver) = encrypted_pair();
+		sender.set_protocol_version(5);
+		let frame = encrypted_frame(&mut sender, &[0x10, 1, 2]);
+		assert_eq!(frame, legacy);
+		assert_eq!(deliver(&mut sender, &mut receiver, &frame, 0).expect("Version 5 frame").data, vec![0x10, 1, 2]);
+	}
+
+	#[test]
+	#[cfg(feature = "mac-debug")]
+	fn mac_digests_match_peer() {
+		let (mut sender, mut receiver) = encrypted_pair();
+		assert_eq!(sender.mac_digests(), receiver.mac_digests());
+
+		let frame = encrypted_frame(&mut sender, &[0x10, 1, 2, 3]);
+		assert!(deliver(&mut sender, &mut receiver, &frame, 0).is_some());
+		let (sender_egress, sender_ingress) = sender.mac_digests();
+		let (receiver_egress, receiver_ingress) = receiver.mac_digests();
+		assert_eq!(sender_egress, receiver_ingress);
+		assert_eq!(sender_ingress, receiver_egress);
+		assert!(sender_egress != sender_ingress);
+	}
+
+	fn backoff(jitter: f64) -> ReconnectBackoff {
+		ReconnectBackoff::new(BackoffConfig {
+			base_ms: 100,
+			max_ms: 1000,
+			jitter: jitter,
+			stable_ms: 500,
+		})
+	}
+
+	#[test]
+	fn backoff_doubles_up_to_max() {
+		let mut backoff = backoff(0.0);
+		assert!(backoff.can_attempt(0));
+
+		let delays: Vec<u64> = (0..6).map(|_| backoff.note_disconnected(0)).collect();
+		assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+		assert_eq!(backoff.failures(), 6);
+
+		for _ in 0..100 {
+			backoff.note_disconnected(0);
+		}
+		assert_eq!(backoff.delay_ms(), 1000);
+	}
+
+	#[test]
+	fn backoff_delays_next_attempt() {
+		let mut backoff = backoff(0.0);
+		backoff.note_disconnected(1000_000_000);
+		assert!(!backoff.can_attempt(1000_000_000));
+		assert!(!backoff.can_attempt(1099_999_999));
+		assert!(backoff.can_attempt(1100_000_000));
+	}
+
+	#[test]
+	fn backoff_resets_after_stable_connection() {
+		let mut backoff = backoff(0.0);
+		backoff.note_disconnected(0);
+		backoff.note_disconnected(0);
+
+		// dropped too early to count as stable
+		backoff.note_connected(0);
+		assert!(!backoff.is_stable(499_000_000));
+		assert_eq!(backoff.note_disconnected(499_000_000), 400);
+		assert!(!backoff.is_stable(2000_000_000));
+
+		backoff.note_connected(1000_000_000);
+		assert!(backoff.is_stable(1500_000_000));
+		assert_eq!(backoff.note_disconnected(1500_000_000), 100);
+		assert_eq!(backoff.failures(), 1);
+	}
+
+	#[test]
+	fn backoff_jitter_is_bounded() {
+		let mut backoff = backoff(0.5);
+		let first = backoff.note_disconnected(0);
+		assert!(first >= 50 && first <= 150);
+		for _ in 0..10 {
+			assert!(backoff.note_disconnected(0) <= 1000);
+		}
+		assert!(backoff.note_disconnected(0) >= 500);
+	}
+
+	#[test]
+	fn payload_split_across_reads_is_reassembled() {
+		let (mut sender, mut receiver) = encrypted_pair();
+		let payload: Vec<u8> = (0..100u8).collect();
+		let first = encrypted_frame(&mut sender, &payload);
+		let second = encrypted_frame(&mut sender, &[0x11, 42]);
+		assert_eq!(first.len(), 32 + 112 + 16);
+
+		// header and the start of the payload in one segment
+		assert!(deliver(&mut sender, &mut receiver, &first[0..40], 8).is_none());
+		assert_eq!(receiver.read_status(), ReadStatus::ReadingPayload { protocol: 0, payload_len: 100, remaining: 92 });
+		// payload complete, MAC missing one byte
+		assert!(deliver(&mut sender, &mut receiver, &first[40..159], 127).is_none());
+		assert_eq!(receiver.read_status(), ReadStatus::ReadingPayload { protocol: 0, payload_len: 100, remaining: 0 });
+
+		// last byte of the frame together with the start of the next one
+		let mut tail = first[159..].to_vec();
+		tail.extend_from_slice(&second[0..10]);
+		let packet = deliver(&mut sender, &mut receiver, &tail, 0).expect("Packet is complete");
+		assert_eq!(packet.data, payload);
+
+		// bytes of the next frame received along with the previous one are kept
+		let packet = deliver(&mut sender, &mut receiver, &second[10..], 0).expect("Packet is complete");
+		assert_eq!(packet.data, vec![0x11, 42]);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn connection_reregister_closed_socket() {
+		use std::str::FromStr;
+		use std::net::SocketAddr;
+		use std::os::unix::io::AsRawFd;
+		use mio::{EventLoop, Handler, Token};
+		use mio::tcp::{TcpListener, TcpStream};
+
+		struct NullHandler;
+		impl Handler for NullHandler {
+			type Timeout = ();
+			type Message = ();
+		}
+
+		let listener = TcpListener::bind(&SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+		let socket = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+		let connection = Connection::new(0, socket, Arc::new(NetworkStats::new()));
+		let mut event_loop = EventLoop::<NullHandler>::new().unwrap();
+
+		assert!(connection.register_socket(Token(1), &mut event_loop).is_ok());
+		assert!(connection.update_socket(Token(1), &mut event_loop).is_ok());
+		assert!(!connection.is_socket_closed());
+
+		unsafe { ::libc::close(connection.socket.as_raw_fd()); }
+		match connection.update_socket(Token(1), &mut event_loop) {
+			Err(NetworkError::SocketClosed) => (),
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert!(connection.is_socket_closed());
+		// the caller has been told to drop the connection, further updates don't fail again
+		assert!(connection.update_socket(Token(1), &mut event_loop).is_ok());
+
+		// the descriptor is closed already
+		::std::mem::forget(connection);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn connection_makes_progress_in_every_poll_mode() {
+		use std::str::FromStr;
+		use std::net::SocketAddr;
+		use mio::{EventLoop, Handler, Token, TryRead, TryWrite};
+		use mio::tcp::{TcpListener, TcpStream};
+
+		struct EventRecorder {
+			events: EventSet,
+		}
+
+		impl Handler for EventRecorder {
+			type Timeout = ();
+			type Message = ();
+
+			fn ready(&mut self, _: &mut EventLoop<Self>, _: Token, events: EventSet) {
+				self.events = self.events | events;
+			}
+		}
+
+		/// Events reported by a single turn of the event loop.
+		fn poll(event_loop: &mut EventLoop<EventRecorder>, timeout_ms: usize) -> EventSet {
+			let mut recorder = EventRecorder { events: EventSet::none() };
+			event_loop.run_once(&mut recorder, Some(timeout_ms)).unwrap();
+			recorder.events
+		}
+
+		fn wait_for(event_loop: &mut EventLoop<EventRecorder>, expected: EventSet) {
+			for _ in 0..200 {
+				let events = poll(event_loop, 10);
+				if (events & expected) == expected {
+					return;
+				}
+			}
+			panic!("Expected events were not reported: {:?}", expected);
+		}
+
+		for mode in vec![PollMode::Edge, PollMode::Oneshot, PollMode::Level] {
+			let listener = TcpListener::bind(&SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+			let socket = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+			let mut peer = None;
+			for _ in 0..200 {
+				peer = listener.accept().unwrap().map(|(socket, _)| socket);
+				if peer.is_some() {
+					break;
+				}
+				::std::thread::sleep(::std::time::Duration::from_millis(5));
+			}
+			let mut peer = peer.expect("Loopback connection is accepted");
+
+			let mut connection = Connection::new(0, socket, Arc::new(NetworkStats::new()));
+			connection.set_poll_mode(mode);
+			let mut event_loop = EventLoop::<EventRecorder>::new().unwrap();
+			connection.register_socket(Token(1), &mut event_loop).unwrap();
+
+			// write
+			connection.send(&test_io(), vec![1, 2, 3]);
+			connection.update_socket(Token(1), &mut event_loop).unwrap();
+			wait_for(&mut event_loop, EventSet::writable());
+			assert_eq!(connection.writable(&test_io()).unwrap(), WriteStatus::Complete);
+			connection.update_socket(Token(1), &mut event_loop).unwrap();
+			let mut received = Vec::new();
+			for _ in 0..200 {
+				let mut buf = [0u8; 16];
+				if let Some(read) = peer.try_read(&mut buf).unwrap() {
+					received.extend_from_slice(&buf[0..read]);
+				}
+				if received.len() == 3 {
+					break;
+				}
+				::std::thread::sleep(::std::time::Duration::from_millis(5));
+			}
+			assert_eq!(received, vec![1, 2, 3]);
+
+			// read
+			assert_eq!(peer.try_write(&[4, 5, 6]).unwrap(), Some(3));
+			wait_for(&mut event_loop, EventSet::readable());
+			// unread data is reported again only in level-triggered mode
+			assert_eq!(poll(&mut event_loop, 50).is_readable(), mode == PollMode::Level);
+			if mode.needs_reregister() {
+				connection.update_socket(Token(1), &mut event_loop).unwrap();
+			}
+			connection.expect(3);
+			assert_eq!(connection.readable().unwrap(), Some(vec![4, 5, 6]));
+		}
+	}
 }
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use bytes::Bytes;
-use std::net::SocketAddr;
+use std::net::{SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr};
 use std::collections::{HashSet, HashMap, BTreeMap, VecDeque};
 use std::mem;
 use std::default::Default;
@@ -28,6 +28,7 @@ use crypto::*;
 use rlp::*;
 use network::node_table::*;
 use network::error::NetworkError;
+use network::ip_utils::with_port;
 use io::StreamToken;
 
 use network::PROTOCOL_VERSION;
@@ -46,6 +47,12 @@ const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
 
 const PING_TIMEOUT_MS: u64 = 300;
+/// Number of distinct source subnets that must report the same address for us before it is trusted as our public address.
+const OBSERVED_ADDRESS_MIN_REPORTS: usize = 4;
+/// Maximal number of source subnets whose reports are kept. The oldest report is evicted to make room for a new one.
+const MAX_OBSERVED_ADDRESS_REPORTS: usize = 256;
+/// Time in nanoseconds after which a report of our address is dropped.
+const OBSERVED_ADDRESS_EXPIRY_NS: u64 = 60 * 60 * 1000_000_000;
 
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
@@ -76,6 +83,29 @@ impl NodeBucket {
 	}
 }
 
+/// Our address as reported in a `Pong` packet.
+struct ObservedReport {
+	/// Reported address with the port cleared.
+	address: SocketAddr,
+	/// Time of the report, in nanoseconds.
+	time_ns: u64,
+}
+
+/// Subnet a report came from: /24 for IPv4 and /64 for IPv6 senders.
+/// Reports from a single subnet count once, so that an attacker can't set our address with a few node ids or hosts.
+fn source_subnet(address: &SocketAddr) -> IpAddr {
+	match *address {
+		SocketAddr::V4(ref a) => {
+			let o = a.ip().octets();
+			IpAddr::V4(Ipv4Addr::new(o[0], o[1], o[2], 0))
+		},
+		SocketAddr::V6(ref a) => {
+			let s = a.ip().segments();
+			IpAddr::V6(Ipv6Addr::new(s[0], s[1], s[2], s[3], 0, 0, 0, 0))
+		},
+	}
+}
+
 struct Datagramm {
 	payload: Bytes,
 	address: SocketAddr,
@@ -93,6 +123,8 @@ pub struct Discovery {
 	node_buckets: Vec<NodeBucket>,
 	send_queue: VecDeque<Datagramm>,
 	check_timestamps: bool,
+	/// Our address as last reported in `Pong` packets by each source subnet.
+	observed_addresses: HashMap<IpAddr, ObservedReport>,
 }
 
 pub struct TableUpdates {
@@ -115,7 +147,51 @@ impl Discovery {
 			udp_socket: socket,
 			send_queue: VecDeque::new(),
 			check_timestamps: true,
+			observed_addresses: HashMap::new(),
+		}
+	}
+
+	/// Update the endpoint advertised in outgoing `Ping` packets.
+	pub fn set_public_endpoint(&mut self, public: NodeEndpoint) {
+		self.public_endpoint = public;
+	}
+
+	/// Returns our global IP address as seen by other nodes, if enough source subnets agree on it.
+	/// The port of the returned address is zero.
+	pub fn observed_address(&self) -> Option<SocketAddr> {
+		let mut reports: HashMap<SocketAddr, usize> = HashMap::new();
+		for report in self.observed_addresses.values() {
+			*reports.entry(report.address.clone()).or_insert(0) += 1;
 		}
+		reports.into_iter()
+			.filter(|&(_, count)| count >= OBSERVED_ADDRESS_MIN_REPORTS)
+			.max_by_key(|&(_, count)| count)
+			.map(|(address, _)| address)
+	}
+
+	fn note_observed_address(&mut self, from: &SocketAddr, endpoint: &NodeEndpoint, now_ns: u64) {
+		if !endpoint.is_valid() || !endpoint.is_global() {
+			return;
+		}
+		let expired: Vec<IpAddr> = self.observed_addresses.iter()
+			.filter(|&(_, report)| now_ns.saturating_sub(report.time_ns) > OBSERVED_ADDRESS_EXPIRY_NS)
+			.map(|(subnet, _)| subnet.clone())
+			.collect();
+		for subnet in expired {
+			self.observed_addresses.remove(&subnet);
+		}
+		let subnet = source_subnet(from);
+		if !self.observed_addresses.contains_key(&subnet) && self.observed_addresses.len() >= MAX_OBSERVED_ADDRESS_REPORTS {
+			let oldest = self.observed_addresses.iter().min_by_key(|&(_, report)| report.time_ns).map(|(subnet, _)| subnet.clone());
+			if let Some(oldest) = oldest {
+				self.observed_addresses.remove(&oldest);
+			}
+		}
+		// A subnet only counts towards the address it reported last.
+		self.observed_addresses.insert(subnet, ObservedReport {
+			address: with_port(&endpoint.address, 0),
+			time_ns: now_ns,
+		});
 	}
 
 	/// Add a new node to discovery table. Pings the node.
@@ -390,6 +466,7 @@ impl Discovery {
 		let dest = try!(NodeEndpoint::from_rlp(&try!(rlp.at(0))));
 		let timestamp: u64 = try!(rlp.val_at(2));
 		try!(self.check_timestamp(timestamp));
+		self.note_observed_address(from, &dest, time::precise_time_ns());
 		let mut entry = NodeEntry { id: node.clone(), endpoint: dest };
 		if !entry.endpoint.is_valid() {
 			debug!(target: "discovery", "Bad address: {:?}", entry);
@@ -644,4 +721,51 @@ mod tests {
 		assert!(discovery.on_packet(&packet, from.clone()).is_ok());
 	}
 
+	#[test]
+	fn observed_address_needs_agreeing_subnets() {
+		let key = KeyPair::create().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40449").unwrap(), udp_port: 40449 };
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0);
+		let seen = NodeEndpoint { address: SocketAddr::from_str("1.2.3.4:40449").unwrap(), udp_port: 40449 };
+		let other = NodeEndpoint { address: SocketAddr::from_str("5.6.7.8:40449").unwrap(), udp_port: 40449 };
+		let from = |ip: &str| SocketAddr::from_str(&format!("{}:30303", ip)).unwrap();
+
+		discovery.note_observed_address(&from("10.0.1.1"), &seen, 0);
+		discovery.note_observed_address(&from("10.0.2.1"), &seen, 0);
+		// Reporting a local address does not count.
+		discovery.note_observed_address(&from("10.0.3.1"), &ep, 0);
+		// Hosts in one subnet count once.
+		discovery.note_observed_address(&from("10.0.2.2"), &seen, 0);
+		discovery.note_observed_address(&from("10.0.2.3"), &seen, 0);
+		assert_eq!(discovery.observed_address(), None);
+
+		discovery.note_observed_address(&from("10.0.3.1"), &seen, 0);
+		discovery.note_observed_address(&from("10.0.4.1"), &seen, 0);
+		assert_eq!(discovery.observed_address(), Some(SocketAddr::from_str("1.2.3.4:0").unwrap()));
+		// A subnet changing its report withdraws the previous one.
+		discovery.note_observed_address(&from("10.0.1.9"), &other, 0);
+		assert_eq!(discovery.observed_address(), None);
+	}
+
+	#[test]
+	fn observed_address_reports_are_bounded() {
+		let key = KeyPair::create().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40449").unwrap(), udp_port: 40449 };
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0);
+		let seen = NodeEndpoint { address: SocketAddr::from_str("1.2.3.4:40449").unwrap(), udp_port: 40449 };
+
+		for i in 0..(super::MAX_OBSERVED_ADDRESS_REPORTS + 10) {
+			let from = SocketAddr::from_str(&format!("10.{}.{}.1:30303", i / 256, i % 256)).unwrap();
+			discovery.note_observed_address(&from, &seen, i as u64);
+		}
+		assert_eq!(discovery.observed_addresses.len(), super::MAX_OBSERVED_ADDRESS_REPORTS);
+		// The oldest reports were evicted.
+		assert!(!discovery.observed_addresses.contains_key(&super::source_subnet(&SocketAddr::from_str("10.0.0.1:30303").unwrap())));
+
+		// Stale reports are dropped on the next report.
+		let from = SocketAddr::from_str("11.0.0.1:30303").unwrap();
+		discovery.note_observed_address(&from, &seen, super::OBSERVED_ADDRESS_EXPIRY_NS + 1000);
+		assert_eq!(discovery.observed_addresses.len(), 1);
+		assert_eq!(discovery.observed_address(), None);
+	}
 }
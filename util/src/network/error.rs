@@ -86,6 +86,14 @@ impl fmt::Display for DisconnectReason {
 pub enum NetworkError {
 	/// Authentication error.
 	Auth,
+	/// Frame MAC does not match the expected value.
+	AuthMacMismatch,
+	/// Frame exceeds maximum allowed size.
+	FrameTooLarge,
+	/// ECDH key agreement failed.
+	EcdhFailed,
+	/// Frame header or payload could not be decoded.
+	DecodeError,
 	/// Unrecognised protocol.
 	BadProtocol,
 	/// Message expired.
@@ -94,6 +102,12 @@ pub enum NetworkError {
 	PeerNotFound,
 	/// Peer is diconnected.
 	Disconnect(DisconnectReason),
+	/// Connection was reset by the remote peer.
+	ConnectionReset,
+	/// Connection was closed by the remote peer.
+	ConnectionClosed,
+	/// Socket is closed or no longer known to the event loop, so it can't be registered.
+	SocketClosed,
 	/// Socket IO error.
 	Io(IoError),
 }
@@ -104,10 +118,17 @@ impl fmt::Display for NetworkError {
 
 		let msg = match *self {
 			Auth => "Authentication failure".into(),
+			AuthMacMismatch => "Frame MAC mismatch".into(),
+			FrameTooLarge => "Frame too large".into(),
+			EcdhFailed => "ECDH key agreement failure".into(),
+			DecodeError => "Frame decoding failure".into(),
 			BadProtocol => "Bad protocol".into(),
 			Expired => "Expired message".into(),
 			PeerNotFound => "Peer not found".into(),
 			Disconnect(ref reason) => format!("Peer disconnected: {}", reason),
+			ConnectionReset => "Connection reset by peer".into(),
+			ConnectionClosed => "Connection closed by peer".into(),
+			SocketClosed => "Socket closed".into(),
 			Io(ref err) => format!("Socket I/O error: {}", err),
 		};
 
@@ -117,7 +138,7 @@ impl fmt::Display for NetworkError {
 
 impl From<DecoderError> for NetworkError {
 	fn from(_err: DecoderError) -> NetworkError {
-		NetworkError::Auth
+		NetworkError::DecodeError
 	}
 }
 
@@ -143,7 +164,7 @@ fn test_errors() {
 	assert_eq!(DisconnectReason::Unknown, r);
 
 	match <NetworkError as From<DecoderError>>::from(DecoderError::RlpIsTooBig) {
-		NetworkError::Auth => {},
+		NetworkError::DecodeError => {},
 		_ => panic!("Unexpeceted error"),
 	}
 
@@ -77,7 +77,6 @@ pub struct Handshake {
 
 const V4_AUTH_PACKET_SIZE: usize = 307;
 const V4_ACK_PACKET_SIZE: usize = 210;
-const HANDSHAKE_TIMEOUT: u64 = 5000;
 const PROTOCOL_VERSION: u64 = 4;
 // Amount of bytes added when encrypting with encryptECIES.
 const ECIES_OVERHEAD: usize = 113;
@@ -109,7 +108,8 @@ impl Handshake {
 	/// Start a handhsake
 	pub fn start<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo, originated: bool) -> Result<(), UtilError> where Message: Send + Clone{
 		self.originated = originated;
-		io.register_timer(self.connection.token, HANDSHAKE_TIMEOUT).ok();
+		// Drop peers which connect but never complete auth/ack.
+		io.register_timer(self.connection.token, host.handshake_timeout()).ok();
 		if originated {
 			try!(self.write_auth(io, host.secret(), host.id()));
 		}
@@ -524,5 +524,42 @@ mod test {
 		assert_eq!(h.state, super::HandshakeState::StartSession);
 		check_ack(&h, 57);
 	}
+
+	#[cfg(unix)]
+	#[test]
+	fn encrypted_connection_fails_on_bad_ephemeral() {
+		use std::os::unix::io::AsRawFd;
+		use mio::{EventLoop, Handler, Token};
+		use mio::tcp::TcpListener;
+		use error::UtilError;
+		use network::error::NetworkError;
+		use network::connection::EncryptedConnection;
+		use network::cipher::CipherBackend;
+
+		struct NullHandler;
+		impl Handler for NullHandler {
+			type Timeout = ();
+			type Message = ();
+		}
+
+		let listener = TcpListener::bind(&SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+		let socket = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+		let mut h = Handshake::new(0, None, socket, &H256::new(), Arc::new(NetworkStats::new())).unwrap();
+		let mut event_loop = EventLoop::<NullHandler>::new().unwrap();
+		h.connection.register_socket(Token(1), &mut event_loop).unwrap();
+		let fd = h.connection.socket.as_raw_fd();
+
+		// Zero public key is not a valid curve point.
+		h.remote_ephemeral = Public::new();
+		match EncryptedConnection::new(&mut h, CipherBackend::Safe) {
+			Err(UtilError::Network(NetworkError::EcdhFailed)) => {},
+			Err(e) => panic!("Unexpected error: {:?}", e),
+			Ok(_) => panic!("Connection created with invalid ephemeral key"),
+		}
+		// Handshake still owns the registered socket, so the host can deregister and close it.
+		assert_eq!(h.connection.socket.as_raw_fd(), fd);
+		assert!(h.connection.update_socket(Token(1), &mut event_loop).is_ok());
+		assert!(!h.connection.is_socket_closed());
+	}
 }
 
@@ -25,6 +25,7 @@ use std::path::{Path, PathBuf};
 use std::io::{Read, Write};
 use std::default::Default;
 use std::fs;
+use std::time::Duration;
 use mio::*;
 use mio::tcp::*;
 use hash::*;
@@ -33,6 +34,8 @@ use crypto::*;
 use sha3::Hashable;
 use rlp::*;
 use network::session::{Session, SessionData};
+use network::connection::{BackoffConfig, ConnectionObserver, KeepaliveConfig, PollMode, ReconnectBackoff, RekeyPolicy, set_keepalive};
+use network::cipher::CipherBackend;
 use error::*;
 use io::*;
 use network::{NetworkProtocolHandler, NonReservedPeerMode, PROTOCOL_VERSION};
@@ -40,7 +43,7 @@ use network::node_table::*;
 use network::stats::NetworkStats;
 use network::error::{NetworkError, DisconnectReason};
 use network::discovery::{Discovery, TableUpdates, NodeEntry};
-use network::ip_utils::{map_external_address, select_public_address};
+use network::ip_utils::{map_external_address, select_public_address, with_port};
 use path::restrict_permissions_owner;
 
 type Slab<T> = ::slab::Slab<T, usize>;
@@ -49,6 +52,16 @@ const MAX_SESSIONS: usize = 1024 + MAX_HANDSHAKES;
 const MAX_HANDSHAKES: usize = 80;
 const MAX_HANDSHAKES_PER_ROUND: usize = 32;
 const MAINTENANCE_TIMEOUT: u64 = 1000;
+/// Time in milliseconds allowed for writing out queued data on shutdown.
+const SHUTDOWN_DRAIN_TIMEOUT: u64 = 1000;
+/// Interval in milliseconds between attempts to write out queued data on shutdown.
+const SHUTDOWN_DRAIN_POLL: u64 = 10;
+/// Time in milliseconds during which a removed peer is not dialled again.
+const REMOVED_PEER_COOLDOWN: u64 = 5 * 60 * 1000;
+/// Default time in milliseconds allowed for completing the `RLPx` handshake.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: u64 = 5000;
+/// Default time in milliseconds queued data may wait for the peer to read it.
+pub const DEFAULT_WRITE_TIMEOUT: u64 = 60000;
 
 #[derive(Debug, Clone)]
 /// Network service configuration
@@ -75,6 +88,27 @@ pub struct NetworkConfiguration {
 	pub reserved_nodes: Vec<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
+	/// Time in milliseconds allowed for the remote peer to complete the `RLPx` handshake.
+	pub handshake_timeout: u64,
+	/// Time in milliseconds after which a peer that does not read queued data is dropped.
+	pub write_timeout: u64,
+	/// TCP keepalive settings for peer connections. Keepalive is disabled if `None`.
+	pub tcp_keepalive: Option<KeepaliveConfig>,
+	/// Periodically switch encrypted connections to fresh keys. Only used with peers that support it.
+	pub rekey: Option<RekeyPolicy>,
+	/// AES implementation used for encrypted connections.
+	pub cipher: CipherBackend,
+	/// How peer sockets are registered with the event loop.
+	pub poll_mode: PollMode,
+	/// Backoff of reconnect attempts to peers we failed to connect to or which dropped the connection.
+	pub reconnect_backoff: BackoffConfig,
+	/// Time window in microseconds within which packets sent to a peer are merged into a single write.
+	/// Zero disables write coalescing.
+	pub write_coalesce_window_us: u64,
+	/// Reason sent in the `Disconnect` packet to a peer dropped for not completing a frame in time.
+	pub idle_disconnect_reason: DisconnectReason,
+	/// Keepalive ping interval in seconds for specific protocols. Other protocols use the default interval.
+	pub ping_intervals: HashMap<ProtocolId, u64>,
 }
 
 impl Default for NetworkConfiguration {
@@ -98,6 +132,16 @@ impl NetworkConfiguration {
 			ideal_peers: 25,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+			write_timeout: DEFAULT_WRITE_TIMEOUT,
+			tcp_keepalive: None,
+			rekey: None,
+			cipher: CipherBackend::Safe,
+			poll_mode: PollMode::Edge,
+			reconnect_backoff: BackoffConfig::default(),
+			write_coalesce_window_us: 0,
+			idle_disconnect_reason: DisconnectReason::PingTimeout,
+			ping_intervals: HashMap::new(),
 		}
 	}
 
@@ -125,6 +169,7 @@ const DISCOVERY_REFRESH: usize = SYS_TIMER + 4;
 const DISCOVERY_ROUND: usize = SYS_TIMER + 5;
 const INIT_PUBLIC: usize = SYS_TIMER + 6;
 const NODE_TABLE: usize = SYS_TIMER + 7;
+const SHUTDOWN_DRAIN: usize = SYS_TIMER + 8;
 const FIRST_SESSION: usize = 0;
 const LAST_SESSION: usize = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: usize = LAST_SESSION + 256;
@@ -299,6 +344,9 @@ pub struct HostInfo {
 	pub local_endpoint: NodeEndpoint,
 	/// Public address + discovery port
 	pub public_endpoint: Option<NodeEndpoint>,
+	/// Public endpoint is a local interface address used in lack of anything better.
+	/// It is replaced by the address other nodes report seeing once discovery learns it.
+	pub public_endpoint_fallback: bool,
 }
 
 impl HostInfo {
@@ -312,6 +360,36 @@ impl HostInfo {
 		self.keys.secret()
 	}
 
+	/// Returns time in milliseconds allowed for the remote peer to complete the handshake.
+	pub fn handshake_timeout(&self) -> u64 {
+		self.config.handshake_timeout
+	}
+
+	/// Returns keepalive ping interval in seconds configured for the protocol, if any.
+	pub fn ping_interval(&self, protocol: &str) -> Option<u64> {
+		self.config.ping_intervals.get(protocol).cloned()
+	}
+
+	/// Returns connection re-keying thresholds, if re-keying is enabled.
+	pub fn rekey_policy(&self) -> Option<&RekeyPolicy> {
+		self.config.rekey.as_ref()
+	}
+
+	/// Returns AES implementation for encrypted connections.
+	pub fn cipher_backend(&self) -> CipherBackend {
+		self.config.cipher
+	}
+
+	/// Returns how peer sockets are registered with the event loop.
+	pub fn poll_mode(&self) -> PollMode {
+		self.config.poll_mode
+	}
+
+	/// Returns the write coalescing window in microseconds.
+	pub fn write_coalesce_window_us(&self) -> u64 {
+		self.config.write_coalesce_window_us
+	}
+
 	/// Increments and returns connection nonce.
 	pub fn next_nonce(&mut self) -> H256 {
 		self.nonce = self.nonce.sha3();
@@ -339,8 +417,17 @@ pub struct Host<Message> where Message: Send + Sync + Clone {
 	timer_counter: RwLock<usize>,
 	stats: Arc<NetworkStats>,
 	reserved_nodes: RwLock<HashSet<NodeId>>,
+	/// Nodes removed on request, with the time in ns until which they are not dialled.
+	removed_nodes: RwLock<HashMap<NodeId, u64>>,
+	/// Reconnect backoff of nodes we dialled.
+	backoff: RwLock<HashMap<NodeId, ReconnectBackoff>>,
 	num_sessions: AtomicUsize,
 	stopping: AtomicBool,
+	/// Sessions to close once their queued data is written out, and the deadline for that in ns.
+	shutdown_drain: Mutex<Option<(Vec<StreamToken>, u64)>>,
+	/// Set once the shutdown drain is over.
+	stopped: (Mutex<bool>, Condvar),
+	observer: RwLock<Option<Arc<ConnectionObserver>>>,
 }
 
 impl<Message> Host<Message> where Message: Send + Sync + Clone {
@@ -383,6 +470,7 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 				client_version: version(),
 				capabilities: Vec::new(),
 				public_endpoint: None,
+				public_endpoint_fallback: false,
 				local_endpoint: local_endpoint,
 			}),
 			discovery: Mutex::new(None),
@@ -394,8 +482,13 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			timer_counter: RwLock::new(USER_TIMER),
 			stats: stats,
 			reserved_nodes: RwLock::new(HashSet::new()),
+			removed_nodes: RwLock::new(HashMap::new()),
+			backoff: RwLock::new(HashMap::new()),
 			num_sessions: AtomicUsize::new(0),
 			stopping: AtomicBool::new(false),
+			shutdown_drain: Mutex::new(None),
+			stopped: (Mutex::new(false), Condvar::new()),
+			observer: RwLock::new(None),
 		};
 
 		for n in boot_nodes {
@@ -474,6 +567,39 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		Ok(())
 	}
 
+	/// Disconnect the peer given by its node id or enode URL. Returns `false` if there is no session with the peer.
+	/// Unless reserved, the node is not dialled again for `REMOVED_PEER_COOLDOWN` milliseconds.
+	pub fn remove_peer(&self, peer: &str, io: &IoContext<NetworkIoMessage<Message>>) -> Result<bool, UtilError> {
+		let id = if peer.starts_with("enode://") {
+			try!(Node::from_str(peer)).id
+		} else {
+			try!(NodeId::from_str(peer))
+		};
+		let mut token = None;
+		for e in self.sessions.read().unwrap().iter() {
+			let mut s = e.lock().unwrap();
+			if s.id() == Some(&id) {
+				s.disconnect(io, DisconnectReason::DisconnectRequested);
+				token = Some(s.token());
+				break;
+			}
+		}
+		match token {
+			Some(token) => {
+				trace!(target: "network", "Removing peer {}", token);
+				self.removed_nodes.write().unwrap().insert(id, ::time::precise_time_ns() + REMOVED_PEER_COOLDOWN * 1000_000);
+				self.kill_connection(token, io, false);
+				Ok(true)
+			},
+			None => Ok(false),
+		}
+	}
+
+	/// Set observer notified about events on all new connections.
+	pub fn set_connection_observer(&self, observer: Arc<ConnectionObserver>) {
+		*self.observer.write().unwrap() = Some(observer);
+	}
+
 	pub fn client_version() -> String {
 		version()
 	}
@@ -488,6 +614,9 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		r
 	}
 
+	/// Disconnect all peers. Queued data, including the disconnect packets, is given up to
+	/// `SHUTDOWN_DRAIN_TIMEOUT` milliseconds to be written out by a timer on the IO loop,
+	/// then the sockets are closed. Use `wait_stopped` to wait for that.
 	pub fn stop(&self, io: &IoContext<NetworkIoMessage<Message>>) -> Result<(), UtilError> {
 		self.stopping.store(true, AtomicOrdering::Release);
 		let mut to_kill = Vec::new();
@@ -496,12 +625,56 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			s.disconnect(io, DisconnectReason::ClientQuit);
 			to_kill.push(s.token());
 		}
+		let deadline = ::time::precise_time_ns() + SHUTDOWN_DRAIN_TIMEOUT * 1000_000;
+		*self.shutdown_drain.lock().unwrap() = Some((to_kill, deadline));
+		try!(io.register_timer(SHUTDOWN_DRAIN, SHUTDOWN_DRAIN_POLL));
+		Ok(())
+	}
+
+	/// Wait until the sockets are closed after `stop`, for a bit longer than the drain timeout at most.
+	/// Returns `false` if that didn't happen in time.
+	pub fn wait_stopped(&self) -> bool {
+		let &(ref lock, ref cvar) = &self.stopped;
+		let deadline = ::time::precise_time_ns() + 2 * SHUTDOWN_DRAIN_TIMEOUT * 1000_000;
+		let mut stopped = lock.lock().unwrap();
+		while !*stopped {
+			let now = ::time::precise_time_ns();
+			if now >= deadline {
+				return false;
+			}
+			stopped = cvar.wait_timeout(stopped, Duration::from_millis((deadline - now) / 1000_000 + 1)).unwrap().0;
+		}
+		true
+	}
+
+	/// Flush send queues of all sessions. Once they are empty or the drain timeout expired
+	/// the sessions are closed and the handler is unregistered.
+	fn drain_sessions(&self, io: &IoContext<NetworkIoMessage<Message>>) {
+		let deadline = match *self.shutdown_drain.lock().unwrap() {
+			Some((_, deadline)) => deadline,
+			None => return,
+		};
+		let queued = self.sessions.read().unwrap().iter().fold(0, |acc, e| acc + e.lock().unwrap().flush(io).unwrap_or(0));
+		if queued != 0 && ::time::precise_time_ns() <= deadline {
+			return;
+		}
+		if queued != 0 {
+			debug!(target: "network", "Shutting down with {} bytes not sent", queued);
+		}
+		let to_kill = self.shutdown_drain.lock().unwrap().take().map_or_else(Vec::new, |(to_kill, _)| to_kill);
 		for p in to_kill {
 			trace!(target: "network", "Disconnecting on shutdown: {}", p);
 			self.kill_connection(p, io, true);
 		}
-		try!(io.unregister_handler());
-		Ok(())
+		if let Err(e) = io.clear_timer(SHUTDOWN_DRAIN) {
+			debug!(target: "network", "Error clearing shutdown timer: {:?}", e);
+		}
+		if let Err(e) = io.unregister_handler() {
+			debug!(target: "network", "Error unregistering network handler: {:?}", e);
+		}
+		let &(ref lock, ref cvar) = &self.stopped;
+		*lock.lock().unwrap() = true;
+		cvar.notify_all();
 	}
 
 	fn init_public_interface(&self, io: &IoContext<NetworkIoMessage<Message>>) -> Result<(), UtilError> {
@@ -509,28 +682,21 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		if self.info.read().unwrap().public_endpoint.is_some() {
 			return Ok(());
 		}
-		let local_endpoint = self.info.read().unwrap().local_endpoint.clone();
-		let public_address = self.info.read().unwrap().config.public_address.clone();
-		let public_endpoint = match public_address {
-			None => {
-				let public_address = select_public_address(local_endpoint.address.port());
-				let public_endpoint = NodeEndpoint { address: public_address, udp_port: local_endpoint.udp_port };
-				if self.info.read().unwrap().config.nat_enabled {
-					match map_external_address(&local_endpoint) {
-						Some(endpoint) => {
-							info!("NAT mapped to external address {}", endpoint.address);
-							endpoint
-						},
-						None => public_endpoint
-					}
-				} else {
-					public_endpoint
-				}
-			}
-			Some(addr) => NodeEndpoint { address: addr, udp_port: local_endpoint.udp_port }
+		let (config, local_endpoint) = {
+			let info = self.info.read().unwrap();
+			(info.config.clone(), info.local_endpoint.clone())
 		};
+		// May block on gateway search, so the host info is not locked here.
+		let (public_endpoint, resolved) = resolve_public_endpoint(&config, &local_endpoint);
+		if !resolved {
+			warn!("Could not determine public address, advertising {}. Use --nat extip:IP if the node is behind NAT.", public_endpoint.address);
+		}
 
-		self.info.write().unwrap().public_endpoint = Some(public_endpoint.clone());
+		{
+			let mut info = self.info.write().unwrap();
+			info.public_endpoint = Some(public_endpoint.clone());
+			info.public_endpoint_fallback = !resolved;
+		}
 		info!("Public node URL: {}", self.external_url().unwrap());
 
 		// Initialize discovery.
@@ -556,6 +722,30 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		Ok(())
 	}
 
+	/// Replace a fallback public endpoint with our address as reported by other nodes.
+	fn update_observed_endpoint(&self) {
+		if !self.info.read().unwrap().public_endpoint_fallback {
+			return;
+		}
+		let mut discovery = self.discovery.lock().unwrap();
+		let discovery = match discovery.as_mut() {
+			Some(discovery) => discovery,
+			None => return,
+		};
+		if let Some(address) = discovery.observed_address() {
+			let endpoint = {
+				let mut info = self.info.write().unwrap();
+				let endpoint = NodeEndpoint { address: with_port(&address, info.local_endpoint.address.port()), udp_port: info.local_endpoint.udp_port };
+				info.public_endpoint = Some(endpoint.clone());
+				info.public_endpoint_fallback = false;
+				endpoint
+			};
+			info!("Public address reported by peers: {}", endpoint.address);
+			discovery.set_public_endpoint(endpoint);
+			info!("Public node URL: {}", self.external_url().unwrap());
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage<Message>>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
@@ -578,10 +768,18 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 	}
 
 	fn keep_alive(&self, io: &IoContext<NetworkIoMessage<Message>>) {
+		let write_timeout = self.info.read().unwrap().config.write_timeout;
 		let mut to_kill = Vec::new();
 		for e in self.sessions.write().unwrap().iter_mut() {
 			let mut s = e.lock().unwrap();
-			if !s.keep_alive(io) {
+			if s.is_socket_closed() {
+				debug!(target: "network", "Socket closed: {}", s.token());
+				to_kill.push(s.token());
+			} else if s.is_write_stalled(write_timeout) {
+				// The peer is not reading, so there is no point in queueing a disconnect packet.
+				debug!(target: "network", "Write timeout: {}", s.token());
+				to_kill.push(s.token());
+			} else if !s.keep_alive(io) {
 				s.disconnect(io, DisconnectReason::PingTimeout);
 				to_kill.push(s.token());
 			}
@@ -630,8 +828,27 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			Vec::new()
 		});
 
+		let removed_nodes: HashSet<NodeId> = {
+			let now = ::time::precise_time_ns();
+			let mut removed = self.removed_nodes.write().unwrap();
+			let active: HashMap<_, _> = removed.drain().filter(|&(_, until)| until > now).collect();
+			*removed = active;
+			removed.keys().cloned().collect()
+		};
+
+		let backed_off: HashSet<NodeId> = {
+			let now = ::time::precise_time_ns();
+			let mut backoff = self.backoff.write().unwrap();
+			// a stable connection starts over with a fresh backoff once it drops
+			let unstable: HashMap<_, _> = backoff.drain().filter(|&(_, ref b)| !b.is_stable(now)).collect();
+			*backoff = unstable;
+			backoff.iter().filter(|&(_, b)| !b.can_attempt(now)).map(|(id, _)| id.clone()).collect()
+		};
+
 		let mut started: usize = 0;
-		for id in nodes.filter(|ref id| !self.have_session(id) && !self.connecting_to(id))
+		for id in nodes.filter(|ref id| reserved_nodes.contains(id) || !removed_nodes.contains(id))
+			.filter(|ref id| !backed_off.contains(id))
+			.filter(|ref id| !self.have_session(id) && !self.connecting_to(id))
 			.take(min(MAX_HANDSHAKES_PER_ROUND, handshake_limit - handshake_count)) {
 			self.connect_peer(&id, io);
 			started += 1;
@@ -667,22 +884,42 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 				Ok(socket) => socket,
 				Err(e) => {
 					debug!(target: "network", "Can't connect to address {:?}: {:?}", address, e);
+					self.note_disconnected(id);
 					return;
 				}
 			}
 		};
 		if let Err(e) = self.create_connection(socket, Some(id), io) {
 			debug!(target: "network", "Can't create connection: {:?}", e);
+			self.note_disconnected(id);
 		}
 	}
 
+	/// Delay the next connection attempt to a dialled node which failed or dropped the connection.
+	fn note_disconnected(&self, id: &NodeId) {
+		let config = self.info.read().unwrap().config.reconnect_backoff.clone();
+		let mut backoff = self.backoff.write().unwrap();
+		let delay = backoff.entry(id.clone())
+			.or_insert_with(|| ReconnectBackoff::new(config))
+			.note_disconnected(::time::precise_time_ns());
+		trace!(target: "network", "Next connection attempt to {:?} in {}ms", id, delay);
+	}
+
 	#[cfg_attr(feature="dev", allow(block_in_if_condition_stmt))]
 	fn create_connection(&self, socket: TcpStream, id: Option<&NodeId>, io: &IoContext<NetworkIoMessage<Message>>) -> Result<(), UtilError> {
 		let nonce = self.info.write().unwrap().next_nonce();
+		if let Some(ref keepalive) = self.info.read().unwrap().config.tcp_keepalive {
+			set_keepalive(&socket, keepalive);
+		}
 		let mut sessions = self.sessions.write().unwrap();
 		let token = sessions.insert_with_opt(|token| {
 			match Session::new(io, socket, token, id, &nonce, self.stats.clone(), &self.info.read().unwrap()) {
-				Ok(s) => Some(Arc::new(Mutex::new(s))),
+				Ok(mut s) => {
+					if let Some(ref observer) = *self.observer.read().unwrap() {
+						s.set_observer(observer.clone());
+					}
+					Some(Arc::new(Mutex::new(s)))
+				},
 				Err(e) => {
 					debug!(target: "network", "Session create error: {:?}", e);
 					None
@@ -726,10 +963,16 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			}
 			if s.done() {
 				io.deregister_stream(token).unwrap_or_else(|e| debug!("Error deregistering stream: {:?}", e));
+			} else if self.info.read().unwrap().poll_mode().needs_reregister() {
+				io.update_registration(token).unwrap_or_else(|e| debug!(target: "network", "Token registration error: {:?}", e));
 			}
 		}
 	}
 
+	fn session_expired(&self, token: StreamToken) -> bool {
+		self.sessions.read().unwrap().get(token).map_or(false, |s| s.lock().unwrap().expired())
+	}
+
 	fn connection_closed(&self, token: TimerToken, io: &IoContext<NetworkIoMessage<Message>>) {
 		trace!(target: "network", "Connection closed: {}", token);
 		self.kill_connection(token, io, true);
@@ -745,7 +988,13 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			loop {
 				match s.readable(io, &self.info.read().unwrap()) {
 					Err(e) => {
-						trace!(target: "network", "Session read error: {}:{:?} ({:?}) {:?}", token, s.id(), s.remote_addr(), e);
+						match e {
+							UtilError::Network(NetworkError::ConnectionReset) =>
+								debug!(target: "network", "Session reset by peer: {}:{:?} ({:?})", token, s.id(), s.remote_addr()),
+							UtilError::Network(NetworkError::ConnectionClosed) =>
+								debug!(target: "network", "Session closed by peer: {}:{:?} ({:?})", token, s.id(), s.remote_addr()),
+							_ => trace!(target: "network", "Session read error: {}:{:?} ({:?}) {:?}", token, s.id(), s.remote_addr(), e),
+						}
 						if let UtilError::Network(NetworkError::Disconnect(DisconnectReason::IncompatibleProtocol)) = e {
 							if let Some(id) = s.id() {
 								self.nodes.write().unwrap().mark_as_useless(id);
@@ -756,6 +1005,11 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 					},
 					Ok(SessionData::Ready) => {
 						self.num_sessions.fetch_add(1, AtomicOrdering::SeqCst);
+						if s.info.originated {
+							if let Some(backoff) = self.backoff.write().unwrap().get_mut(s.id().unwrap()) {
+								backoff.note_connected(::time::precise_time_ns());
+							}
+						}
 						if !s.info.originated {
 							let session_count = self.session_count();
 							let reserved_nodes = self.reserved_nodes.read().unwrap();
@@ -805,6 +1059,8 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 		}
 		if kill {
 			self.kill_connection(token, io, true);
+		} else if session.is_some() && self.info.read().unwrap().poll_mode().needs_reregister() {
+			io.update_registration(token).unwrap_or_else(|e| debug!(target: "network", "Token registration error: {:?}", e));
 		}
 		let handlers = self.handlers.read().unwrap();
 		for p in ready_data {
@@ -821,13 +1077,20 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 	}
 
 	fn connection_timeout(&self, token: StreamToken, io: &IoContext<NetworkIoMessage<Message>>) {
-		trace!(target: "network", "Connection timeout: {}", token);
+		debug!(target: "network", "Connection timeout: {} (peer idle)", token);
+		let session = { self.sessions.read().unwrap().get(token).cloned() };
+		if let Some(session) = session {
+			// Let the peer know why it is dropped. Only sent once the handshake is complete.
+			let reason = self.info.read().unwrap().config.idle_disconnect_reason;
+			session.lock().unwrap().disconnect(io, reason);
+		}
 		self.kill_connection(token, io, true)
 	}
 
 	fn kill_connection(&self, token: StreamToken, io: &IoContext<NetworkIoMessage<Message>>, remote: bool) {
 		let mut to_disconnect: Vec<ProtocolId> = Vec::new();
 		let mut failure_id = None;
+		let mut originated = false;
 		let mut deregister = false;
 		let mut expired_session = None;
 		if let FIRST_SESSION ... LAST_SESSION = token {
@@ -846,6 +1109,7 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 					}
 					s.set_expired();
 					failure_id = s.id().cloned();
+					originated = s.info.originated;
 				}
 				deregister = remote || s.done();
 			}
@@ -854,6 +1118,9 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			if remote {
 				self.nodes.write().unwrap().note_failure(&id);
 			}
+			if originated {
+				self.note_disconnected(&id);
+			}
 		}
 		for p in to_disconnect {
 			let h = self.handlers.read().unwrap().get(p).unwrap().clone();
@@ -882,6 +1149,12 @@ impl<Message> Host<Message> where Message: Send + Sync + Clone {
 			trace!(target: "network", "Removed from node table: {}", i);
 			self.kill_connection(i, io, false);
 		}
+		{
+			let mut backoff = self.backoff.write().unwrap();
+			for id in &node_changes.removed {
+				backoff.remove(id);
+			}
+		}
 		self.nodes.write().unwrap().update(node_changes);
 	}
 }
@@ -897,7 +1170,19 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 	fn stream_hup(&self, io: &IoContext<NetworkIoMessage<Message>>, stream: StreamToken) {
 		trace!(target: "network", "Hup: {}", stream);
 		match stream {
-			FIRST_SESSION ... LAST_SESSION => self.connection_closed(stream, io),
+			FIRST_SESSION ... LAST_SESSION => {
+				// Peer may have sent data (e.g. a disconnect reason) right before hanging up. Process it first.
+				let was_expired = self.session_expired(stream);
+				if !self.stopping.load(AtomicOrdering::Acquire) {
+					self.session_readable(stream, io);
+				}
+				// Reading may have failed and killed the connection already.
+				if !was_expired && self.session_expired(stream) {
+					return;
+				}
+				debug!(target: "network", "Connection closed by peer: {}", stream);
+				self.connection_closed(stream, io)
+			},
 			_ => warn!(target: "network", "Unexpected hup"),
 		};
 	}
@@ -913,6 +1198,7 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 				if let Some(node_changes) = node_changes {
 					self.update_nodes(io, node_changes);
 				}
+				self.update_observed_endpoint();
 				io.update_registration(DISCOVERY).expect("Error updating discovery registration");
 			},
 			TCP_ACCEPT => self.accept(io),
@@ -935,6 +1221,10 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 	}
 
 	fn timeout(&self, io: &IoContext<NetworkIoMessage<Message>>, token: TimerToken) {
+		if token == SHUTDOWN_DRAIN {
+			self.drain_sessions(io);
+			return;
+		}
 		if self.stopping.load(AtomicOrdering::Acquire) {
 			return;
 		}
@@ -1038,7 +1328,10 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 			FIRST_SESSION ... LAST_SESSION => {
 				let session = { self.sessions.read().unwrap().get(stream).cloned() };
 				if let Some(session) = session {
-					session.lock().unwrap().register_socket(reg, event_loop).expect("Error registering socket");
+					if let Err(e) = session.lock().unwrap().register_socket(reg, event_loop) {
+						// Closed sockets are dropped by `keep_alive`.
+						debug!(target: "network", "Error registering socket {}: {:?}", stream, e);
+					}
 				}
 			}
 			DISCOVERY => self.discovery.lock().unwrap().as_ref().unwrap().register_socket(event_loop).expect("Error registering discovery socket"),
@@ -1066,7 +1359,10 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 			FIRST_SESSION ... LAST_SESSION => {
 				let connection = { self.sessions.read().unwrap().get(stream).cloned() };
 				if let Some(connection) = connection {
-					connection.lock().unwrap().update_socket(reg, event_loop).expect("Error updating socket");
+					if let Err(e) = connection.lock().unwrap().update_socket(reg, event_loop) {
+						// Closed sockets are dropped by `keep_alive`.
+						debug!(target: "network", "Error updating socket {}: {:?}", stream, e);
+					}
 				}
 			}
 			DISCOVERY => self.discovery.lock().unwrap().as_ref().unwrap().update_registration(event_loop).expect("Error reregistering discovery socket"),
@@ -1076,6 +1372,29 @@ impl<Message> IoHandler<NetworkIoMessage<Message>> for Host<Message> where Messa
 	}
 }
 
+/// Determine the endpoint to advertise to other nodes: the configured public address, or the UPnP mapping
+/// if NAT traversal is enabled. Falls back to the bind address or the address of a local interface, in which case
+/// `false` is returned unless that address is globally routable.
+fn resolve_public_endpoint(config: &NetworkConfiguration, local_endpoint: &NodeEndpoint) -> (NodeEndpoint, bool) {
+	if let Some(address) = config.public_address {
+		return (NodeEndpoint { address: address, udp_port: local_endpoint.udp_port }, true);
+	}
+	if config.nat_enabled {
+		if let Some(endpoint) = map_external_address(local_endpoint) {
+			info!("NAT mapped to external address {}", endpoint.address);
+			return (endpoint, true);
+		}
+	}
+	let address = if local_endpoint.is_valid() {
+		local_endpoint.address.clone()
+	} else {
+		select_public_address(local_endpoint.address.port())
+	};
+	let endpoint = NodeEndpoint { address: address, udp_port: local_endpoint.udp_port };
+	let global = endpoint.is_global();
+	(endpoint, global)
+}
+
 fn save_key(path: &Path, key: &Secret) {
 	let mut path_buf = PathBuf::from(path);
 	if let Err(e) = fs::create_dir_all(path_buf.as_path()) {
@@ -1145,3 +1464,54 @@ fn host_client_url() {
 	let host: Host<u32> = Host::new(config, Arc::new(NetworkStats::new())).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
+
+#[test]
+fn configured_public_address_overrides_bind_address() {
+	let mut config = NetworkConfiguration::new_local();
+	config.public_address = Some(SocketAddr::from_str("1.2.3.4:30303").unwrap());
+	let local = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:30304").unwrap(), udp_port: 30305 };
+	let (endpoint, resolved) = resolve_public_endpoint(&config, &local);
+	assert!(resolved);
+	assert_eq!(endpoint.address, SocketAddr::from_str("1.2.3.4:30303").unwrap());
+	assert_eq!(endpoint.udp_port, 30305);
+}
+
+#[test]
+fn unknown_public_address_falls_back_to_bind_address() {
+	let config = NetworkConfiguration::new_local();
+	let local = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:30304").unwrap(), udp_port: 30304 };
+	let (endpoint, resolved) = resolve_public_endpoint(&config, &local);
+	assert!(!resolved);
+	assert_eq!(endpoint.address, local.address);
+}
+
+#[test]
+fn idle_timeout_sends_configured_disconnect_reason() {
+	use network::connection::encrypted_pair;
+	use network::connection::PacketKind;
+	use network::session::test_session;
+
+	let mut config = NetworkConfiguration::new_local();
+	config.idle_disconnect_reason = DisconnectReason::TooManyPeers;
+	let host = Host::<i32>::new(config, Arc::new(NetworkStats::new())).unwrap();
+	let (connection, mut peer) = encrypted_pair();
+	let token = host.sessions.write().unwrap().insert_with_opt(|_| Some(Arc::new(Mutex::new(test_session(connection))))).unwrap();
+
+	// the frame receive timer of the session fires
+	let io = IoContext::new(IoChannel::disconnected(), 0);
+	host.timeout(&io, token);
+
+	let mut packet = None;
+	for _ in 0..200 {
+		packet = peer.readable(&IoContext::<i32>::new(IoChannel::disconnected(), 0)).unwrap();
+		if packet.is_some() {
+			break;
+		}
+		::std::thread::sleep(Duration::from_millis(5));
+	}
+	let packet = packet.expect("Disconnect packet is received before the connection is closed");
+	assert_eq!(packet.data[0], PacketKind::Disconnect.to_raw());
+	let reason: u32 = UntrustedRlp::new(&packet.data[1..]).val_at(0).unwrap();
+	assert_eq!(reason, DisconnectReason::TooManyPeers as u32);
+	assert!(host.sessions.read().unwrap().get(token).unwrap().lock().unwrap().expired());
+}
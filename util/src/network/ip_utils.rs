@@ -169,6 +169,14 @@ pub fn select_public_address(port: u16) -> SocketAddr {
 	SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port))
 }
 
+/// Returns `address` with the port replaced.
+pub fn with_port(address: &SocketAddr, port: u16) -> SocketAddr {
+	match *address {
+		SocketAddr::V4(a) => SocketAddr::V4(SocketAddrV4::new(a.ip().clone(), port)),
+		SocketAddr::V6(a) => SocketAddr::V6(SocketAddrV6::new(a.ip().clone(), port, a.flowinfo(), a.scope_id())),
+	}
+}
+
 pub fn map_external_address(local: &NodeEndpoint) -> Option<NodeEndpoint> {
 	if let SocketAddr::V4(ref local_addr) = local.address {
 		match search_gateway_from_timeout(local_addr.ip().clone(), Duration::new(5, 0)) {
@@ -67,6 +67,7 @@
 //! ```
 mod host;
 mod connection;
+mod cipher;
 mod handshake;
 mod session;
 mod discovery;
@@ -85,9 +86,11 @@ pub use network::host::NetworkContext;
 pub use network::service::NetworkService;
 pub use network::host::NetworkIoMessage;
 pub use network::host::NetworkIoMessage::User as UserMessage;
-pub use network::error::NetworkError;
-pub use network::host::NetworkConfiguration;
+pub use network::error::{NetworkError, DisconnectReason};
+pub use network::host::{NetworkConfiguration, DEFAULT_HANDSHAKE_TIMEOUT, DEFAULT_WRITE_TIMEOUT};
 pub use network::stats::NetworkStats;
+pub use network::cipher::{CipherBackend, FrameCipher, MacCipher};
+pub use network::connection::{BackoffConfig, ConnectionEvent, ConnectionObserver, KeepaliveConfig, PacketKind, PeerInfo, PollMode, ReadStatus, ReconnectBackoff, RekeyPolicy};
 
 use io::TimerToken;
 pub use network::node_table::is_valid_node_url;
@@ -21,6 +21,7 @@ use network::{NetworkProtocolHandler, NetworkConfiguration};
 use network::error::NetworkError;
 use network::host::{Host, NetworkIoMessage, ProtocolId};
 use network::stats::NetworkStats;
+use network::connection::ConnectionObserver;
 use io::*;
 
 /// IO Service with networking
@@ -32,6 +33,7 @@ pub struct NetworkService<Message> where Message: Send + Sync + Clone + 'static
 	stats: Arc<NetworkStats>,
 	panic_handler: Arc<PanicHandler>,
 	config: NetworkConfiguration,
+	observer: RwLock<Option<Arc<ConnectionObserver>>>,
 }
 
 impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'static {
@@ -50,6 +52,7 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 			panic_handler: panic_handler,
 			host: RwLock::new(None),
 			config: config,
+			observer: RwLock::new(None),
 		})
 	}
 
@@ -90,11 +93,20 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 		host.as_ref().map(|h| h.local_url())
 	}
 
+	/// Returns true if the network host is running and accepting incoming connections.
+	/// The host is only created once the listening socket is bound.
+	pub fn is_listening(&self) -> bool {
+		self.host.read().unwrap().is_some()
+	}
+
 	/// Start network IO
 	pub fn start(&self) -> Result<(), UtilError> {
 		let mut host = self.host.write().unwrap();
 		if host.is_none() {
 			let h = Arc::new(try!(Host::new(self.config.clone(), self.stats.clone())));
+			if let Some(ref observer) = *self.observer.read().unwrap() {
+				h.set_connection_observer(observer.clone());
+			}
 			try!(self.io_service.register_handler(h.clone()));
 			*host = Some(h);
 		}
@@ -103,12 +115,16 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 
 	/// Stop network IO
 	pub fn stop(&self) -> Result<(), UtilError> {
-		let mut host = self.host.write().unwrap();
-		if let Some(ref host) = *host {
+		// don't hold the lock while sessions drain, other users of the host would block on it
+		let host = self.host.read().unwrap().clone();
+		if let Some(host) = host {
 			let io = IoContext::new(self.io_service.channel(), 0); //TODO: take token id from host
 			try!(host.stop(&io));
+			if !host.wait_stopped() {
+				warn!(target: "network", "Timed out waiting for peer connections to close");
+			}
 		}
-		*host = None;
+		*self.host.write().unwrap() = None;
 		Ok(())
 	}
 
@@ -132,6 +148,26 @@ impl<Message> NetworkService<Message> where Message: Send + Sync + Clone + 'stat
 		}
 	}
 
+	/// Disconnect a peer given by its node id or enode URL. Returns `true` if the peer was connected.
+	pub fn remove_peer(&self, peer: &str) -> Result<bool, UtilError> {
+		let host = self.host.read().unwrap();
+		if let Some(ref host) = *host {
+			let io = IoContext::new(self.io_service.channel(), 0);
+			host.remove_peer(peer, &io)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Set observer notified about events on peer connections (authentication failures,
+	/// protocol errors, received packets). Applies to connections created after this call.
+	pub fn set_connection_observer(&self, observer: Arc<ConnectionObserver>) {
+		if let Some(ref host) = *self.host.read().unwrap() {
+			host.set_connection_observer(observer.clone());
+		}
+		*self.observer.write().unwrap() = Some(observer);
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: ::network::NonReservedPeerMode) {
 		let host = self.host.read().unwrap();
@@ -16,12 +16,13 @@
 
 use std::net::SocketAddr;
 use std::io;
+use std::cmp;
 use std::sync::*;
 use mio::*;
 use mio::tcp::*;
 use rlp::*;
 use hash::*;
-use network::connection::{EncryptedConnection, Packet, Connection};
+use network::connection::{EncryptedConnection, Packet, PacketKind, PACKET_USER, Connection, ConnectionObserver, ConnectionEvent, PeerInfo};
 use network::handshake::Handshake;
 use error::*;
 use io::{IoContext, StreamToken};
@@ -32,6 +33,7 @@ use network::stats::NetworkStats;
 use time;
 
 const PING_TIMEOUT_SEC: u64 = 30;
+/// Ping interval of protocols not configuring their own.
 const PING_INTERVAL_SEC: u64 = 30;
 
 /// Peer session over encrypted connection.
@@ -47,6 +49,8 @@ pub struct Session {
 	ping_time_ns: u64,
 	pong_time_ns: Option<u64>,
 	state: State,
+	/// Connection events observer.
+	observer: Option<Arc<ConnectionObserver>>,
 }
 
 enum State {
@@ -112,14 +116,9 @@ struct SessionCapabilityInfo {
 	pub id_offset: u8,
 }
 
-const PACKET_HELLO: u8 = 0x80;
-const PACKET_DISCONNECT: u8 = 0x01;
-const PACKET_PING: u8 = 0x02;
-const PACKET_PONG: u8 = 0x03;
-const PACKET_GET_PEERS: u8 = 0x04;
-const PACKET_PEERS: u8 = 0x05;
-const PACKET_USER: u8 = 0x10;
-const PACKET_LAST: u8 = 0x7f;
+/// Pseudo-capability advertised in `Hello` by peers supporting connection re-keying.
+const REKEY_CAPABILITY: &'static str = "rky";
+const REKEY_VERSION: u8 = 1;
 
 impl Session {
 	/// Create a new session out of comepleted handshake. This clones the handshake connection object
@@ -129,6 +128,8 @@ impl Session {
 		where Message: Send + Clone {
 		let originated = id.is_some();
 		let mut handshake = Handshake::new(token, id, socket, &nonce, stats).expect("Can't create handshake");
+		handshake.connection.set_poll_mode(host.poll_mode());
+		handshake.connection.set_coalesce_window(host.write_coalesce_window_us());
 		try!(handshake.start(io, host, originated));
 		Ok(Session {
 			state: State::Handshake(handshake),
@@ -144,13 +145,24 @@ impl Session {
 			ping_time_ns: 0,
 			pong_time_ns: None,
 			expired: false,
+			observer: None,
 		})
 	}
 
 	fn complete_handshake<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<(), UtilError> where Message: Send + Sync + Clone {
 		let connection = if let State::Handshake(ref mut h) = self.state {
+			let mut connection = match EncryptedConnection::new(h, host.cipher_backend()) {
+				Ok(c) => c,
+				Err(e) => {
+					h.expired = true;
+					return Err(e);
+				}
+			};
 			self.info.id = Some(h.id.clone());
-			try!(EncryptedConnection::new(h))
+			if let Some(ref observer) = self.observer {
+				connection.set_observer(observer.clone());
+			}
+			connection
 		} else {
 			panic!("Unexpected state");
 		};
@@ -167,6 +179,14 @@ impl Session {
 		}
 	}
 
+	/// Set observer notified about connection events.
+	pub fn set_observer(&mut self, observer: Arc<ConnectionObserver>) {
+		if let State::Session(ref mut c) = self.state {
+			c.set_observer(observer.clone());
+		}
+		self.observer = Some(observer);
+	}
+
 	/// Get id of the remote peer
 	pub fn id(&self) -> Option<&NodeId> {
 		self.info.id.as_ref()
@@ -195,6 +215,27 @@ impl Session {
 		self.expired() && !self.connection().is_sending()
 	}
 
+	/// Peer information negotiated in the `Hello` exchange.
+	pub fn peer_info(&self) -> Option<&PeerInfo> {
+		match self.state {
+			State::Session(ref c) => c.peer_info(),
+			State::Handshake(_) => None,
+		}
+	}
+
+	/// Time left in milliseconds until the connection idle timer fires, if set.
+	pub fn idle_timeout_remaining(&self) -> Option<u64> {
+		match self.state {
+			State::Session(ref c) => c.idle_timeout_remaining(),
+			State::Handshake(_) => None,
+		}
+	}
+
+	/// Check if queued data has not been written to the socket for more than `timeout_ms` milliseconds.
+	pub fn is_write_stalled(&self, timeout_ms: u64) -> bool {
+		self.connection().is_write_stalled(timeout_ms)
+	}
+
 	/// Get remote peer address
 	pub fn remote_addr(&self) -> io::Result<SocketAddr> {
 		self.connection().remote_addr()
@@ -207,6 +248,7 @@ impl Session {
 		}
 		let mut create_session = false;
 		let mut packet_data = None;
+		let mut mac_failed = false;
 		match self.state {
 			State::Handshake(ref mut h) => {
 				try!(h.readable(io, host));
@@ -215,14 +257,25 @@ impl Session {
 				}
 			}
 			State::Session(ref mut c) => {
-				match try!(c.readable(io)) {
-					data @ Some(_) => packet_data = data,
-					None => return Ok(SessionData::None)
+				match c.readable(io) {
+					Ok(data @ Some(_)) => packet_data = data,
+					Ok(None) => return Ok(SessionData::None),
+					Err(UtilError::Network(NetworkError::AuthMacMismatch)) => mac_failed = true,
+					Err(e) => return Err(e),
 				}
 			}
 		}
+		if mac_failed {
+			return Err(From::from(self.disconnect(io, DisconnectReason::BadProtocol)));
+		}
 		if let Some(data) = packet_data {
-			return Ok(try!(self.read_packet(io, data, host)));
+			let result = self.read_packet(io, data, host);
+			if let Err(UtilError::Network(NetworkError::BadProtocol)) = result {
+				if let Some(ref observer) = self.observer {
+					observer.on_event(self.token(), ConnectionEvent::ProtocolError);
+				}
+			}
+			return Ok(try!(result));
 		}
 		if create_session {
 			try!(self.complete_handshake(io, host));
@@ -259,6 +312,11 @@ impl Session {
 		Ok(())
 	}
 
+	/// Check if the event loop rejected the session socket as closed. The session should be dropped.
+	pub fn is_socket_closed(&self) -> bool {
+		self.connection().is_socket_closed()
+	}
+
 	/// Delete registration
 	pub fn deregister_socket<Host:Handler>(&self, event_loop: &mut EventLoop<Host>) -> Result<(), UtilError> {
 		try!(self.connection().deregister_socket(event_loop));
@@ -301,7 +359,11 @@ impl Session {
 			time::precise_time_ns() - self.ping_time_ns > PING_TIMEOUT_SEC * 1000_000_000
 		};
 
-		if !timed_out && time::precise_time_ns() - self.ping_time_ns > PING_INTERVAL_SEC * 1000_000_000 {
+		let interval = match self.state {
+			State::Session(ref c) => c.keepalive_interval().unwrap_or(PING_INTERVAL_SEC),
+			State::Handshake(_) => PING_INTERVAL_SEC,
+		};
+		if !timed_out && time::precise_time_ns() - self.ping_time_ns > interval * 1000_000_000 {
 			if let Err(e) = self.send_ping(io) {
 				debug!("Error sending ping message: {:?}", e);
 			}
@@ -318,17 +380,17 @@ impl Session {
 		if packet.data.len() < 2 {
 			return Err(From::from(NetworkError::BadProtocol));
 		}
-		let packet_id = packet.data[0];
-		if packet_id != PACKET_HELLO && packet_id != PACKET_DISCONNECT && !self.had_hello {
+		let packet_id = PacketKind::from_raw(packet.data[0]);
+		if packet_id != PacketKind::Hello && packet_id != PacketKind::Disconnect && !self.had_hello {
 			return Err(From::from(NetworkError::BadProtocol));
 		}
 		match packet_id {
-			PACKET_HELLO => {
+			PacketKind::Hello => {
 				let rlp = UntrustedRlp::new(&packet.data[1..]); //TODO: validate rlp expected size
 				try!(self.read_hello(io, &rlp, host));
 				Ok(SessionData::Ready)
 			},
-			PACKET_DISCONNECT => {
+			PacketKind::Disconnect => {
 				let rlp = UntrustedRlp::new(&packet.data[1..]);
 				let reason: u8 = try!(rlp.val_at(0));
 				if self.had_hello {
@@ -336,18 +398,25 @@ impl Session {
 				}
 				Err(From::from(NetworkError::Disconnect(DisconnectReason::from_u8(reason))))
 			}
-			PACKET_PING => {
+			PacketKind::Ping => {
 				try!(self.send_pong(io));
 				Ok(SessionData::Continue)
 			},
-			PACKET_PONG => {
+			PacketKind::Pong => {
 				self.pong_time_ns = Some(time::precise_time_ns());
 				self.info.ping_ms = Some((self.pong_time_ns.unwrap() - self.ping_time_ns) / 1000_000);
 				Ok(SessionData::Continue)
 			},
-			PACKET_GET_PEERS => Ok(SessionData::None), //TODO;
-			PACKET_PEERS => Ok(SessionData::None),
-			PACKET_USER ... PACKET_LAST => {
+			PacketKind::Rekey => {
+				// keys are switched by the connection as the packet is decrypted
+				match self.state {
+					State::Session(ref c) if c.is_rekey_enabled() => Ok(SessionData::Continue),
+					_ => Err(From::from(NetworkError::BadProtocol)),
+				}
+			},
+			PacketKind::GetPeers => Ok(SessionData::None), //TODO;
+			PacketKind::Peers => Ok(SessionData::None),
+			PacketKind::Capability(packet_id) => {
 				let mut i = 0usize;
 				while packet_id < self.info.capabilities[i].id_offset {
 					i += 1;
@@ -362,7 +431,7 @@ impl Session {
 				let pid = packet_id - self.info.capabilities[i].id_offset;
 				Ok(SessionData::Packet { data: packet.data, protocol: protocol, packet_id: pid } )
 			},
-			_ => {
+			PacketKind::Unknown(packet_id) => {
 				debug!(target: "network", "Unknown packet: {:?}", packet_id);
 				Ok(SessionData::Continue)
 			}
@@ -371,12 +440,24 @@ impl Session {
 
 	fn write_hello<Message>(&mut self, io: &IoContext<Message>, host: &HostInfo) -> Result<(), UtilError> where Message: Send + Sync + Clone {
 		let mut rlp = RlpStream::new();
-		rlp.append_raw(&[PACKET_HELLO as u8], 0);
+		rlp.append_raw(&[PacketKind::Hello.to_raw()], 0);
 		rlp.begin_list(5)
 			.append(&host.protocol_version)
-			.append(&host.client_version)
-			.append(&host.capabilities)
-			.append(&host.local_endpoint.address.port())
+			.append(&host.client_version);
+		match host.rekey_policy() {
+			Some(_) => {
+				let rekey = CapabilityInfo { protocol: REKEY_CAPABILITY, version: REKEY_VERSION, packet_count: 0 };
+				rlp.begin_list(host.capabilities.len() + 1);
+				for c in &host.capabilities {
+					rlp.append(c);
+				}
+				rlp.append(&rekey);
+			},
+			None => {
+				rlp.append(&host.capabilities);
+			},
+		}
+		rlp.append(&host.local_endpoint.address.port())
 			.append(host.id());
 		self.send(io, rlp)
 	}
@@ -431,37 +512,70 @@ impl Session {
 			trace!(target: "network", "Peer protocol version mismatch: {}", protocol);
 			return Err(From::from(self.disconnect(io, DisconnectReason::UselessPeer)));
 		}
+		if let State::Session(ref mut c) = self.state {
+			c.set_protocol_version(cmp::min(protocol, host.protocol_version));
+			c.set_peer_info(PeerInfo {
+				id: id.clone(),
+				client_version: self.info.client_version.clone(),
+				protocol_version: protocol,
+				capabilities: self.info.capabilities.iter().map(|c| (c.protocol.to_owned(), c.version)).collect(),
+			});
+			c.set_ping_intervals(self.info.capabilities.iter()
+				.map(|c| (c.protocol.to_owned(), host.ping_interval(c.protocol).unwrap_or(PING_INTERVAL_SEC)))
+				.collect());
+		}
+		if let Some(policy) = host.rekey_policy() {
+			if peer_caps.iter().any(|c| c.protocol == REKEY_CAPABILITY && c.version == REKEY_VERSION) {
+				if let State::Session(ref mut c) = self.state {
+					trace!(target: "network", "{}: Re-keying enabled", self.info.client_version);
+					c.enable_rekey(policy.clone());
+				}
+			}
+		}
 		self.had_hello = true;
 		Ok(())
 	}
 
 	/// Senf ping packet
 	pub fn send_ping<Message>(&mut self, io: &IoContext<Message>) -> Result<(), UtilError> where Message: Send + Sync + Clone {
-		try!(self.send(io, try!(Session::prepare(PACKET_PING))));
+		try!(self.send(io, try!(Session::prepare(PacketKind::Ping))));
 		self.ping_time_ns = time::precise_time_ns();
 		self.pong_time_ns = None;
 		Ok(())
 	}
 
 	fn send_pong<Message>(&mut self, io: &IoContext<Message>) -> Result<(), UtilError> where Message: Send + Sync + Clone {
-		self.send(io, try!(Session::prepare(PACKET_PONG)))
+		self.send(io, try!(Session::prepare(PacketKind::Pong)))
 	}
 
 	/// Disconnect this session
 	pub fn disconnect<Message>(&mut self, io: &IoContext<Message>, reason: DisconnectReason) -> NetworkError where Message: Send + Sync + Clone {
 		if let State::Session(_) = self.state {
 			let mut rlp = RlpStream::new();
-			rlp.append(&(PACKET_DISCONNECT as u32));
+			rlp.append_raw(&[PacketKind::Disconnect.to_raw()], 0);
 			rlp.begin_list(1);
 			rlp.append(&(reason as u32));
 			self.send(io, rlp).ok();
 		}
+		// Make sure the reason is written out before the connection is closed.
+		if let State::Session(ref mut c) = self.state {
+			c.connection.flush(io).ok();
+		}
 		NetworkError::Disconnect(reason)
 	}
 
-	fn prepare(packet_id: u8) -> Result<RlpStream, UtilError> {
+	/// Try to write out queued data without waiting for the socket to become writable.
+	/// Returns the number of bytes still queued.
+	pub fn flush<Message>(&mut self, io: &IoContext<Message>) -> Result<usize, UtilError> where Message: Send + Sync + Clone {
+		match self.state {
+			State::Handshake(ref mut h) => h.connection.flush(io),
+			State::Session(ref mut s) => s.connection.flush(io),
+		}
+	}
+
+	fn prepare(packet_id: PacketKind) -> Result<RlpStream, UtilError> {
 		let mut rlp = RlpStream::new();
-		rlp.append(&(packet_id as u32));
+		rlp.append_raw(&[packet_id.to_raw()], 0);
 		rlp.begin_list(0);
 		Ok(rlp)
 	}
@@ -479,3 +593,66 @@ impl Session {
 	}
 }
 
+/// Session over an established encrypted connection, e.g. one from `encrypted_pair`.
+#[cfg(test)]
+pub fn test_session(connection: EncryptedConnection) -> Session {
+	Session {
+		info: SessionInfo {
+			id: None,
+			client_version: String::new(),
+			protocol_version: 4,
+			capabilities: Vec::new(),
+			ping_ms: None,
+			originated: false,
+		},
+		had_hello: true,
+		expired: false,
+		ping_time_ns: 0,
+		pong_time_ns: None,
+		state: State::Session(connection),
+		observer: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use time;
+	use io::{IoContext, IoChannel};
+	use network::connection::encrypted_pair;
+	use super::test_session;
+
+	fn test_io() -> IoContext<i32> {
+		IoContext::new(IoChannel::disconnected(), 0)
+	}
+
+	#[test]
+	fn capabilities_use_own_ping_intervals() {
+		let (mut eth, _eth_peer) = encrypted_pair();
+		eth.set_ping_intervals(vec![("eth".to_owned(), 60)]);
+		let (mut shh, _shh_peer) = encrypted_pair();
+		shh.set_ping_intervals(vec![("shh".to_owned(), 5)]);
+		let mut eth = test_session(eth);
+		let mut shh = test_session(shh);
+
+		let last_ping = time::precise_time_ns() - 10 * 1000_000_000;
+		eth.ping_time_ns = last_ping;
+		shh.ping_time_ns = last_ping;
+		assert!(eth.keep_alive(&test_io()));
+		assert!(shh.keep_alive(&test_io()));
+
+		// only the quiet protocol is due a ping after 10 seconds
+		assert_eq!(eth.ping_time_ns, last_ping);
+		assert!(shh.ping_time_ns > last_ping);
+	}
+
+	#[test]
+	fn keepalive_follows_most_frequent_capability() {
+		let (mut connection, _peer) = encrypted_pair();
+		assert_eq!(connection.keepalive_interval(), None);
+		connection.set_ping_intervals(vec![("eth".to_owned(), 60), ("shh".to_owned(), 5)]);
+		assert_eq!(connection.ping_interval("eth"), Some(60));
+		assert_eq!(connection.ping_interval("shh"), Some(5));
+		assert_eq!(connection.ping_interval("bzz"), None);
+		assert_eq!(connection.keepalive_interval(), Some(5));
+	}
+}
@@ -0,0 +1,352 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper protocol handler and message pool.
+
+use std::collections::{HashMap, HashSet, BTreeMap};
+use std::sync::{Arc, RwLock, Mutex};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::thread;
+use util::*;
+use util::crypto::KeyPair;
+use util::network::{NetworkProtocolHandler, NetworkService, NetworkContext, PeerId};
+use message::{Envelope, Message, MessageError, Topic};
+
+/// Supported protocol version.
+pub const PROTOCOL_VERSION: u8 = 2;
+
+const STATUS_PACKET: u8 = 0x00;
+const MESSAGES_PACKET: u8 = 0x01;
+
+const MAINTAIN_TIMER: TimerToken = 0;
+const MAINTAIN_INTERVAL_MS: u64 = 1000;
+/// Time spent searching for proof-of-work when posting a message.
+const SEAL_TIME_MS: u64 = 500;
+/// Maximal number of posted messages waiting for proof-of-work.
+const MAX_QUEUED_POSTS: usize = 16;
+/// Maximal number of envelopes kept in the pool.
+pub const MAX_POOL_SIZE: usize = 4096;
+
+/// Envelopes known to this node, indexed by arrival order.
+struct Pool {
+	envelopes: HashMap<H256, Envelope>,
+	arrivals: BTreeMap<u64, H256>,
+	next_arrival: u64,
+	capacity: usize,
+}
+
+impl Pool {
+	fn new(capacity: usize) -> Self {
+		Pool {
+			envelopes: HashMap::new(),
+			arrivals: BTreeMap::new(),
+			next_arrival: 0,
+			capacity: capacity,
+		}
+	}
+
+	fn insert(&mut self, hash: H256, envelope: Envelope) -> bool {
+		if self.envelopes.contains_key(&hash) || envelope.is_expired() {
+			return false;
+		}
+		if self.envelopes.len() >= self.capacity {
+			self.prune();
+		}
+		// still full, make room by dropping the oldest envelope
+		if self.envelopes.len() >= self.capacity {
+			let oldest = self.arrivals.keys().next().cloned();
+			if let Some(hash) = oldest.and_then(|arrival| self.arrivals.remove(&arrival)) {
+				self.envelopes.remove(&hash);
+			}
+		}
+		self.envelopes.insert(hash.clone(), envelope);
+		self.arrivals.insert(self.next_arrival, hash);
+		self.next_arrival += 1;
+		true
+	}
+
+	fn prune(&mut self) {
+		let expired: Vec<_> = self.envelopes.iter().filter(|&(_, e)| e.is_expired()).map(|(h, _)| h.clone()).collect();
+		if expired.is_empty() {
+			return;
+		}
+		for hash in &expired {
+			self.envelopes.remove(hash);
+		}
+		let envelopes = &self.envelopes;
+		let arrivals = ::std::mem::replace(&mut self.arrivals, BTreeMap::new());
+		self.arrivals = arrivals.into_iter().filter(|&(_, ref h)| envelopes.contains_key(h)).collect();
+	}
+}
+
+/// Whisper host. Keeps the envelope pool and local identities and floods envelopes to connected peers.
+pub struct WhisperHost {
+	/// Proof-of-work required from envelopes, both received and posted.
+	min_work: u32,
+	pool: RwLock<Pool>,
+	identities: RwLock<HashMap<Public, KeyPair>>,
+	/// Envelope hashes known by each peer.
+	peers: RwLock<HashMap<PeerId, HashSet<H256>>>,
+	/// Posted envelopes waiting for proof-of-work, which is done on a dedicated thread.
+	sealing: Mutex<SyncSender<Envelope>>,
+}
+
+impl WhisperHost {
+	/// Creates new whisper host requiring `min_work` bits of proof-of-work.
+	pub fn new(min_work: u32) -> Arc<WhisperHost> {
+		WhisperHost::with_pool_size(min_work, MAX_POOL_SIZE)
+	}
+
+	/// Creates new whisper host requiring `min_work` bits of proof-of-work and keeping at most `pool_size` envelopes.
+	pub fn with_pool_size(min_work: u32, pool_size: usize) -> Arc<WhisperHost> {
+		let (sender, receiver) = sync_channel::<Envelope>(MAX_QUEUED_POSTS);
+		let host = Arc::new(WhisperHost {
+			min_work: min_work,
+			pool: RwLock::new(Pool::new(pool_size)),
+			identities: RwLock::new(HashMap::new()),
+			peers: RwLock::new(HashMap::new()),
+			sealing: Mutex::new(sender),
+		});
+
+		let weak = Arc::downgrade(&host);
+		thread::Builder::new().name("whisper-seal".into()).spawn(move || {
+			// ends once the host, and with it the sender, is dropped
+			for mut envelope in receiver.iter() {
+				let host = match weak.upgrade() {
+					Some(host) => host,
+					None => break,
+				};
+				match envelope.seal(host.min_work, SEAL_TIME_MS) {
+					Ok(()) => {
+						let hash = envelope.hash();
+						host.pool.write().unwrap().insert(hash, envelope);
+					},
+					Err(e) => warn!(target: "whisper", "Could not seal posted message: {:?}", e),
+				}
+			}
+		}).expect("Error starting whisper sealing thread");
+
+		host
+	}
+
+	/// Register protocol with the network service
+	pub fn register<M>(service: &NetworkService<M>, host: Arc<WhisperHost>) -> Result<(), NetworkError> where M: Send + Sync + Clone + 'static {
+		service.register_protocol(host, "shh", &[PROTOCOL_VERSION])
+	}
+
+	/// Generate a new identity and return its public key.
+	pub fn new_identity(&self) -> Result<Public, MessageError> {
+		let keypair = try!(KeyPair::create().map_err(|_| MessageError::Crypto));
+		let public = keypair.public().clone();
+		self.identities.write().unwrap().insert(public.clone(), keypair);
+		Ok(public)
+	}
+
+	/// Check if the identity is known.
+	pub fn has_identity(&self, public: &Public) -> bool {
+		self.identities.read().unwrap().contains_key(public)
+	}
+
+	/// Post a message. The message is signed when `from` is given and encrypted when `to` is given.
+	/// Proof-of-work is done in the background; the message enters the pool once it's sealed.
+	pub fn post(&self, payload: &[u8], from: Option<&Public>, to: Option<&Public>, topics: Vec<Topic>, ttl: u64) -> Result<(), MessageError> {
+		let envelope = {
+			let identities = self.identities.read().unwrap();
+			let from = match from {
+				Some(public) => Some(try!(identities.get(public).ok_or(MessageError::UnknownIdentity))),
+				None => None,
+			};
+			try!(Envelope::compose(payload, from, to, topics, ttl))
+		};
+		match self.sealing.lock().unwrap().try_send(envelope) {
+			Ok(()) => Ok(()),
+			Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => Err(MessageError::Busy),
+		}
+	}
+
+	/// Position in the arrival order of the next envelope.
+	pub fn cursor(&self) -> u64 {
+		self.pool.read().unwrap().next_arrival
+	}
+
+	/// Returns messages which arrived at or after `cursor` and can be opened by this node,
+	/// together with the cursor to use for the next call.
+	/// Only messages carrying all of `topics` and, if given, sent to `to` are returned.
+	pub fn messages(&self, cursor: u64, to: Option<&Public>, topics: &[Topic]) -> (Vec<Message>, u64) {
+		let pool = self.pool.read().unwrap();
+		let identities = self.identities.read().unwrap();
+		let messages = pool.arrivals.iter()
+			.filter(|&(arrival, _)| *arrival >= cursor)
+			.filter_map(|(_, hash)| pool.envelopes.get(hash))
+			.filter(|envelope| envelope.matches(topics))
+			.filter_map(|envelope| envelope.open(&identities))
+			.filter(|message| to.map_or(true, |to| message.to.as_ref() == Some(to)))
+			.collect();
+		(messages, pool.next_arrival)
+	}
+
+	fn on_messages<M>(&self, io: &NetworkContext<M>, peer: &PeerId, data: &[u8]) where M: Send + Sync + Clone {
+		let rlp = UntrustedRlp::new(data);
+		let mut pool = self.pool.write().unwrap();
+		let mut peers = self.peers.write().unwrap();
+		for item in rlp.iter() {
+			let envelope: Envelope = match item.as_val() {
+				Ok(envelope) => envelope,
+				Err(e) => {
+					debug!(target: "whisper", "{}: Bad envelope: {:?}", peer, e);
+					io.disable_peer(*peer);
+					return;
+				}
+			};
+			let hash = envelope.hash();
+			if let Some(known) = peers.get_mut(peer) {
+				known.insert(hash.clone());
+			}
+			if let Err(e) = envelope.validate(self.min_work) {
+				trace!(target: "whisper", "{}: Ignoring envelope {}: {:?}", peer, hash, e);
+				continue;
+			}
+			if pool.insert(hash.clone(), envelope) {
+				trace!(target: "whisper", "{}: New envelope {}", peer, hash);
+			}
+		}
+	}
+
+	/// Drop expired envelopes and send the ones peers don't know about yet.
+	fn propagate<M>(&self, io: &NetworkContext<M>) where M: Send + Sync + Clone {
+		let mut pool = self.pool.write().unwrap();
+		pool.prune();
+		let mut peers = self.peers.write().unwrap();
+		for (peer, known) in peers.iter_mut() {
+			let live = known.iter().filter(|hash| pool.envelopes.contains_key(hash)).cloned().collect();
+			*known = live;
+			let unknown: Vec<_> = pool.envelopes.iter().filter(|&(h, _)| !known.contains(h)).collect();
+			if unknown.is_empty() {
+				continue;
+			}
+			let mut s = RlpStream::new_list(unknown.len());
+			for &(hash, envelope) in &unknown {
+				s.append(envelope);
+				known.insert(hash.clone());
+			}
+			if let Err(e) = io.send(*peer, MESSAGES_PACKET, s.out()) {
+				debug!(target: "whisper", "{}: Error sending envelopes: {:?}", peer, e);
+			}
+		}
+	}
+}
+
+impl<M> NetworkProtocolHandler<M> for WhisperHost where M: Send + Sync + Clone + 'static {
+	fn initialize(&self, io: &NetworkContext<M>) {
+		io.register_timer(MAINTAIN_TIMER, MAINTAIN_INTERVAL_MS).expect("Error registering whisper timer");
+	}
+
+	fn read(&self, io: &NetworkContext<M>, peer: &PeerId, packet_id: u8, data: &[u8]) {
+		match packet_id {
+			STATUS_PACKET => {
+				let version: Result<u8, _> = UntrustedRlp::new(data).val_at(0);
+				trace!(target: "whisper", "{}: Status, version {:?}", peer, version);
+			},
+			MESSAGES_PACKET => self.on_messages(io, peer, data),
+			_ => debug!(target: "whisper", "{}: Unknown packet {}", peer, packet_id),
+		}
+	}
+
+	fn connected(&self, io: &NetworkContext<M>, peer: &PeerId) {
+		trace!(target: "whisper", "Connected {}", peer);
+		self.peers.write().unwrap().insert(*peer, HashSet::new());
+		let mut s = RlpStream::new_list(1);
+		s.append(&PROTOCOL_VERSION);
+		if let Err(e) = io.send(*peer, STATUS_PACKET, s.out()) {
+			debug!(target: "whisper", "{}: Error sending status: {:?}", peer, e);
+		}
+	}
+
+	fn disconnected(&self, _io: &NetworkContext<M>, peer: &PeerId) {
+		trace!(target: "whisper", "Disconnected {}", peer);
+		self.peers.write().unwrap().remove(peer);
+	}
+
+	fn timeout(&self, io: &NetworkContext<M>, _timer: TimerToken) {
+		self.propagate(io);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::thread;
+	use std::time::{Duration, Instant};
+	use util::*;
+	use message::{abridge_topic, Envelope, MessageError};
+	use super::*;
+
+	/// Waits for posted messages to be sealed and enter the pool.
+	fn wait_for_pool(host: &WhisperHost, cursor: u64) {
+		let deadline = Instant::now() + Duration::from_secs(30);
+		while host.cursor() < cursor {
+			assert!(Instant::now() < deadline, "posted messages were not sealed");
+			thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	#[test]
+	fn posts_and_filters_messages() {
+		let host = WhisperHost::new(1);
+		let cursor = host.cursor();
+		let topic = abridge_topic(b"test");
+
+		host.post(b"hello", None, None, vec![topic.clone()], 50).unwrap();
+		host.post(b"other", None, None, vec![abridge_topic(b"other")], 50).unwrap();
+		wait_for_pool(&host, cursor + 2);
+
+		let (messages, next) = host.messages(cursor, None, &[topic]);
+		assert_eq!(messages.len(), 1);
+		assert_eq!(messages[0].payload, b"hello".to_vec());
+		assert_eq!(next, cursor + 2);
+		assert!(host.messages(next, None, &[]).0.is_empty());
+	}
+
+	#[test]
+	fn opens_messages_for_own_identities() {
+		let host = WhisperHost::new(1);
+		let me = host.new_identity().unwrap();
+		let stranger = Public::from(1);
+
+		host.post(b"to me", Some(&me), Some(&me), vec![], 50).unwrap();
+		assert_eq!(host.post(b"hello", Some(&stranger), None, vec![], 50), Err(MessageError::UnknownIdentity));
+		wait_for_pool(&host, 1);
+
+		let (messages, _) = host.messages(0, Some(&me), &[]);
+		assert_eq!(messages.len(), 1);
+		assert_eq!(messages[0].from, Some(me));
+	}
+
+	#[test]
+	fn drops_oldest_envelopes_when_pool_is_full() {
+		let host = WhisperHost::with_pool_size(1, 2);
+		let envelopes = (0..3u8).map(|i| Envelope::compose(&[i], None, None, vec![], 50).unwrap()).collect::<Vec<_>>();
+		{
+			let mut pool = host.pool.write().unwrap();
+			for envelope in &envelopes {
+				assert!(pool.insert(envelope.hash(), envelope.clone()));
+			}
+		}
+
+		let (messages, next) = host.messages(0, None, &[]);
+		assert_eq!(next, 3);
+		assert_eq!(messages.iter().map(|m| m.payload.clone()).collect::<Vec<_>>(), vec![vec![1u8], vec![2u8]]);
+	}
+}
@@ -0,0 +1,37 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+#![warn(missing_docs)]
+#![cfg_attr(feature="dev", feature(plugin))]
+#![cfg_attr(feature="dev", plugin(clippy))]
+
+//! Whisper messaging.
+//! Implements the `shh` sub-protocol as described here:
+//! https://github.com/ethereum/wiki/wiki/Whisper-Wire-Protocol
+//!
+//! Envelopes are flooded to all peers supporting the protocol until they expire.
+//! Each envelope has to carry a minimal amount of proof-of-work to be accepted.
+
+#[macro_use]
+extern crate log;
+extern crate ethcore_util as util;
+extern crate time;
+
+mod message;
+mod host;
+
+pub use self::message::{Envelope, Message, Topic, MessageError, abridge_topic, MAX_TTL, DEFAULT_WORK};
+pub use self::host::{WhisperHost, PROTOCOL_VERSION, MAX_POOL_SIZE};
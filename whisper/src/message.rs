@@ -0,0 +1,348 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Whisper envelopes and messages.
+
+use std::collections::HashMap;
+use util::*;
+use util::crypto::{KeyPair, ec, ecies};
+use time;
+
+/// Abridged topic.
+pub type Topic = H32;
+
+/// Maximal time to live of an envelope in seconds.
+pub const MAX_TTL: u64 = 2 * 24 * 60 * 60;
+/// Proof-of-work (number of leading zero bits) required by default.
+pub const DEFAULT_WORK: u32 = 8;
+/// Allowed clock difference between peers in seconds.
+const EXPIRY_SLACK: u64 = 30;
+
+/// Payload is not encrypted.
+const FLAG_PLAIN: u8 = 0;
+/// Payload is encrypted to the recipient's public key.
+const FLAG_ENCRYPTED: u8 = 1;
+
+/// Envelope composition and validation errors.
+#[derive(Debug, PartialEq)]
+pub enum MessageError {
+	/// Time to live is zero or exceeds `MAX_TTL`.
+	InvalidTtl(u64),
+	/// Envelope has already expired.
+	Expired,
+	/// Envelope expires further in the future than its time to live allows.
+	InvalidExpiry,
+	/// Not enough proof-of-work.
+	InsufficientWork(u32),
+	/// Message could not be signed or encrypted.
+	Crypto,
+	/// Sender identity is not known to this node.
+	UnknownIdentity,
+	/// Too many posted messages are waiting for proof-of-work.
+	Busy,
+}
+
+/// Returns topic abridged to 4 bytes.
+pub fn abridge_topic(topic: &[u8]) -> Topic {
+	Topic::from_slice(&topic.sha3()[0..4])
+}
+
+fn now() -> u64 {
+	time::get_time().sec as u64
+}
+
+/// Whisper envelope as it is sent over the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+	/// Unix time in seconds at which the envelope expires.
+	pub expiry: u64,
+	/// Time to live in seconds.
+	pub ttl: u64,
+	/// Abridged topics.
+	pub topics: Vec<Topic>,
+	/// Message data, possibly encrypted.
+	pub data: Bytes,
+	/// Proof-of-work nonce.
+	pub nonce: u64,
+}
+
+impl Envelope {
+	/// Compose an envelope for `payload`. Signs the payload with `from` and encrypts it to `to` when given.
+	/// The returned envelope still needs to be sealed.
+	pub fn compose(payload: &[u8], from: Option<&KeyPair>, to: Option<&Public>, topics: Vec<Topic>, ttl: u64) -> Result<Envelope, MessageError> {
+		if ttl == 0 || ttl > MAX_TTL {
+			return Err(MessageError::InvalidTtl(ttl));
+		}
+		let signature = match from {
+			Some(keypair) => try!(keypair.sign(&payload.sha3()).map_err(|_| MessageError::Crypto)).to_vec(),
+			None => Vec::new(),
+		};
+		let mut s = RlpStream::new_list(2);
+		s.append(&payload.to_vec());
+		s.append(&signature);
+		let body = s.out();
+
+		let mut data = Vec::with_capacity(body.len() + 1);
+		match to {
+			Some(public) => {
+				data.push(FLAG_ENCRYPTED);
+				data.extend(try!(ecies::encrypt(public, &[], &body).map_err(|_| MessageError::Crypto)));
+			},
+			None => {
+				data.push(FLAG_PLAIN);
+				data.extend(body);
+			},
+		}
+
+		Ok(Envelope {
+			expiry: now() + ttl,
+			ttl: ttl,
+			topics: topics,
+			data: data,
+			nonce: 0,
+		})
+	}
+
+	/// Hash of the envelope without the nonce.
+	pub fn bare_hash(&self) -> H256 {
+		let mut s = RlpStream::new_list(4);
+		s.append(&self.expiry);
+		s.append(&self.ttl);
+		s.append(&self.topics);
+		s.append(&self.data);
+		s.out().sha3()
+	}
+
+	/// Envelope hash, used to identify the envelope.
+	pub fn hash(&self) -> H256 {
+		encode(self).to_vec().sha3()
+	}
+
+	/// Proof-of-work: number of leading zero bits of `sha3(bare_hash ++ nonce)`.
+	pub fn work(&self) -> u32 {
+		Self::work_for(&self.bare_hash(), self.nonce)
+	}
+
+	fn work_for(bare_hash: &H256, nonce: u64) -> u32 {
+		let mut buf = [0u8; 40];
+		buf[0..32].clone_from_slice(bare_hash);
+		for i in 0..8 {
+			buf[32 + i] = (nonce >> (8 * (7 - i))) as u8;
+		}
+		let hash = (&buf[..]).sha3();
+		let mut zeros = 0;
+		for byte in hash.iter() {
+			if *byte == 0 {
+				zeros += 8;
+			} else {
+				zeros += byte.leading_zeros();
+				break;
+			}
+		}
+		zeros
+	}
+
+	/// Search for a nonce providing at least `work` bits of proof-of-work, giving up after `max_ms` milliseconds.
+	pub fn seal(&mut self, work: u32, max_ms: u64) -> Result<(), MessageError> {
+		let bare_hash = self.bare_hash();
+		let deadline = time::precise_time_ns() + max_ms * 1_000_000;
+		let mut nonce = 0u64;
+		loop {
+			if Self::work_for(&bare_hash, nonce) >= work {
+				self.nonce = nonce;
+				return Ok(());
+			}
+			nonce += 1;
+			// checking time is expensive compared to hashing
+			if nonce % 1024 == 0 && time::precise_time_ns() > deadline {
+				return Err(MessageError::InsufficientWork(work));
+			}
+		}
+	}
+
+	/// Check if the envelope has expired.
+	pub fn is_expired(&self) -> bool {
+		self.expiry <= now()
+	}
+
+	/// Validate an envelope received from the network.
+	pub fn validate(&self, min_work: u32) -> Result<(), MessageError> {
+		let now = now();
+		if self.ttl == 0 || self.ttl > MAX_TTL {
+			return Err(MessageError::InvalidTtl(self.ttl));
+		}
+		if self.expiry <= now {
+			return Err(MessageError::Expired);
+		}
+		if self.expiry > now + self.ttl + EXPIRY_SLACK {
+			return Err(MessageError::InvalidExpiry);
+		}
+		let work = self.work();
+		if work < min_work {
+			return Err(MessageError::InsufficientWork(work));
+		}
+		Ok(())
+	}
+
+	/// Check if the envelope carries all of the given topics.
+	pub fn matches(&self, topics: &[Topic]) -> bool {
+		topics.iter().all(|t| self.topics.contains(t))
+	}
+
+	/// Try to open the envelope. Encrypted envelopes are opened with the first identity that can decrypt them.
+	pub fn open(&self, identities: &HashMap<Public, KeyPair>) -> Option<Message> {
+		let (body, to) = match self.data.first() {
+			Some(&FLAG_PLAIN) => (self.data[1..].to_vec(), None),
+			Some(&FLAG_ENCRYPTED) => {
+				match identities.iter().filter_map(|(public, keypair)| ecies::decrypt(keypair.secret(), &[], &self.data[1..]).ok().map(|body| (body, public.clone()))).next() {
+					Some((body, public)) => (body, Some(public)),
+					None => return None,
+				}
+			},
+			_ => return None,
+		};
+
+		let rlp = UntrustedRlp::new(&body);
+		let payload: Bytes = match rlp.val_at(0) {
+			Ok(payload) => payload,
+			Err(_) => return None,
+		};
+		let signature: Bytes = match rlp.val_at(1) {
+			Ok(signature) => signature,
+			Err(_) => return None,
+		};
+		let from = match signature.len() {
+			65 => match ec::recover(&Signature::from_slice(&signature), &payload.sha3()) {
+				Ok(public) => Some(public),
+				Err(_) => return None,
+			},
+			_ => None,
+		};
+
+		Some(Message {
+			hash: self.hash(),
+			from: from,
+			to: to,
+			expiry: self.expiry,
+			ttl: self.ttl,
+			topics: self.topics.clone(),
+			payload: payload,
+			work: self.work(),
+		})
+	}
+}
+
+impl Encodable for Envelope {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(5);
+		s.append(&self.expiry);
+		s.append(&self.ttl);
+		s.append(&self.topics);
+		s.append(&self.data);
+		s.append(&self.nonce);
+	}
+}
+
+impl Decodable for Envelope {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let d = decoder.as_rlp();
+		let envelope = Envelope {
+			expiry: try!(d.val_at(0)),
+			ttl: try!(d.val_at(1)),
+			topics: try!(d.val_at(2)),
+			data: try!(d.val_at(3)),
+			nonce: try!(d.val_at(4)),
+		};
+		Ok(envelope)
+	}
+}
+
+/// Opened whisper message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+	/// Hash of the envelope carrying the message.
+	pub hash: H256,
+	/// Sender, if the message was signed.
+	pub from: Option<Public>,
+	/// Recipient, if the message was encrypted.
+	pub to: Option<Public>,
+	/// Unix time in seconds at which the message expires.
+	pub expiry: u64,
+	/// Time to live in seconds.
+	pub ttl: u64,
+	/// Abridged topics.
+	pub topics: Vec<Topic>,
+	/// Message payload.
+	pub payload: Bytes,
+	/// Proof-of-work of the envelope.
+	pub work: u32,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use util::*;
+	use util::crypto::KeyPair;
+	use super::*;
+
+	#[test]
+	fn envelope_rlp_roundtrip() {
+		let envelope = Envelope::compose(b"hello", None, None, vec![abridge_topic(b"test")], 50).unwrap();
+		let decoded: Envelope = decode(&encode(&envelope));
+		assert_eq!(envelope, decoded);
+	}
+
+	#[test]
+	fn seals_envelope() {
+		let mut envelope = Envelope::compose(b"hello", None, None, vec![], 50).unwrap();
+		envelope.seal(DEFAULT_WORK, 10_000).unwrap();
+		assert!(envelope.work() >= DEFAULT_WORK);
+		assert_eq!(envelope.validate(DEFAULT_WORK), Ok(()));
+	}
+
+	#[test]
+	fn rejects_invalid_envelopes() {
+		assert_eq!(Envelope::compose(b"hello", None, None, vec![], MAX_TTL + 1), Err(MessageError::InvalidTtl(MAX_TTL + 1)));
+
+		let mut envelope = Envelope::compose(b"hello", None, None, vec![], 50).unwrap();
+		envelope.expiry = 1;
+		assert_eq!(envelope.validate(0), Err(MessageError::Expired));
+
+		let mut envelope = Envelope::compose(b"hello", None, None, vec![], 50).unwrap();
+		envelope.expiry += 3600;
+		assert_eq!(envelope.validate(0), Err(MessageError::InvalidExpiry));
+	}
+
+	#[test]
+	fn opens_signed_encrypted_message() {
+		let sender = KeyPair::create().unwrap();
+		let recipient = KeyPair::create().unwrap();
+		let topics = vec![abridge_topic(b"test")];
+		let envelope = Envelope::compose(b"hello", Some(&sender), Some(recipient.public()), topics.clone(), 50).unwrap();
+
+		assert_eq!(envelope.open(&HashMap::new()), None);
+
+		let mut identities = HashMap::new();
+		identities.insert(recipient.public().clone(), KeyPair::from_secret(recipient.secret().clone()).unwrap());
+		let message = envelope.open(&identities).unwrap();
+		assert_eq!(message.payload, b"hello".to_vec());
+		assert_eq!(message.from.as_ref(), Some(sender.public()));
+		assert_eq!(message.to.as_ref(), Some(recipient.public()));
+		assert_eq!(message.topics, topics);
+		assert!(envelope.matches(&topics));
+		assert!(!envelope.matches(&[abridge_topic(b"other")]));
+	}
+}